@@ -1,5 +1,3 @@
-#![feature(duration_as_u128)]
-
 extern crate rand;
 extern crate sm;
 
@@ -112,6 +110,7 @@ struct TestTunables {
     rng: rand::ThreadRng,
 }
 
+#[allow(unused_assignments)]
 fn main() {
     let config = Config {
         fixed_updates_per_second: 100,
@@ -223,13 +222,11 @@ fn main() {
     // shutdown. In this example, we loop for a fixed amount of cycles, and then
     // terminate the example.
     let mut i = 0;
-    let max_count: u32;
-
-    if tunables.min_ticks == tunables.max_ticks {
-        max_count = tunables.min_ticks;
+    let max_count = if tunables.min_ticks == tunables.max_ticks {
+        tunables.min_ticks
     } else {
-        max_count = Uniform::from(tunables.min_ticks..tunables.max_ticks).sample(&mut tunables.rng);
-    }
+        Uniform::from(tunables.min_ticks..tunables.max_ticks).sample(&mut tunables.rng)
+    };
 
     let mut total_duration = Duration::from_secs(0);
     let mut game_is_running = true;
@@ -341,13 +338,11 @@ fn handle_idle(count: &mut u32, i: &mut u32) {
 fn handle_update(count: &mut u32, tunables: &mut TestTunables) {
     *count += 1;
 
-    let n: u64;
-    if tunables.update_min_duration == tunables.update_max_duration {
-        n = tunables.update_min_duration;
+    let n = if tunables.update_min_duration == tunables.update_max_duration {
+        tunables.update_min_duration
     } else {
-        n = Uniform::from(tunables.update_min_duration..tunables.update_max_duration)
-            .sample(&mut tunables.rng);
-    }
+        Uniform::from(tunables.update_min_duration..tunables.update_max_duration).sample(&mut tunables.rng)
+    };
 
     // Simulate game update duration...
     std::thread::sleep(Duration::from_millis(n));
@@ -356,13 +351,11 @@ fn handle_update(count: &mut u32, tunables: &mut TestTunables) {
 fn handle_render(count: &mut u32, tunables: &mut TestTunables) {
     *count += 1;
 
-    let n: u64;
-    if tunables.render_min_duration == tunables.render_max_duration {
-        n = tunables.render_min_duration;
+    let n = if tunables.render_min_duration == tunables.render_max_duration {
+        tunables.render_min_duration
     } else {
-        n = Uniform::from(tunables.render_min_duration..tunables.render_max_duration)
-            .sample(&mut tunables.rng);
-    }
+        Uniform::from(tunables.render_min_duration..tunables.render_max_duration).sample(&mut tunables.rng)
+    };
 
     // Simulate frame rendering duration...
     std::thread::sleep(Duration::from_millis(n));
@@ -0,0 +1,116 @@
+//! Share the state of a machine between interrupt handlers and the main loop.
+//!
+//! A `StateCell` stores the state of a machine as an atomic `u8`, and applies
+//! events using a compare-and-swap loop, validated against the transition
+//! table of the machine. No critical section is required, but the target has
+//! to support atomic compare-and-swap operations on bytes (for example, ARMv7-M
+//! does, ARMv6-M does not). As states are stored in a byte, machines with more
+//! than 256 states can't be stored in a `StateCell`.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::atomic::StateCell;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!
+//!         Break {
+//!             Locked => Broken
+//!         }
+//!     }
+//! }
+//!
+//! static LOCK: StateCell<Lock::Variant> = StateCell::from_index(0);
+//!
+//! fn main() {
+//!     use Lock::{EventId, StateId};
+//!
+//!     assert_eq!(LOCK.state(), StateId::Locked);
+//!     assert_eq!(LOCK.transition(EventId::TurnKey), Ok(StateId::Unlocked));
+//!     assert_eq!(LOCK.transition(EventId::Break), Err(StateId::Unlocked));
+//!     assert_eq!(LOCK.state(), StateId::Unlocked);
+//! }
+//! ```
+
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicU8, Ordering};
+
+use crate::{Id, Table};
+
+/// StateCell holds the current state of a machine, and allows it to be read
+/// and transitioned from multiple execution contexts at once.
+#[derive(Debug)]
+pub struct StateCell<T> {
+    state: AtomicU8,
+    table: PhantomData<T>,
+}
+
+impl<T> StateCell<T> {
+    /// from_index creates a new cell from the index of a state in the `ALL`
+    /// constant of the `StateId` type of the machine. Contrary to `new`, this
+    /// can be used to initialise a `static` cell.
+    ///
+    /// Using an index that is out of bounds causes `state` and `transition` to
+    /// panic.
+    pub const fn from_index(index: u8) -> Self {
+        StateCell {
+            state: AtomicU8::new(index),
+            table: PhantomData,
+        }
+    }
+}
+
+impl<T: Table> StateCell<T> {
+    /// new creates a new cell in the provided state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index of the state doesn't fit in a `u8`.
+    pub fn new(state: T::StateId) -> Self {
+        Self::from_index(Self::byte(state))
+    }
+
+    /// state returns the current state stored in the cell.
+    pub fn state(&self) -> T::StateId {
+        T::StateId::ALL[self.state.load(Ordering::Acquire) as usize]
+    }
+
+    /// transition atomically applies the event to the stored state, and
+    /// returns the new state. If the event has no valid transition from the
+    /// current state, the cell is left untouched, and the current state is
+    /// returned as the error.
+    pub fn transition(&self, event: T::EventId) -> Result<T::StateId, T::StateId> {
+        let mut current = self.state.load(Ordering::Acquire);
+
+        loop {
+            let state = T::StateId::ALL[current as usize];
+            let next = match T::next(state, event) {
+                Some(next) => next,
+                None => return Err(state),
+            };
+
+            match self.state.compare_exchange_weak(
+                current,
+                Self::byte(next),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(next),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    fn byte(state: T::StateId) -> u8 {
+        u8::try_from(state.index()).expect("StateCell only supports machines with up to 256 states")
+    }
+}
@@ -0,0 +1,148 @@
+//! Keep an audit log of the transitions of a machine, as JSON lines.
+//!
+//! `JsonLog` wraps a machine and a writer. Every event applied through it that
+//! causes a transition is written to the writer as a single line of JSON,
+//! before the transition is committed, so the machine never moves without a
+//! record of it:
+//!
+//! ```text
+//! {"timestamp":1700000000000,"machine":"Lock","from":"Locked","event":"TurnKey","to":"Unlocked","step":0}
+//! ```
+//!
+//! The timestamp is the number of milliseconds since the Unix epoch, and the
+//! step is the position of the transition in the log, starting at zero.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::audit::JsonLog;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut log = JsonLog::new(Machine::new(Locked).as_enum(), Vec::new());
+//!     log.apply(EventId::TurnKey).unwrap();
+//!
+//!     let (sm, lines) = log.into_parts();
+//!     assert_eq!(sm.state_id(), StateId::Unlocked);
+//!
+//!     let lines = String::from_utf8(lines).unwrap();
+//!     assert!(lines.contains(r#""machine":"Lock","from":"Locked","event":"TurnKey","to":"Unlocked","step":0}"#));
+//! }
+//! ```
+
+use core::fmt;
+use std::io::{self, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::{Id, Table, TransitionError, TryTransition};
+
+/// Error is returned by `JsonLog::apply` when an event cannot be applied.
+#[derive(Debug)]
+pub enum Error<S, E> {
+    /// Transition is returned when the event has no valid transition from the
+    /// current state of the machine.
+    Transition(TransitionError<S, E>),
+
+    /// Io is returned when the transition could not be written to the log. The
+    /// machine is left untouched.
+    Io(io::Error),
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for Error<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Transition(err) => err.fmt(f),
+            Error::Io(err) => write!(f, "writing the audit log failed: {}", err),
+        }
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for Error<S, E> {}
+
+/// JsonLog applies events to a machine, and writes every transition to a
+/// writer as a line of JSON.
+#[derive(Debug)]
+pub struct JsonLog<V, W> {
+    machine: V,
+    writer: W,
+    step: u64,
+}
+
+impl<V, W> JsonLog<V, W> {
+    /// new wraps the provided machine, logging its transitions to the provided
+    /// writer.
+    pub fn new(machine: V, writer: W) -> Self {
+        JsonLog {
+            machine,
+            writer,
+            step: 0,
+        }
+    }
+
+    /// machine returns a reference to the current machine.
+    pub fn machine(&self) -> &V {
+        &self.machine
+    }
+
+    /// writer returns a reference to the writer.
+    pub fn writer(&self) -> &W {
+        &self.writer
+    }
+
+    /// into_parts consumes the log, and returns the current machine and the
+    /// writer.
+    pub fn into_parts(self) -> (V, W) {
+        (self.machine, self.writer)
+    }
+}
+
+impl<V, W> JsonLog<V, W>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+    W: Write,
+{
+    /// apply applies the event to the machine, and logs the transition. If the
+    /// event has no valid transition, nothing is logged.
+    pub fn apply(&mut self, event: V::EventId) -> Result<(), Error<V::StateId, V::EventId>> {
+        let from = self.machine.state();
+        let prepared = match self.machine.prepare(event) {
+            Some(prepared) => prepared,
+            None => return Err(Error::Transition(TransitionError::Invalid { state: from, event })),
+        };
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis())
+            .unwrap_or(0);
+
+        writeln!(
+            self.writer,
+            r#"{{"timestamp":{},"machine":"{}","from":"{}","event":"{}","to":"{}","step":{}}}"#,
+            timestamp,
+            V::NAME,
+            from.name(),
+            event.name(),
+            prepared.next().state().name(),
+            self.step,
+        )
+        .map_err(Error::Io)?;
+
+        let _ = prepared.commit();
+        self.step += 1;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,574 @@
+//! Assemble machines at runtime, without the macro.
+//!
+//! The `sm!` macro needs the machine to be known at compile time. Tools that
+//! read their machines from user configuration can describe them using a
+//! `MachineBuilder` instead, which validates the description and produces a
+//! `DynMachine`. States and events of a `DynMachine` are plain strings, and
+//! transitions can be guarded by a closure that decides, given the machine,
+//! whether the transition is allowed. `DynMachine::report` tells which guards
+//! keep the machine from accepting its events.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::builder::MachineBuilder;
+//!
+//! fn main() {
+//!     let mut builder = MachineBuilder::new();
+//!     builder.state("Locked").state("Unlocked").state("Broken");
+//!     builder.initial("Locked");
+//!     builder.on("TurnKey").from("Locked").to("Unlocked");
+//!     builder.on("TurnKey").from("Unlocked").to("Locked");
+//!     builder
+//!         .on("Break")
+//!         .from("Locked")
+//!         .from("Unlocked")
+//!         .to("Broken")
+//!         .guard(|sm| sm.trigger() == Some("TurnKey"));
+//!
+//!     let mut sm = builder.build().unwrap();
+//!     assert_eq!(sm.state(), "Locked");
+//!     assert!(sm.apply("Break").is_err());
+//!
+//!     sm.apply("TurnKey").unwrap();
+//!     assert_eq!(sm.state(), "Unlocked");
+//!
+//!     sm.apply("Break").unwrap();
+//!     assert_eq!(sm.state(), "Broken");
+//!     assert!(sm.is_terminal());
+//! }
+//! ```
+//!
+//! With the `persist` feature enabled, a machine generated by the `sm!` macro
+//! converts into a `DynMachine` with the same states, events and transitions,
+//! in the same state and with the same trigger, using
+//! `DynMachine::from_typed`, and back using `DynMachine::to_typed`, so a
+//! machine can be handled by name at the boundaries of a system, such as an RPC
+//! or a store, and by type inside of it.
+
+use std::error;
+use std::fmt;
+use std::rc::Rc;
+use std::string::{String, ToString};
+use std::vec::Vec;
+
+#[cfg(feature = "persist")]
+use crate::persist::{Restore, Snapshot};
+use crate::TransitionError;
+#[cfg(feature = "persist")]
+use crate::Id;
+
+/// MachineBuilder describes a machine, to be validated and built into a
+/// `DynMachine`.
+#[derive(Debug, Default)]
+pub struct MachineBuilder {
+    states: Vec<String>,
+    initial: Vec<String>,
+    transitions: Vec<Rule>,
+}
+
+impl MachineBuilder {
+    /// new creates a builder without any states or transitions.
+    pub fn new() -> Self {
+        MachineBuilder::default()
+    }
+
+    /// state declares a state of the machine.
+    pub fn state(&mut self, name: &str) -> &mut Self {
+        if !self.states.iter().any(|s| s == name) {
+            self.states.push(name.to_string());
+        }
+
+        self
+    }
+
+    /// initial marks a declared state as an initial state of the machine. The
+    /// first initial state is the state the built machine starts in.
+    pub fn initial(&mut self, name: &str) -> &mut Self {
+        if !self.initial.iter().any(|s| s == name) {
+            self.initial.push(name.to_string());
+        }
+
+        self
+    }
+
+    /// on starts describing the transitions triggered by the given event.
+    pub fn on(&mut self, event: &str) -> On<'_> {
+        On {
+            builder: self,
+            event: event.to_string(),
+            from: Vec::new(),
+        }
+    }
+
+    /// build validates the description, and returns a machine in its first
+    /// initial state.
+    ///
+    /// Every state used as an initial state, or by a transition, has to be
+    /// declared, at least one initial state is required, and a transition
+    /// can't follow an unguarded transition for the same state and event, as
+    /// it would never be taken.
+    pub fn build(self) -> Result<DynMachine, BuildError> {
+        let MachineBuilder {
+            states,
+            initial: initial_states,
+            transitions: rules,
+        } = self;
+
+        let index = |name: &str| {
+            states
+                .iter()
+                .position(|s| s == name)
+                .ok_or_else(|| BuildError::UnknownState(name.to_string()))
+        };
+
+        let mut initial = Vec::new();
+        for name in &initial_states {
+            initial.push(index(name)?);
+        }
+
+        if initial.is_empty() {
+            return Err(BuildError::NoInitialState);
+        }
+
+        let mut events: Vec<String> = Vec::new();
+        let mut transitions: Vec<DynTransition> = Vec::new();
+
+        for rule in rules {
+            let event = match events.iter().position(|e| *e == rule.event) {
+                Some(event) => event,
+                None => {
+                    events.push(rule.event.clone());
+                    events.len() - 1
+                }
+            };
+
+            let to = index(&rule.to)?;
+
+            for name in &rule.from {
+                let from = index(name)?;
+
+                if transitions
+                    .iter()
+                    .any(|t| t.from == from && t.event == event && t.guard.is_none())
+                {
+                    return Err(BuildError::Unreachable {
+                        state: name.clone(),
+                        event: rule.event.clone(),
+                    });
+                }
+
+                transitions.push(DynTransition {
+                    from,
+                    event,
+                    to,
+                    guard: rule.guard.as_ref().map(|g| g.share()),
+                });
+            }
+        }
+
+        Ok(DynMachine {
+            state: initial[0],
+            trigger: None,
+            states,
+            events,
+            initial,
+            transitions,
+        })
+    }
+}
+
+/// On describes the transitions triggered by an event, see
+/// `MachineBuilder::on`.
+#[derive(Debug)]
+pub struct On<'a> {
+    builder: &'a mut MachineBuilder,
+    event: String,
+    from: Vec<String>,
+}
+
+impl<'a> On<'a> {
+    /// from adds a state the event transitions from.
+    pub fn from(mut self, state: &str) -> Self {
+        self.from.push(state.to_string());
+        self
+    }
+
+    /// to sets the state the event transitions to, and adds the transitions
+    /// to the builder.
+    pub fn to(self, state: &str) -> To<'a> {
+        self.builder.transitions.push(Rule {
+            event: self.event,
+            from: self.from,
+            to: state.to_string(),
+            guard: None,
+        });
+
+        To {
+            rule: self.builder.transitions.last_mut().unwrap(),
+        }
+    }
+}
+
+/// To allows guarding the transitions just added by `On::to`.
+#[derive(Debug)]
+pub struct To<'a> {
+    rule: &'a mut Rule,
+}
+
+impl To<'_> {
+    /// guard only allows the transitions when the given closure returns
+    /// `true` for the machine about to transition. The machine is still in
+    /// the state the transition starts from, so a guard shared by transitions
+    /// from several states can branch on `DynMachine::state`:
+    ///
+    /// ```rust
+    /// extern crate sm;
+    /// use sm::builder::MachineBuilder;
+    ///
+    /// fn main() {
+    ///     let mut builder = MachineBuilder::new();
+    ///     builder.state("Locked").state("Unlocked").state("Broken");
+    ///     builder.initial("Locked");
+    ///     builder.on("TurnKey").from("Locked").to("Unlocked");
+    ///     builder
+    ///         .on("Break")
+    ///         .from("Locked")
+    ///         .from("Unlocked")
+    ///         .to("Broken")
+    ///         .guard(|sm| sm.state() == "Unlocked");
+    ///
+    ///     let mut sm = builder.build().unwrap();
+    ///     assert!(!sm.can("Break"));
+    ///
+    ///     sm.apply("TurnKey").unwrap();
+    ///     assert!(sm.can("Break"));
+    /// }
+    /// ```
+    pub fn guard<F: Fn(&DynMachine) -> bool + 'static>(self, guard: F) {
+        self.rule.guard = Some(Guard(Rc::new(guard)));
+    }
+}
+
+/// BuildError is returned when a `MachineBuilder` describes an invalid
+/// machine.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BuildError {
+    /// UnknownState is returned when a state is used without being declared.
+    UnknownState(String),
+
+    /// NoInitialState is returned when no initial state is marked.
+    NoInitialState,
+
+    /// Unreachable is returned when a transition follows an unguarded
+    /// transition for the same state and event.
+    Unreachable {
+        /// state is the state the transitions start from.
+        state: String,
+
+        /// event is the event triggering the transitions.
+        event: String,
+    },
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::UnknownState(state) => write!(f, "state {:?} is not declared", state),
+            BuildError::NoInitialState => write!(f, "no initial state is marked"),
+            BuildError::Unreachable { state, event } => write!(
+                f,
+                "transition from {:?} on {:?} follows an unguarded transition",
+                state, event
+            ),
+        }
+    }
+}
+
+impl error::Error for BuildError {}
+
+/// DynMachine is a machine built at runtime by a `MachineBuilder`.
+#[derive(Debug)]
+pub struct DynMachine {
+    state: usize,
+    trigger: Option<usize>,
+    states: Vec<String>,
+    events: Vec<String>,
+    initial: Vec<usize>,
+    transitions: Vec<DynTransition>,
+}
+
+impl DynMachine {
+    /// state returns the current state of the machine.
+    pub fn state(&self) -> &str {
+        &self.states[self.state]
+    }
+
+    /// trigger returns the event that triggered the last transition, if any.
+    pub fn trigger(&self) -> Option<&str> {
+        self.trigger.map(|e| &*self.events[e])
+    }
+
+    /// states returns all states of the machine, in declaration order.
+    pub fn states(&self) -> impl Iterator<Item = &str> {
+        self.states.iter().map(|s| &**s)
+    }
+
+    /// events returns all events of the machine, in the order they were
+    /// first used.
+    pub fn events(&self) -> impl Iterator<Item = &str> {
+        self.events.iter().map(|e| &**e)
+    }
+
+    /// is_initial returns whether the given state is an initial state.
+    pub fn is_initial(&self, state: &str) -> bool {
+        self.initial.iter().any(|&s| self.states[s] == state)
+    }
+
+    /// is_terminal returns whether the current state has no outgoing
+    /// transitions.
+    pub fn is_terminal(&self) -> bool {
+        !self.transitions.iter().any(|t| t.from == self.state)
+    }
+
+    /// reset moves the machine back to the given initial state, clearing its
+    /// trigger. It returns `false`, leaving the machine unchanged, if the
+    /// state is not an initial state.
+    pub fn reset(&mut self, state: &str) -> bool {
+        match self.initial.iter().find(|&&s| self.states[s] == state) {
+            Some(&s) => {
+                self.state = s;
+                self.trigger = None;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// can returns whether the given event would currently be accepted.
+    pub fn can(&self, event: &str) -> bool {
+        self.find(event).is_some()
+    }
+
+    /// apply transitions the machine using the given event. The first
+    /// transition from the current state on that event whose guard allows it
+    /// is taken.
+    pub fn apply(&mut self, event: &str) -> Result<(), TransitionError<String, String>> {
+        match self.find(event) {
+            Some((to, event)) => {
+                self.state = to;
+                self.trigger = Some(event);
+                Ok(())
+            }
+            None => Err(TransitionError::Invalid {
+                state: self.state().to_string(),
+                event: event.to_string(),
+            }),
+        }
+    }
+
+    /// report evaluates every event with a transition from the current state,
+    /// and returns, for each of them, the transitions whose guard rejected the
+    /// event and the transition that would be taken, if any. It helps finding
+    /// out why a machine doesn't accept an event.
+    ///
+    /// ```rust
+    /// extern crate sm;
+    /// use sm::builder::{Considered, MachineBuilder};
+    ///
+    /// fn main() {
+    ///     let mut builder = MachineBuilder::new();
+    ///     builder.state("Locked").state("Unlocked").state("Broken");
+    ///     builder.initial("Locked");
+    ///     builder.on("TurnKey").from("Locked").to("Unlocked");
+    ///     builder
+    ///         .on("Break")
+    ///         .from("Locked")
+    ///         .to("Broken")
+    ///         .guard(|sm| sm.trigger().is_some());
+    ///
+    ///     let sm = builder.build().unwrap();
+    ///     assert_eq!(
+    ///         sm.report(),
+    ///         vec![
+    ///             Considered { event: "TurnKey", rejected: vec![], to: Some("Unlocked") },
+    ///             Considered { event: "Break", rejected: vec!["Broken"], to: None },
+    ///         ]
+    ///     );
+    /// }
+    /// ```
+    pub fn report(&self) -> Vec<Considered<'_>> {
+        let mut report: Vec<Considered<'_>> = Vec::new();
+
+        for t in self.transitions.iter().filter(|t| t.from == self.state) {
+            let event = &*self.events[t.event];
+            let position = match report.iter().position(|c| c.event == event) {
+                Some(position) => position,
+                None => {
+                    report.push(Considered {
+                        event,
+                        rejected: Vec::new(),
+                        to: None,
+                    });
+                    report.len() - 1
+                }
+            };
+
+            let considered = &mut report[position];
+            if considered.to.is_some() {
+                continue;
+            }
+
+            if t.guard.as_ref().is_none_or(|g| (g.0)(self)) {
+                considered.to = Some(&self.states[t.to]);
+            } else {
+                considered.rejected.push(&self.states[t.to]);
+            }
+        }
+
+        report
+    }
+
+    /// from_typed returns a machine with the states, events and transitions of
+    /// the provided typed machine, in its state, and with its trigger. States
+    /// and events are declared in the order of their `StateId` and `EventId`
+    /// enums, and none of the transitions is guarded.
+    ///
+    /// ```rust
+    /// extern crate sm;
+    /// use sm::builder::DynMachine;
+    /// use sm::sm;
+    ///
+    /// sm! {
+    ///     Lock {
+    ///         InitialStates { Locked }
+    ///
+    ///         TurnKey {
+    ///             Locked => Unlocked
+    ///             Unlocked => Locked
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     use Lock::*;
+    ///
+    ///     let sm = Machine::new(Locked).transition(TurnKey).as_enum();
+    ///     let mut dynamic = DynMachine::from_typed(&sm);
+    ///     assert_eq!(dynamic.state(), "Unlocked");
+    ///     assert_eq!(dynamic.trigger(), Some("TurnKey"));
+    ///
+    ///     dynamic.apply("TurnKey").unwrap();
+    ///     let sm: Variant = dynamic.to_typed().unwrap();
+    ///     assert_eq!(sm.state_id(), StateId::Locked);
+    ///     assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+    /// }
+    /// ```
+    #[cfg(feature = "persist")]
+    pub fn from_typed<V: Restore>(machine: &V) -> Self {
+        let states = V::StateId::ALL;
+        let events = V::EventId::ALL;
+        let snapshot = machine.snapshot();
+
+        let initial = states
+            .iter()
+            .filter(|&&state| V::restore(Snapshot { state, trigger: None }).is_some())
+            .map(|state| state.index())
+            .collect();
+
+        let mut transitions = Vec::new();
+        for &from in states {
+            for &event in events {
+                if let Some(to) = V::next(from, event) {
+                    transitions.push(DynTransition {
+                        from: from.index(),
+                        event: event.index(),
+                        to: to.index(),
+                        guard: None,
+                    });
+                }
+            }
+        }
+
+        DynMachine {
+            state: snapshot.state.index(),
+            trigger: snapshot.trigger.map(Id::index),
+            states: V::StateId::NAMES.iter().map(|s| s.to_string()).collect(),
+            events: V::EventId::NAMES.iter().map(|e| e.to_string()).collect(),
+            initial,
+            transitions,
+        }
+    }
+
+    /// to_typed returns the typed machine in the state of this machine, and
+    /// with its trigger, matched by name. It returns `None` if the typed
+    /// machine has no such state or event, or if the trigger can't lead to the
+    /// state in the typed machine. Only the state and the trigger are
+    /// converted: the transitions of this machine are not checked against the
+    /// ones of the typed machine.
+    #[cfg(feature = "persist")]
+    pub fn to_typed<V: Restore>(&self) -> Option<V> {
+        let state = V::StateId::from_name(self.state())?;
+        let trigger = match self.trigger() {
+            Some(name) => Some(V::EventId::from_name(name)?),
+            None => None,
+        };
+
+        V::restore(Snapshot { state, trigger })
+    }
+
+    fn find(&self, event: &str) -> Option<(usize, usize)> {
+        let event = self.events.iter().position(|e| e == event)?;
+
+        self.transitions
+            .iter()
+            .filter(|t| t.from == self.state && t.event == event)
+            .find(|t| t.guard.as_ref().is_none_or(|g| (g.0)(self)))
+            .map(|t| (t.to, event))
+    }
+}
+
+/// Considered describes how an event was evaluated by `DynMachine::report`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Considered<'a> {
+    /// event is the name of the event.
+    pub event: &'a str,
+
+    /// rejected lists the states the event would have transitioned to, had
+    /// the guard of the transition allowed it, in declaration order.
+    pub rejected: Vec<&'a str>,
+
+    /// to is the state the event transitions to, if any transition allows it.
+    pub to: Option<&'a str>,
+}
+
+#[derive(Debug)]
+struct Rule {
+    event: String,
+    from: Vec<String>,
+    to: String,
+    guard: Option<Guard>,
+}
+
+#[derive(Debug)]
+struct DynTransition {
+    from: usize,
+    event: usize,
+    to: usize,
+    guard: Option<Guard>,
+}
+
+/// Guard decides whether a transition is allowed. It is shared by all
+/// transitions described by the same rule.
+struct Guard(Rc<dyn Fn(&DynMachine) -> bool>);
+
+impl Guard {
+    fn share(&self) -> Self {
+        Guard(self.0.clone())
+    }
+}
+
+impl fmt::Debug for Guard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Guard")
+    }
+}
@@ -0,0 +1,137 @@
+//! Send and receive the events of a protocol through typed channels.
+//!
+//! Machines marked `#[channel]` come with a `Channel` type, wrapping a
+//! `Transport` along with a state of the machine. Every event whose name
+//! starts with `Send` becomes a method of the channels in the states it
+//! transitions from, named after the event, which sends it, and returns the
+//! channel in the state it transitions to. Channels in a state receiving
+//! events, whose names start with `Receive`, have a `receive` method, which
+//! returns an enum named after the state, such as `WaitingReceived`, with one
+//! variant per event it can receive, holding the channel in the state it
+//! transitions to. Operations that aren't valid in a state don't exist, so a
+//! protocol can't be misused without the compiler noticing.
+//!
+//! Combined with the `dual!` macro, both sides of a protocol are generated
+//! from a single definition:
+//!
+//! ```rust
+//! extern crate sm;
+//! use std::cell::RefCell;
+//! use std::collections::VecDeque;
+//! use sm::channel::Transport;
+//! use sm::dual;
+//!
+//! dual! {
+//!     #[channel]
+//!     Client {
+//!         InitialStates { Idle }
+//!
+//!         SendRequest {
+//!             Idle => Waiting
+//!         }
+//!
+//!         ReceiveResponse {
+//!             Waiting => Idle
+//!         }
+//!     }
+//!
+//!     Server
+//! }
+//!
+//! #[derive(Debug, Default)]
+//! struct Wire {
+//!     requests: RefCell<VecDeque<Client::EventId>>,
+//!     responses: RefCell<VecDeque<Server::EventId>>,
+//! }
+//!
+//! #[derive(Debug)]
+//! struct ClientEnd<'a>(&'a Wire);
+//!
+//! #[derive(Debug)]
+//! struct ServerEnd<'a>(&'a Wire);
+//!
+//! impl<'a> Transport<Client::EventId> for ClientEnd<'a> {
+//!     type Error = ();
+//!
+//!     fn send(&mut self, event: Client::EventId) -> Result<(), ()> {
+//!         self.0.requests.borrow_mut().push_back(event);
+//!         Ok(())
+//!     }
+//!
+//!     fn receive(&mut self) -> Result<Client::EventId, ()> {
+//!         self.0.responses.borrow_mut().pop_front().map(Into::into).ok_or(())
+//!     }
+//! }
+//!
+//! impl<'a> Transport<Server::EventId> for ServerEnd<'a> {
+//!     type Error = ();
+//!
+//!     fn send(&mut self, event: Server::EventId) -> Result<(), ()> {
+//!         self.0.responses.borrow_mut().push_back(event);
+//!         Ok(())
+//!     }
+//!
+//!     fn receive(&mut self) -> Result<Server::EventId, ()> {
+//!         self.0.requests.borrow_mut().pop_front().map(Into::into).ok_or(())
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let wire = Wire::default();
+//!     let client = Client::Channel::new(Client::Idle, ClientEnd(&wire));
+//!     let server = Server::Channel::new(Server::Idle, ServerEnd(&wire));
+//!
+//!     let client = client.send_request().unwrap();
+//!     let server = match server.receive().unwrap() {
+//!         Server::IdleReceived::ReceiveRequest(server) => server,
+//!     };
+//!
+//!     server.send_response().unwrap();
+//!     let client = match client.receive().unwrap() {
+//!         Client::WaitingReceived::ReceiveResponse(client) => client,
+//!     };
+//!
+//!     assert_eq!(client.state(), Client::Idle);
+//! }
+//! ```
+
+use core::fmt;
+
+/// Transport sends and receives the events of a machine on behalf of its
+/// channels.
+pub trait Transport<E> {
+    /// Error is the error returned when an event could not be sent or
+    /// received.
+    type Error;
+
+    /// send sends the event to the peer.
+    fn send(&mut self, event: E) -> Result<(), Self::Error>;
+
+    /// receive waits for the next event sent by the peer.
+    fn receive(&mut self) -> Result<E, Self::Error>;
+}
+
+/// ChannelError describes why a channel could not send or receive an event.
+/// It is returned along with the channel, which is left in its state.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ChannelError<E, X> {
+    /// Transport is returned when the transport failed to send or receive the
+    /// event.
+    Transport(X),
+
+    /// Unexpected is returned when the event received can't be received in
+    /// the state of the channel.
+    Unexpected(E),
+}
+
+impl<E: fmt::Debug, X: fmt::Debug> fmt::Display for ChannelError<E, X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelError::Transport(err) => write!(f, "transport failed: {:?}", err),
+            ChannelError::Unexpected(event) => write!(f, "event {:?} was not expected", event),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: fmt::Debug, X: fmt::Debug> std::error::Error for ChannelError<E, X> {}
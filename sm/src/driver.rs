@@ -0,0 +1,814 @@
+//! Drive a machine from events received through any runtime.
+//!
+//! A `Driver` receives the next event for a machine, and publishes the machine
+//! after every applied transition. The `drive` function turns a driver and a
+//! machine into a future, which applies events until the driver runs out of
+//! them, and resolves to the final machine. The same future can be awaited on
+//! any executor, or, with the `std` feature, waited upon without one.
+//!
+//! Drivers are provided for [tokio][t] channels (the `tokio` feature),
+//! [async-std][a] channels (the `async-std` feature), [embassy][e] channels
+//! (the `embassy` feature), and blocking standard library channels (the `std`
+//! feature). A fixed-capacity `Queue`, with a
+//! configurable `Backpressure` policy, is available without any runtime. Other
+//! sources of events can implement `Driver` themselves:
+//!
+//! ```rust
+//! extern crate futures;
+//! extern crate sm;
+//! use std::task::{Context, Poll};
+//! use futures::executor::block_on;
+//! use sm::driver::{drive, Driver};
+//! use sm::sm;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!
+//!         Break {
+//!             Locked => Broken
+//!         }
+//!     }
+//! }
+//!
+//! use Lock::*;
+//!
+//! struct Script {
+//!     events: std::vec::IntoIter<EventId>,
+//!     states: Vec<StateId>,
+//! }
+//!
+//! impl Driver<Variant> for Script {
+//!     type Event = EventId;
+//!
+//!     fn poll_event(&mut self, _: &mut Context<'_>) -> Poll<Option<EventId>> {
+//!         Poll::Ready(self.events.next())
+//!     }
+//!
+//!     fn publish(&mut self, machine: &Variant) {
+//!         self.states.push(machine.state_id());
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut script = Script {
+//!         events: vec![EventId::TurnKey, EventId::Break, EventId::TurnKey].into_iter(),
+//!         states: vec![],
+//!     };
+//!
+//!     let sm = block_on(drive(&mut script, Machine::new(Locked).as_enum()));
+//!
+//!     assert_eq!(sm.state_id(), StateId::Locked);
+//!     assert_eq!(script.states, vec![StateId::Unlocked, StateId::Locked]);
+//! }
+//! ```
+//!
+//! With the `std` feature, `terminal` returns a future resolving once the
+//! machine enters a terminal state, along with a handle applying events to the
+//! machine from anywhere, so the completion of a workflow can be awaited.
+//!
+//! Input that isn't made of events, such as the characters read by a lexer or
+//! the bytes read by a protocol decoder, can be run through a machine by
+//! `classify`, which maps every item to an event using a `Classify`
+//! implementation.
+//!
+//! [t]: https://docs.rs/tokio/1/tokio/sync/index.html
+//! [a]: https://docs.rs/async-std/1/async_std/channel/index.html
+//! [e]: https://docs.rs/embassy-sync
+
+#[cfg(feature = "embassy")]
+use core::fmt;
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+#[cfg(feature = "std")]
+use core::task::Waker;
+
+use crate::{Table, TransitionError, TryTransition};
+
+/// Driver connects a machine to the outside world: it receives the events to
+/// apply to the machine, and publishes the machine after every transition.
+pub trait Driver<V> {
+    /// Event is the type of the events received by the driver.
+    type Event;
+
+    /// poll_event attempts to receive the next event. Returning
+    /// `Poll::Ready(None)` signals that no more events will be received.
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Event>>;
+
+    /// publish is called with the machine after every applied transition.
+    fn publish(&mut self, machine: &V);
+}
+
+impl<D, V> Driver<V> for &mut D
+where
+    D: Driver<V> + ?Sized,
+{
+    type Event = D::Event;
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<Self::Event>> {
+        (**self).poll_event(cx)
+    }
+
+    fn publish(&mut self, machine: &V) {
+        (**self).publish(machine)
+    }
+}
+
+/// drive returns a future applying every event received by the driver to the
+/// machine. Events without a valid transition from the current state are
+/// skipped.
+pub fn drive<D, V>(driver: D, machine: V) -> Drive<D, V>
+where
+    D: Driver<V>,
+    V: TryTransition<D::Event> + Clone,
+{
+    Drive {
+        driver,
+        machine: Some(machine),
+    }
+}
+
+/// Drive is the future returned by `drive`. It resolves to the final machine
+/// once the driver stops receiving events. Once resolved, it stays pending.
+#[derive(Debug)]
+pub struct Drive<D, V> {
+    driver: D,
+    machine: Option<V>,
+}
+
+impl<D, V> Drive<D, V>
+where
+    D: Driver<V>,
+    V: TryTransition<D::Event> + Clone,
+{
+    /// wait applies events until the driver stops receiving them, without
+    /// requiring an executor. The current thread is parked while the driver
+    /// returns `Poll::Pending`, until the driver wakes it.
+    #[cfg(feature = "std")]
+    pub fn wait(mut self) -> V {
+        let waker = Waker::from(std::sync::Arc::new(Unpark(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+
+        loop {
+            match self.poll_drive(&mut cx) {
+                Poll::Ready(machine) => return machine,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
+
+    fn poll_drive(&mut self, cx: &mut Context<'_>) -> Poll<V> {
+        loop {
+            let event = match self.driver.poll_event(cx) {
+                Poll::Ready(Some(event)) => event,
+                Poll::Ready(None) => match self.machine.take() {
+                    Some(machine) => return Poll::Ready(machine),
+                    None => return Poll::Pending,
+                },
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let machine = match self.machine.take() {
+                Some(machine) => machine,
+                None => return Poll::Pending,
+            };
+
+            match machine.try_transition(event) {
+                Ok(next) => {
+                    self.driver.publish(&next);
+                    self.machine = Some(next);
+
+                    #[cfg(feature = "log")]
+                    crate::export::log::trace!("applied a driven event");
+                }
+                Err(machine) => {
+                    self.machine = Some(machine);
+
+                    #[cfg(feature = "log")]
+                    crate::export::log::trace!("skipped a driven event without a valid transition");
+                }
+            }
+        }
+    }
+}
+
+/// Unpark wakes the thread waiting on a `Drive`.
+#[cfg(feature = "std")]
+struct Unpark(std::thread::Thread);
+
+#[cfg(feature = "std")]
+impl std::task::Wake for Unpark {
+    fn wake(self: std::sync::Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+impl<D, V> Future for Drive<D, V>
+where
+    D: Driver<V> + Unpin,
+    V: TryTransition<D::Event> + Clone + Unpin,
+{
+    type Output = V;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<V> {
+        self.get_mut().poll_drive(cx)
+    }
+}
+
+/// Backpressure determines what a `Queue` does with an event pushed while it is
+/// full.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Backpressure {
+    /// DropOldest discards the oldest queued event to make room for the new
+    /// one.
+    DropOldest,
+
+    /// DropNewest discards the pushed event, leaving the queue untouched.
+    DropNewest,
+
+    /// Error returns the pushed event to the producer, leaving the queue
+    /// untouched.
+    Error,
+}
+
+/// Queue is a fixed-capacity queue of events, holding up to `N` events. It is
+/// also a driver, applying the queued events in order until the queue is
+/// empty.
+///
+/// ```rust
+/// # extern crate futures;
+/// # extern crate sm;
+/// # use futures::executor::block_on;
+/// # use sm::sm;
+/// use sm::driver::{drive, Backpressure, Queue};
+///
+/// # sm! {
+/// #     Lock {
+/// #         InitialStates { Locked }
+/// #
+/// #         TurnKey {
+/// #             Locked => Unlocked
+/// #             Unlocked => Locked
+/// #         }
+/// #     }
+/// # }
+/// #
+/// # fn main() {
+/// use Lock::*;
+///
+/// let mut queue = Queue::<EventId, 2>::new(Backpressure::Error);
+///
+/// assert_eq!(queue.push(EventId::TurnKey), Ok(()));
+/// assert_eq!(queue.push(EventId::TurnKey), Ok(()));
+/// assert_eq!(queue.push(EventId::TurnKey), Err(EventId::TurnKey));
+///
+/// let sm = block_on(drive(&mut queue, Machine::new(Locked).as_enum()));
+/// assert_eq!(sm.state_id(), StateId::Locked);
+/// assert!(queue.is_empty());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Queue<E, const N: usize> {
+    events: [Option<E>; N],
+    head: usize,
+    len: usize,
+    backpressure: Backpressure,
+}
+
+impl<E, const N: usize> Queue<E, N> {
+    /// new creates an empty queue, applying the provided policy once it is
+    /// full. A queue without room for any event doesn't compile.
+    pub fn new(backpressure: Backpressure) -> Self {
+        const { assert!(N > 0, "a queue needs room for at least one event") };
+
+        Queue {
+            events: core::array::from_fn(|_| None),
+            head: 0,
+            len: 0,
+            backpressure,
+        }
+    }
+
+    /// push adds an event to the back of the queue. If the queue is full, the
+    /// configured `Backpressure` decides which event is discarded, or whether
+    /// the event is returned as the error.
+    pub fn push(&mut self, event: E) -> Result<(), E> {
+        if self.len == N {
+            match self.backpressure {
+                Backpressure::DropOldest => {
+                    let _ = self.pop();
+                }
+                Backpressure::DropNewest => return Ok(()),
+                Backpressure::Error => return Err(event),
+            }
+        }
+
+        self.events[(self.head + self.len) % N] = Some(event);
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// pop removes the event at the front of the queue, if any.
+    pub fn pop(&mut self) -> Option<E> {
+        let event = self.events[self.head].take()?;
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+
+        Some(event)
+    }
+
+    /// len returns the number of queued events.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// is_empty returns `true` if no events are queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<E, V, const N: usize> Driver<V> for Queue<E, N> {
+    type Event = E;
+
+    fn poll_event(&mut self, _: &mut Context<'_>) -> Poll<Option<E>> {
+        Poll::Ready(self.pop())
+    }
+
+    fn publish(&mut self, _: &V) {}
+}
+
+/// Classify maps the items of an input to the events of a machine. It is
+/// implemented by every closure taking a reference to an item and returning
+/// an event.
+pub trait Classify<I> {
+    /// Event is the type of the events items are mapped to.
+    type Event;
+
+    /// classify returns the event matching the item.
+    fn classify(&mut self, item: &I) -> Self::Event;
+}
+
+impl<I, E, F> Classify<I> for F
+where
+    F: FnMut(&I) -> E,
+{
+    type Event = E;
+
+    fn classify(&mut self, item: &I) -> E {
+        self(item)
+    }
+}
+
+/// classify returns an iterator running the machine over the input, applying
+/// the event each item is classified as. It yields the state of the machine
+/// after every item, or the error if the event has no valid transition from
+/// the current state, in which case the machine is left untouched and the
+/// next item is classified as usual.
+///
+/// ```rust
+/// # extern crate sm;
+/// # use sm::sm;
+/// use sm::driver::classify;
+///
+/// sm! {
+///     #[table]
+///     Lexer {
+///         InitialStates { Blank }
+///
+///         Digit {
+///             Blank => Number
+///             Number => Number
+///         }
+///
+///         Letter {
+///             Blank => Word
+///             Word => Word
+///         }
+///
+///         Space {
+///             Blank => Blank
+///             Number => Blank
+///             Word => Blank
+///         }
+///     }
+/// }
+///
+/// # fn main() {
+/// use Lexer::*;
+///
+/// let classifier = |c: &char| match c {
+///     '0'..='9' => EventId::Digit,
+///     ' ' => EventId::Space,
+///     _ => EventId::Letter,
+/// };
+///
+/// let mut lexer = classify("ab 1c".chars(), classifier, Machine::new(Blank).as_enum());
+///
+/// assert_eq!(lexer.next(), Some(Ok(StateId::Word)));
+/// assert_eq!(lexer.next(), Some(Ok(StateId::Word)));
+/// assert_eq!(lexer.next(), Some(Ok(StateId::Blank)));
+/// assert_eq!(lexer.next(), Some(Ok(StateId::Number)));
+/// assert!(lexer.next().unwrap().is_err());
+/// assert_eq!(lexer.next(), None);
+/// assert_eq!(lexer.machine().state_id(), StateId::Number);
+/// # }
+/// ```
+pub fn classify<I, C, V>(input: I, classifier: C, machine: V) -> Classified<I::IntoIter, C, V>
+where
+    I: IntoIterator,
+    C: Classify<I::Item, Event = V::EventId>,
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    Classified {
+        input: input.into_iter(),
+        classifier,
+        machine,
+    }
+}
+
+/// Classified is the iterator returned by `classify`.
+#[derive(Debug)]
+pub struct Classified<I, C, V> {
+    input: I,
+    classifier: C,
+    machine: V,
+}
+
+impl<I, C, V> Classified<I, C, V> {
+    /// machine returns the machine, in the state reached so far.
+    pub fn machine(&self) -> &V {
+        &self.machine
+    }
+
+    /// into_machine consumes the iterator, and returns the machine in the
+    /// state reached so far.
+    pub fn into_machine(self) -> V {
+        self.machine
+    }
+}
+
+impl<I, C, V> Iterator for Classified<I, C, V>
+where
+    I: Iterator,
+    C: Classify<I::Item, Event = V::EventId>,
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    type Item = Result<V::StateId, TransitionError<V::StateId, V::EventId>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.input.next()?;
+        let event = self.classifier.classify(&item);
+        let state = self.machine.state();
+
+        Some(match self.machine.prepare(event) {
+            Some(prepared) => Ok(prepared.commit().state()),
+            None => Err(TransitionError::Invalid { state, event }),
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.input.size_hint()
+    }
+}
+
+/// Tokio receives events from a tokio `mpsc` channel, and publishes the machine
+/// on a tokio `watch` channel.
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct Tokio<E, V> {
+    events: tokio::sync::mpsc::Receiver<E>,
+    machines: tokio::sync::watch::Sender<V>,
+}
+
+#[cfg(feature = "tokio")]
+impl<E, V> Tokio<E, V> {
+    /// new creates a new driver from the receiving end of an event channel,
+    /// and the sending end of a watch channel.
+    pub fn new(
+        events: tokio::sync::mpsc::Receiver<E>,
+        machines: tokio::sync::watch::Sender<V>,
+    ) -> Self {
+        Tokio { events, machines }
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<E, V: Clone> Driver<V> for Tokio<E, V> {
+    type Event = E;
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        self.events.poll_recv(cx)
+    }
+
+    fn publish(&mut self, machine: &V) {
+        let _ = self.machines.send_replace(machine.clone());
+    }
+}
+
+/// AsyncStd receives events from an async-std channel, and publishes the
+/// machine on another one. A machine that cannot be published because the
+/// channel is full or closed is dropped.
+#[cfg(feature = "async-std")]
+#[derive(Debug)]
+pub struct AsyncStd<E, V> {
+    events: async_std::channel::Receiver<E>,
+    machines: async_std::channel::Sender<V>,
+}
+
+#[cfg(feature = "async-std")]
+impl<E, V> AsyncStd<E, V> {
+    /// new creates a new driver from the receiving end of an event channel,
+    /// and the sending end of a machine channel.
+    pub fn new(
+        events: async_std::channel::Receiver<E>,
+        machines: async_std::channel::Sender<V>,
+    ) -> Self {
+        AsyncStd { events, machines }
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl<E, V: Clone> Driver<V> for AsyncStd<E, V> {
+    type Event = E;
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        async_std::stream::Stream::poll_next(Pin::new(&mut self.events), cx)
+    }
+
+    fn publish(&mut self, machine: &V) {
+        let _ = self.machines.try_send(machine.clone());
+    }
+}
+
+/// Embassy receives events from an embassy channel, and signals the machine
+/// after every transition. Embassy channels cannot be closed, so a machine
+/// driven by this driver is driven forever, which suits a dedicated task:
+///
+/// ```rust,ignore
+/// #[embassy_executor::task]
+/// async fn lock(events: Receiver<'static, CriticalSectionRawMutex, EventId, 4>) {
+///     let driver = Embassy::new(events, &LOCK);
+///     drive(driver, Machine::new(Locked).as_enum()).await;
+/// }
+/// ```
+#[cfg(feature = "embassy")]
+pub struct Embassy<'ch, M, E, V, const N: usize>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    events: embassy_sync::channel::Receiver<'ch, M, E, N>,
+    machines: &'ch embassy_sync::signal::Signal<M, V>,
+}
+
+#[cfg(feature = "embassy")]
+impl<'ch, M, E, V, const N: usize> Embassy<'ch, M, E, V, N>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    /// new creates a new driver from the receiving end of an event channel,
+    /// and a signal to publish the machine on.
+    pub fn new(
+        events: embassy_sync::channel::Receiver<'ch, M, E, N>,
+        machines: &'ch embassy_sync::signal::Signal<M, V>,
+    ) -> Self {
+        Embassy { events, machines }
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<M, E, V, const N: usize> Driver<V> for Embassy<'_, M, E, V, N>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+    V: Clone,
+{
+    type Event = E;
+
+    fn poll_event(&mut self, cx: &mut Context<'_>) -> Poll<Option<E>> {
+        self.events.poll_receive(cx).map(Some)
+    }
+
+    fn publish(&mut self, machine: &V) {
+        self.machines.signal(machine.clone());
+    }
+}
+
+#[cfg(feature = "embassy")]
+impl<M, E, V, const N: usize> fmt::Debug for Embassy<'_, M, E, V, N>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Embassy").finish_non_exhaustive()
+    }
+}
+
+/// Blocking receives events from a standard library channel, blocking the
+/// current thread until an event arrives, and publishes the machine on another
+/// one. Use `Drive::wait` to drive a machine without an executor.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct Blocking<E, V> {
+    events: std::sync::mpsc::Receiver<E>,
+    machines: std::sync::mpsc::Sender<V>,
+}
+
+#[cfg(feature = "std")]
+impl<E, V> Blocking<E, V> {
+    /// new creates a new driver from the receiving end of an event channel,
+    /// and the sending end of a machine channel.
+    pub fn new(events: std::sync::mpsc::Receiver<E>, machines: std::sync::mpsc::Sender<V>) -> Self {
+        Blocking { events, machines }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E, V: Clone> Driver<V> for Blocking<E, V> {
+    type Event = E;
+
+    fn poll_event(&mut self, _: &mut Context<'_>) -> Poll<Option<E>> {
+        Poll::Ready(self.events.recv().ok())
+    }
+
+    fn publish(&mut self, machine: &V) {
+        let _ = self.machines.send(machine.clone());
+    }
+}
+
+/// terminal returns a future resolving to the machine once it enters a
+/// terminal state, from which no event can transition it, along with a handle
+/// applying events to the machine. The handle can be cloned, and sent to other
+/// threads. If every handle is dropped before the machine enters a terminal
+/// state, the future resolves to the machine as the error.
+///
+/// ```rust
+/// # extern crate sm;
+/// # use sm::sm;
+/// use std::future::Future;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll, Waker};
+/// use sm::driver::terminal;
+///
+/// # sm! {
+/// #     #[table]
+/// #     Lock {
+/// #         InitialStates { Locked }
+/// #
+/// #         TurnKey {
+/// #             Locked => Unlocked
+/// #             Unlocked => Locked
+/// #         }
+/// #
+/// #         Break {
+/// #             Locked => Broken
+/// #         }
+/// #     }
+/// # }
+/// #
+/// # fn main() {
+/// use Lock::*;
+///
+/// let (handle, mut broken) = terminal(Machine::new(Locked).as_enum());
+/// let mut cx = Context::from_waker(Waker::noop());
+///
+/// handle.apply(EventId::TurnKey).unwrap();
+/// assert!(Pin::new(&mut broken).poll(&mut cx).is_pending());
+///
+/// handle.apply(EventId::TurnKey).unwrap();
+/// handle.apply(EventId::Break).unwrap();
+///
+/// match Pin::new(&mut broken).poll(&mut cx) {
+///     Poll::Ready(Ok(sm)) => assert_eq!(sm.state_id(), StateId::Broken),
+///     _ => unreachable!(),
+/// }
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn terminal<V>(machine: V) -> (TerminalHandle<V>, TerminalFuture<V>)
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    let shared = std::sync::Arc::new(std::sync::Mutex::new(Terminal {
+        machine,
+        handles: 1,
+        waker: None,
+    }));
+
+    (
+        TerminalHandle {
+            shared: shared.clone(),
+        },
+        TerminalFuture { shared },
+    )
+}
+
+#[cfg(feature = "std")]
+#[derive(Debug)]
+struct Terminal<V> {
+    machine: V,
+    handles: usize,
+    waker: Option<Waker>,
+}
+
+#[cfg(feature = "std")]
+fn lock<V>(shared: &std::sync::Mutex<Terminal<V>>) -> std::sync::MutexGuard<'_, Terminal<V>> {
+    shared.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// TerminalHandle applies events to the machine of a `TerminalFuture`.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TerminalHandle<V> {
+    shared: std::sync::Arc<std::sync::Mutex<Terminal<V>>>,
+}
+
+#[cfg(feature = "std")]
+impl<V> TerminalHandle<V>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    /// apply applies the event to the machine, and wakes the future if the
+    /// machine enters a terminal state.
+    pub fn apply(&self, event: V::EventId) -> Result<(), TransitionError<V::StateId, V::EventId>> {
+        let mut terminal = lock(&self.shared);
+        let state = terminal.machine.state();
+        let next = match terminal.machine.prepare(event) {
+            Some(prepared) => prepared.commit().state(),
+            None => return Err(TransitionError::Invalid { state, event }),
+        };
+
+        if V::is_terminal(next) {
+            if let Some(waker) = terminal.waker.take() {
+                waker.wake();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// state returns the current state of the machine.
+    pub fn state(&self) -> V::StateId {
+        lock(&self.shared).machine.state()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> Clone for TerminalHandle<V> {
+    fn clone(&self) -> Self {
+        lock(&self.shared).handles += 1;
+
+        TerminalHandle {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V> Drop for TerminalHandle<V> {
+    fn drop(&mut self) {
+        let mut terminal = lock(&self.shared);
+        terminal.handles -= 1;
+
+        if terminal.handles == 0 {
+            if let Some(waker) = terminal.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+/// TerminalFuture is the future returned by `terminal`. It resolves to the
+/// machine once it enters a terminal state, or to the machine as the error
+/// once every handle is dropped.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct TerminalFuture<V> {
+    shared: std::sync::Arc<std::sync::Mutex<Terminal<V>>>,
+}
+
+#[cfg(feature = "std")]
+impl<V: Table + Clone> Future for TerminalFuture<V> {
+    type Output = Result<V, V>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<V, V>> {
+        let mut terminal = lock(&self.shared);
+
+        if V::is_terminal(terminal.machine.state()) {
+            return Poll::Ready(Ok(terminal.machine.clone()));
+        }
+
+        if terminal.handles == 0 {
+            return Poll::Ready(Err(terminal.machine.clone()));
+        }
+
+        terminal.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
@@ -0,0 +1,339 @@
+//! Schedule and route events across many machines of different types.
+//!
+//! An `Executor` owns machine instances keyed by an id of your choosing, each
+//! of them of any machine type, and a queue of messages, each carrying the id
+//! of a machine and the name of an event. Messages are processed one at a time
+//! by `step`, or until the queue is empty by `run`, in the order decided by the
+//! `Policy` of the executor: in the order they were sent, by priority, or
+//! taking turns between the machines so that a busy machine can't starve the
+//! others. Guards registered on the executor can reject a
+//! message before it is applied.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::exec::{ExecError, Executor, Policy};
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Order {
+//!         InitialStates { Placed }
+//!
+//!         Pay {
+//!             Placed => Paid
+//!         }
+//!
+//!         Ship {
+//!             Paid => Shipped
+//!         }
+//!     }
+//!
+//!     #[table]
+//!     Door {
+//!         InitialStates { Closed }
+//!
+//!         Open {
+//!             Closed => Opened
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut exec = Executor::new(Policy::Priority);
+//!     let _ = exec.spawn("order", Order::Machine::new(Order::Placed).as_enum());
+//!     let _ = exec.spawn("door", Door::Machine::new(Door::Closed).as_enum());
+//!     exec.guard(|id, _, event| !(*id == "door" && event == "Open"));
+//!
+//!     exec.send("order", "Ship");
+//!     exec.send_with_priority("order", "Pay", 1);
+//!     exec.send("door", "Open");
+//!
+//!     let outcomes = exec.run();
+//!     assert_eq!(outcomes[0], ("order", Ok(())));
+//!     assert_eq!(outcomes[1], ("order", Ok(())));
+//!     assert_eq!(outcomes[2], ("door", Err(ExecError::Rejected)));
+//!
+//!     assert_eq!(exec.get(&"order").unwrap().state_name(), "Shipped");
+//!     assert_eq!(exec.get(&"door").unwrap().state_name(), "Closed");
+//! }
+//! ```
+
+use std::boxed::Box;
+use std::collections::{HashMap, VecDeque};
+use std::error;
+use std::fmt;
+use std::hash::Hash;
+use std::string::String;
+use std::vec::Vec;
+
+use crate::{Id, Table, TransitionError, TryTransition};
+
+/// Instance is a machine of any type, driven by the names of its events. It is
+/// implemented by every machine implementing `Table` and `TryTransition`, such
+/// as the `Variant` enum generated by the `sm!` macro.
+pub trait Instance: fmt::Debug {
+    /// machine_name returns the name of the machine, as declared in the macro.
+    fn machine_name(&self) -> &'static str;
+
+    /// state_name returns the name of the current state of the machine.
+    fn state_name(&self) -> &'static str;
+
+    /// is_terminal returns `true` if no event can transition the machine out
+    /// of its current state.
+    fn is_terminal(&self) -> bool;
+
+    /// apply_event applies the event with the provided name to the machine. A
+    /// name matching no event of the machine is rejected with
+    /// `TransitionError::UnknownEvent`.
+    fn apply_event(&mut self, event: &str) -> Result<(), TransitionError<&'static str, &'static str>>;
+}
+
+impl<V> Instance for V
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone + fmt::Debug,
+{
+    fn machine_name(&self) -> &'static str {
+        V::NAME
+    }
+
+    fn state_name(&self) -> &'static str {
+        self.state().name()
+    }
+
+    fn is_terminal(&self) -> bool {
+        V::is_terminal(self.state())
+    }
+
+    fn apply_event(&mut self, event: &str) -> Result<(), TransitionError<&'static str, &'static str>> {
+        let event = V::EventId::from_name(event).ok_or(TransitionError::UnknownEvent)?;
+        let state = self.state();
+
+        match self.prepare(event) {
+            Some(prepared) => {
+                let _ = prepared.commit();
+                Ok(())
+            }
+            None => Err(TransitionError::Invalid {
+                state: state.name(),
+                event: event.name(),
+            }),
+        }
+    }
+}
+
+/// Policy decides which queued message an `Executor` processes next.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Policy {
+    /// Fifo processes messages in the order they were sent.
+    Fifo,
+
+    /// Priority processes the messages with the highest priority first, and
+    /// messages with the same priority in the order they were sent.
+    Priority,
+
+    /// RoundRobin processes the oldest message of the machine that was served
+    /// the longest time ago, so a busy machine can't starve the others.
+    RoundRobin,
+}
+
+/// ExecError describes why a message could not be processed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExecError {
+    /// UnknownMachine is returned when no machine is registered under the id
+    /// of the message.
+    UnknownMachine,
+
+    /// Rejected is returned when a guard rejected the message.
+    Rejected,
+
+    /// Transition is returned when the event could not be applied to the
+    /// machine.
+    Transition(TransitionError<&'static str, &'static str>),
+}
+
+impl fmt::Display for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecError::UnknownMachine => f.write_str("no machine is registered under this id"),
+            ExecError::Rejected => f.write_str("the message was rejected by a guard"),
+            ExecError::Transition(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl error::Error for ExecError {}
+
+/// Executor owns machines of any type, and processes the messages sent to
+/// them.
+#[derive(Debug)]
+pub struct Executor<K> {
+    machines: HashMap<K, Entry>,
+    queue: VecDeque<Message<K>>,
+    guards: Vec<Guard<K>>,
+    policy: Policy,
+    steps: u64,
+}
+
+#[derive(Debug)]
+struct Entry {
+    machine: Box<dyn Instance>,
+    served: u64,
+}
+
+#[derive(Debug)]
+struct Message<K> {
+    id: K,
+    event: String,
+    priority: u8,
+}
+
+/// GuardFn is the signature of the closures registered by `Executor::guard`.
+type GuardFn<K> = dyn Fn(&K, &dyn Instance, &str) -> bool;
+
+/// Guard decides whether a message is applied to its machine.
+struct Guard<K>(Box<GuardFn<K>>);
+
+impl<K> fmt::Debug for Guard<K> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Guard")
+    }
+}
+
+impl<K: Eq + Hash> Executor<K> {
+    /// new creates an executor without any machines, processing messages
+    /// according to the provided policy.
+    pub fn new(policy: Policy) -> Self {
+        Executor {
+            machines: HashMap::new(),
+            queue: VecDeque::new(),
+            guards: Vec::new(),
+            policy,
+            steps: 0,
+        }
+    }
+
+    /// spawn adds a machine under the given id, and returns the machine that
+    /// was previously registered under that id, if any.
+    pub fn spawn<V: Instance + 'static>(&mut self, id: K, machine: V) -> Option<Box<dyn Instance>> {
+        let entry = Entry {
+            machine: Box::new(machine),
+            served: 0,
+        };
+
+        self.machines.insert(id, entry).map(|entry| entry.machine)
+    }
+
+    /// get returns the machine registered under the given id, if any.
+    pub fn get(&self, id: &K) -> Option<&dyn Instance> {
+        self.machines.get(id).map(|entry| &*entry.machine)
+    }
+
+    /// remove removes the machine registered under the given id, and returns
+    /// it, if any. Messages sent to it that are still queued fail with
+    /// `ExecError::UnknownMachine` once processed.
+    pub fn remove(&mut self, id: &K) -> Option<Box<dyn Instance>> {
+        self.machines.remove(id).map(|entry| entry.machine)
+    }
+
+    /// len returns the number of registered machines.
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    /// is_empty returns `true` if no machines are registered.
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    /// pending returns the number of queued messages.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// guard registers a guard, called with the id of the machine, the machine
+    /// and the name of the event of every message before it is applied. A
+    /// message is only applied if every guard returns `true`.
+    pub fn guard<G>(&mut self, guard: G)
+    where
+        G: Fn(&K, &dyn Instance, &str) -> bool + 'static,
+    {
+        self.guards.push(Guard(Box::new(guard)));
+    }
+
+    /// send queues an event for the machine registered under the given id,
+    /// with the lowest priority.
+    pub fn send<S: Into<String>>(&mut self, id: K, event: S) {
+        self.send_with_priority(id, event, 0);
+    }
+
+    /// send_with_priority queues an event for the machine registered under the
+    /// given id. Priorities are only taken into account by `Policy::Priority`.
+    pub fn send_with_priority<S: Into<String>>(&mut self, id: K, event: S, priority: u8) {
+        self.queue.push_back(Message {
+            id,
+            event: event.into(),
+            priority,
+        });
+    }
+
+    /// step processes the next message, and returns the id of its machine
+    /// along with the outcome, or `None` if no message is queued.
+    pub fn step(&mut self) -> Option<(K, Result<(), ExecError>)> {
+        let next = self.next()?;
+        let message = self.queue.remove(next)?;
+        self.steps += 1;
+
+        let outcome = match self.machines.get_mut(&message.id) {
+            Some(entry) => {
+                entry.served = self.steps;
+
+                if self
+                    .guards
+                    .iter()
+                    .all(|guard| (guard.0)(&message.id, &*entry.machine, &message.event))
+                {
+                    entry
+                        .machine
+                        .apply_event(&message.event)
+                        .map_err(ExecError::Transition)
+                } else {
+                    Err(ExecError::Rejected)
+                }
+            }
+            None => Err(ExecError::UnknownMachine),
+        };
+
+        Some((message.id, outcome))
+    }
+
+    /// run processes messages until the queue is empty, and returns the
+    /// outcome of each of them, in the order they were processed.
+    pub fn run(&mut self) -> Vec<(K, Result<(), ExecError>)> {
+        let mut outcomes = Vec::new();
+
+        while let Some(outcome) = self.step() {
+            outcomes.push(outcome);
+        }
+
+        outcomes
+    }
+
+    fn next(&self) -> Option<usize> {
+        let mut messages = self.queue.iter().enumerate();
+
+        match self.policy {
+            Policy::Fifo => messages.next().map(|(i, _)| i),
+            // `max_by_key` returns the last of equal elements, so positions
+            // are reversed to keep the oldest message.
+            Policy::Priority => messages
+                .max_by_key(|(i, message)| (message.priority, usize::MAX - i))
+                .map(|(i, _)| i),
+            Policy::RoundRobin => messages
+                .min_by_key(|(i, message)| {
+                    let served = self.machines.get(&message.id).map_or(0, |entry| entry.served);
+                    (served, *i)
+                })
+                .map(|(i, _)| i),
+        }
+    }
+}
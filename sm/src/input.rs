@@ -0,0 +1,167 @@
+//! Turn the edges of digital inputs into events.
+//!
+//! An `Input` polls a pin implementing the [`embedded-hal`][eh] `InputPin`
+//! trait, and reports the changes of its level as an `Edge`, or as the event
+//! mapped to that edge. Buttons and switches bounce, so an `Input` can be
+//! debounced, only reporting a change once the new level has been read on a
+//! number of consecutive polls.
+//!
+//! Polling is left to the caller, from the main loop or a timer interrupt, and
+//! so is applying the events to the machine:
+//!
+//! ```rust
+//! extern crate embedded_hal;
+//! extern crate sm;
+//! use std::convert::Infallible;
+//! use embedded_hal::digital::{ErrorType, InputPin};
+//! use sm::input::Input;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[apply]
+//!     Light {
+//!         InitialStates { Off }
+//!
+//!         Toggle {
+//!             Off => On
+//!             On => Off
+//!         }
+//!     }
+//! }
+//!
+//! struct Button(Vec<bool>);
+//!
+//! impl ErrorType for Button {
+//!     type Error = Infallible;
+//! }
+//!
+//! impl InputPin for Button {
+//!     fn is_high(&mut self) -> Result<bool, Infallible> {
+//!         Ok(self.0.remove(0))
+//!     }
+//!
+//!     fn is_low(&mut self) -> Result<bool, Infallible> {
+//!         self.is_high().map(|high| !high)
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Light::*;
+//!
+//!     // The button bounces once when pressed.
+//!     let button = Button(vec![false, true, false, true, true, true]);
+//!     let mut button = Input::new(button).unwrap().on_rising(EventId::Toggle).debounce(2);
+//!     let mut sm = Machine::new(Off).as_enum();
+//!
+//!     for _ in 0..5 {
+//!         if let Some(event) = button.poll().unwrap() {
+//!             sm.apply(event).unwrap();
+//!         }
+//!     }
+//!
+//!     assert_eq!(sm.state_id(), StateId::On);
+//! }
+//! ```
+//!
+//! [eh]: https://docs.rs/embedded-hal
+
+use embedded_hal::digital::InputPin;
+
+/// Edge is a change of the level of an input.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Edge {
+    /// Rising is a change from low to high.
+    Rising,
+
+    /// Falling is a change from high to low.
+    Falling,
+}
+
+/// Input polls a pin, and maps the edges of its level to events.
+#[derive(Debug)]
+pub struct Input<P, E> {
+    pin: P,
+    rising: Option<E>,
+    falling: Option<E>,
+    debounce: u8,
+    high: bool,
+    changed: u8,
+}
+
+impl<P: InputPin, E: Copy> Input<P, E> {
+    /// new reads the current level of the pin, which edges are reported
+    /// against. No edge is mapped to an event yet.
+    pub fn new(mut pin: P) -> Result<Self, P::Error> {
+        let high = pin.is_high()?;
+
+        Ok(Input {
+            pin,
+            rising: None,
+            falling: None,
+            debounce: 1,
+            high,
+            changed: 0,
+        })
+    }
+
+    /// on_rising maps rising edges to the provided event.
+    pub fn on_rising(mut self, event: E) -> Self {
+        self.rising = Some(event);
+        self
+    }
+
+    /// on_falling maps falling edges to the provided event.
+    pub fn on_falling(mut self, event: E) -> Self {
+        self.falling = Some(event);
+        self
+    }
+
+    /// debounce only reports an edge once the new level has been read on
+    /// `polls` consecutive polls. By default, an edge is reported on the first
+    /// poll reading the new level.
+    pub fn debounce(mut self, polls: u8) -> Self {
+        self.debounce = polls.max(1);
+        self
+    }
+
+    /// is_high returns whether the level of the input was high, as of the last
+    /// reported edge.
+    pub fn is_high(&self) -> bool {
+        self.high
+    }
+
+    /// poll_edge reads the pin, and returns the edge it went through, if any.
+    pub fn poll_edge(&mut self) -> Result<Option<Edge>, P::Error> {
+        let high = self.pin.is_high()?;
+
+        if high == self.high {
+            self.changed = 0;
+            return Ok(None);
+        }
+
+        self.changed += 1;
+        if self.changed < self.debounce {
+            return Ok(None);
+        }
+
+        self.high = high;
+        self.changed = 0;
+
+        Ok(Some(if high { Edge::Rising } else { Edge::Falling }))
+    }
+
+    /// poll reads the pin, and returns the event mapped to the edge it went
+    /// through, if any.
+    pub fn poll(&mut self) -> Result<Option<E>, P::Error> {
+        Ok(match self.poll_edge()? {
+            Some(Edge::Rising) => self.rising,
+            Some(Edge::Falling) => self.falling,
+            None => None,
+        })
+    }
+
+    /// release consumes the input, and returns the pin.
+    pub fn release(self) -> P {
+        self.pin
+    }
+}
@@ -170,6 +170,98 @@
 //! # }
 //! ```
 //!
+//! Each initial state also comes with its own constructor, named after it in
+//! snake case, which doesn't need the state in scope:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! let sm = Lock::Machine::locked();
+//! # assert_eq!(sm.state(), Lock::Locked);
+//! # }
+//! ```
+//!
+//! Every state and event also has a constant instance, named after it in
+//! screaming snake case, which can be used in constant expressions:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! const KEYS: [Lock::TurnKey; 2] = [Lock::TURN_KEY, Lock::TURN_KEY];
+//!
+//! # fn main() {
+//! let sm = Lock::Machine::new(Lock::LOCKED).transition(KEYS[0]);
+//! # assert_eq!(sm.state(), Lock::UNLOCKED);
+//! # }
+//! ```
+//!
+//! The `sm!` macro brings the traits of this crate needed to use the machine
+//! into scope where it is invoked. To use the machine from another module,
+//! import its `prelude`, which contains the machine, its states, events and
+//! `Variant` enum, along with those traits:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! mod door {
+//!     use Lock::prelude::*;
+//!
+//!     pub fn lock() -> Variant {
+//!         Machine::new(Unlocked).transition(TurnKey).as_enum()
+//!     }
+//! }
+//! #
+//! # fn main() {
+//! # door::lock();
+//! # }
+//! ```
+//!
 //! We've initialised our machine in the `Locked` state. You can get the current
 //! state of the machine by sending the `state()` method to the machine:
 //!
@@ -199,6 +291,92 @@
 //! # }
 //! ```
 //!
+//! Machines compare equal only if both their state and the event that
+//! triggered it are equal. To compare the states alone, use `same_state()`,
+//! which is also available on the `Variant` enum:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Locked);
+//! let other = Machine::new(Unlocked).transition(TurnKey);
+//! assert!(sm.same_state(&other));
+//! assert!(sm.as_enum().same_state(&other.as_enum()));
+//! # }
+//! ```
+//!
+//! A machine also converts into its state, whatever event triggered it, so
+//! functions can accept either a state or a machine through `Into`:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # use Lock::*;
+//! fn unlocked(state: impl Into<Unlocked>) -> Unlocked {
+//!     state.into()
+//! }
+//!
+//! # fn main() {
+//! assert_eq!(unlocked(Unlocked), Unlocked);
+//! assert_eq!(unlocked(Machine::new(Locked).transition(TurnKey)), Unlocked);
+//! # }
+//! ```
+//!
+//! The `Debug` output of a machine names it, along with its state and trigger,
+//! which keeps logs and failing assertions readable:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Locked).transition(TurnKey);
+//! assert_eq!(
+//!     format!("{:?}", sm),
+//!     "Lock::Machine { state: Unlocked, trigger: Some(TurnKey) }",
+//! );
+//! # }
+//! ```
+//!
 //! While you _can_ use `sm.state()` with conditional branching to execute your
 //! code based on the current state, this can be a bit tedious, it's less
 //! idiomatic, and it prevents you from using one extra compile-time validation
@@ -273,8 +451,10 @@
 //! new state down the road, but forget to add it to a pattern match somewhere
 //! deep inside your code-base.
 //!
-//! To transition this machine to the `Unlocked` state, we send the `transition`
-//! method, using the `TurnKey` event:
+//! `as_enum` consumes the machine. To inspect a machine you want to keep using
+//! afterwards, `as_enum_ref` borrows it instead, and returns a `VariantRef`
+//! enum, with the same variants as `Variant`, each holding a reference to the
+//! machine. It is available on every machine type, and on `Variant` itself:
 //!
 //! ```rust
 //! # extern crate sm;
@@ -287,72 +467,82 @@
 //! #           Locked => Unlocked
 //! #           Unlocked => Locked
 //! #       }
-//! #
-//! #       Break {
-//! #           Locked, Unlocked => Broken
-//! #       }
 //! #   }
 //! # }
 //! #
 //! # fn main() {
 //! # use Lock::*;
-//! # let sm = Machine::new(Locked);
+//! let sm = Machine::new(Locked).transition(TurnKey);
+//!
+//! match sm.as_enum_ref() {
+//!     VariantRef::UnlockedByTurnKey(m) => assert_eq!(m.state(), Unlocked),
+//!     _ => unreachable!(),
+//! }
+//!
 //! let sm = sm.transition(TurnKey);
-//! assert_eq!(sm.state(), Unlocked);
 //! # }
 //! ```
 //!
-//! Because multiple events can lead to a single state, it's also important to
-//! be able to determine what event caused the machine to transition to the
-//! current state. We can ask this information using the `trigger()` method:
+//! As an alternative to matching on the enum, the `visit` method of `Variant`
+//! of machines marked `#[visitor]` calls the method of a visitor matching its
+//! variant. The visitor trait is named after the machine, such as `LockVisitor`
+//! for `Lock`, and has one `visit_<variant>` method per variant, which receives
+//! a reference to the machine. Visitors can be used as trait objects:
 //!
 //! ```rust
 //! # extern crate sm;
 //! # use sm::sm;
 //! # sm! {
+//! #   #[visitor]
 //! #   Lock {
-//! #       InitialStates { Locked, Unlocked }
+//! #       InitialStates { Locked }
 //! #
 //! #       TurnKey {
 //! #           Locked => Unlocked
 //! #           Unlocked => Locked
 //! #       }
-//! #
-//! #       Break {
-//! #           Locked, Unlocked => Broken
-//! #       }
 //! #   }
 //! # }
 //! #
-//! # fn main() {
-//! # use Lock::*;
-//! # let sm = Machine::new(Locked);
-//! # let sm = sm.transition(TurnKey);
-//! # assert_eq!(sm.state(), Unlocked);
-//! assert_eq!(sm.trigger().unwrap(), TurnKey);
-//! # }
-//! ```
+//! use sm::NoneEvent;
+//! use Lock::*;
 //!
-//! The `trigger()` method returns `None` if no state transition has taken place
-//! yet (ie. the machine is still in its initial state), and `Some(Event)` if
-//! one or more transitions have taken place.
+//! struct Open(bool);
 //!
-//! #### A word about Type-Safety and Ownership
+//! impl LockVisitor for Open {
+//!     fn visit_initial_locked(&mut self, _: &Machine<Locked, NoneEvent>) {
+//!         self.0 = false;
+//!     }
 //!
-//! It's important to realise that we've _consumed_ the original machine in the
-//! above example when we transitioned the machine to a different state, and got
-//! a newly initialised machine back in the `Unlocked` state.
+//!     fn visit_unlocked_by_turn_key(&mut self, _: &Machine<Unlocked, TurnKey>) {
+//!         self.0 = true;
+//!     }
 //!
-//! This allows us to safely use the machine without having to worry about
-//! multiple readers using the machine in different states.
+//!     fn visit_locked_by_turn_key(&mut self, _: &Machine<Locked, TurnKey>) {
+//!         self.0 = false;
+//!     }
+//! }
 //!
-//! All these checks are applied on compile-time, so the following example would
-//! fail to compile:
+//! # fn main() {
+//! let mut open = Open(false);
+//! let sm = Machine::new(Locked).transition(TurnKey).as_enum();
 //!
-//! ```rust,compile_fail
+//! sm.visit(&mut open as &mut dyn LockVisitor);
+//! assert!(open.0);
+//! # }
+//! ```
+//!
+//! When you only care about the state of the machine, and not about the event
+//! that triggered it, a machine marked `#[match_macro]` also gets a
+//! `<machine>_match!` macro, named after the machine in snake case. It matches
+//! on the enum variants by state, and has to list every state of the machine
+//! exactly once, in any order:
+//!
+//! ```rust
 //! # extern crate sm;
 //! # use sm::sm;
 //! # sm! {
+//! #   #[match_macro]
 //! #   Lock {
 //! #       InitialStates { Locked, Unlocked }
 //! #
@@ -370,29 +560,22 @@
 //! # fn main() {
 //! # use Lock::*;
 //! # let sm = Machine::new(Locked);
-//! let sm2 = sm.transition(TurnKey);
-//! assert_eq!(sm.state(), Locked);
+//! let open = lock_match!(sm,
+//!     Unlocked(_) => true,
+//!     Broken(m) => m.trigger().is_some(),
+//!     Locked(_) => false,
+//! );
+//! assert!(!open);
 //! # }
 //! ```
 //!
-//! This fails with the following compilation error:
-//!
-//! ```text
-//! error[E0382]: use of moved value: `sm`
-//!   --> src/lib.rs:315:12
-//!    |
-//! 22 | let sm2 = sm.transition(TurnKey);
-//!    |           -- value moved here
-//! 23 | assert_eq!(sm.state(), Locked);
-//!    |            ^^ value used here after move
-//!    |
-//!    = note: move occurs because `sm` has type `Lock::Machine<Lock::Locked>`, which does not implement the `Copy` trait
-//! ```
+//! The macro refers to the machine by its name, which has to be in scope
+//! wherever the macro is used.
 //!
-//! Similarly, we cannot execute undefined transitions, these are also caught by
-//! the compiler:
+//! To transition this machine to the `Unlocked` state, we send the `transition`
+//! method, using the `TurnKey` event:
 //!
-//! ```rust,compile_fail
+//! ```rust
 //! # extern crate sm;
 //! # use sm::sm;
 //! # sm! {
@@ -413,39 +596,16 @@
 //! # fn main() {
 //! # use Lock::*;
 //! # let sm = Machine::new(Locked);
-//! # let sm = sm.transition(Break);
-//! assert_eq!(sm.state(), Broken);
-//!
 //! let sm = sm.transition(TurnKey);
+//! assert_eq!(sm.state(), Unlocked);
 //! # }
 //! ```
 //!
-//! This fails with the following compilation error:
-//!
-//! ```text
-//! error[E0599]: no method named `transition` found for type `Lock::Machine<Lock::Broken>` in the current scope
-//!   --> src/lib.rs:360:13
-//!    |
-//! 4  | sm! {
-//!    | --- method `transition` not found for this
-//! ...
-//! 25 | let sm = sm.transition(TurnKey);
-//!    |             ^^^^^^^^^^
-//!    |
-//!    = help: items from traits can only be used if the trait is implemented and in scope
-//!    = note: the following trait defines an item `transition`, perhaps you need to implement it:
-//!            candidate #1: `sm::Transition`
-//! ```
-//!
-//! The error message is not great (and can potentially be improved in the
-//! future), but any error telling you `transition` is not implemented, or the
-//! passed in event type is invalid is an indication that you are trying to
-//! execute an illegal state transition.
-//!
-//! Finally, we are confined to initialising a new machine in only the states
-//! that we defined in `InitialStates`:
+//! Because multiple events can lead to a single state, it's also important to
+//! be able to determine what event caused the machine to transition to the
+//! current state. We can ask this information using the `trigger()` method:
 //!
-//! ```rust,compile_fail
+//! ```rust
 //! # extern crate sm;
 //! # use sm::sm;
 //! # sm! {
@@ -465,14 +625,443 @@
 //! #
 //! # fn main() {
 //! # use Lock::*;
-//! let sm = Machine::new(Broken);
-//! # assert_eq!(sm.state(), Broken);
+//! # let sm = Machine::new(Locked);
+//! # let sm = sm.transition(TurnKey);
+//! # assert_eq!(sm.state(), Unlocked);
+//! assert_eq!(sm.trigger().unwrap(), TurnKey);
 //! # }
 //! ```
 //!
-//! This results in the following error:
+//! The `trigger()` method returns `None` if no state transition has taken place
+//! yet (ie. the machine is still in its initial state), and `Some(Event)` if
+//! one or more transitions have taken place.
 //!
-//! ```text
+//! When the event is only known at runtime, such as when it is parsed from user
+//! input, the `transition_checked()` method of machines marked
+//! `#[transition_checked]` takes its `EventId` instead. It returns the machine
+//! as an enum variant if the event is valid for the current state, or a
+//! `TransitionError` otherwise:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   #[transition_checked]
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! # let sm = Machine::new(Locked);
+//! let sm = sm.transition_checked(EventId::TurnKey).unwrap();
+//! assert_eq!(sm.state_id(), StateId::Unlocked);
+//! # }
+//! ```
+//!
+//! The states, the events, and their `StateId` and `EventId` enums all convert
+//! into the `&'static str` of their name, as declared in the machine, which
+//! can be logged or used as a key without formatting it first:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let name: &'static str = Locked.into();
+//! assert_eq!(name, "Locked");
+//! assert_eq!(<&str>::from(EventId::TurnKey), "TurnKey");
+//! # }
+//! ```
+//!
+//! The `StateId` and `EventId` enums are plain, non-generic enums, which can be
+//! stored in maps, sent over the wire or persisted. Every state and event
+//! converts into its id, and implements `Identify`, which provides the id
+//! without an instance of the type:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! use sm::Identify;
+//!
+//! assert_eq!(StateId::from(Unlocked), StateId::Unlocked);
+//! assert_eq!(EventId::from(TurnKey), EventId::TurnKey);
+//! assert_eq!(<TurnKey as Identify>::ID, EventId::TurnKey);
+//! # }
+//! ```
+//!
+//! Once the machine is an enum variant, the `apply()` method of machines marked
+//! `#[apply]` transitions it in place on an `EventId`, and `apply_named()` on
+//! the name of an event, such as one received in a message. A name matching no
+//! event of the machine is rejected with `TransitionError::UnknownEvent`:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   #[apply]
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! use sm::TransitionError;
+//!
+//! let mut sm = Machine::new(Locked).as_enum();
+//! sm.apply(EventId::TurnKey).unwrap();
+//! sm.apply_named("TurnKey").unwrap();
+//! assert_eq!(sm.state_id(), StateId::Locked);
+//! assert_eq!(sm.apply_named("Kick"), Err(TransitionError::UnknownEvent));
+//! # }
+//! ```
+//!
+//! The `drive()` method of machines marked `#[drive]` applies a sequence of
+//! events lazily, returning an iterator over copies of the machine after every
+//! applied event, so the evolution of a machine can be inspected, collected, or
+//! zipped with other data. Events without a valid transition from the current
+//! state are skipped:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   #[drive]
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Locked).as_enum();
+//! let events = vec![EventId::TurnKey, EventId::TurnKey];
+//! let states: Vec<StateId> = sm.drive(events).map(|sm| sm.state_id()).collect();
+//! assert_eq!(states, vec![StateId::Unlocked, StateId::Locked]);
+//! # }
+//! ```
+//!
+//! A sequence of events can be checked before it is applied, such as a workflow
+//! submitted by a user or an imported log, using the `validate_sequence()`
+//! function generated for machines marked `#[validate_sequence]`. It follows
+//! the events through the transition table from the provided state, without
+//! creating a machine, and returns the state they lead to, or an `InvalidAt`
+//! error with the position of the first event without a valid transition:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   #[validate_sequence]
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! use sm::InvalidAt;
+//!
+//! let events = [EventId::TurnKey, EventId::TurnKey];
+//! assert_eq!(Lock::validate_sequence(StateId::Locked, &events), Ok(StateId::Locked));
+//!
+//! let events = [EventId::TurnKey, EventId::Break, EventId::TurnKey];
+//! assert_eq!(
+//!     Lock::validate_sequence(StateId::Locked, &events),
+//!     Err(InvalidAt { step: 2, state: StateId::Broken, event: EventId::TurnKey })
+//! );
+//! # }
+//! ```
+//!
+//! Multiple transitions can be chained with the `then()` method, which reads
+//! as a pipeline. Its guarded counterpart, `then_if()`, only transitions the
+//! machine if the guard accepts it, and returns the untouched machine as an
+//! enum variant otherwise:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Locked).then(TurnKey).then(TurnKey);
+//! assert_eq!(sm.state(), Locked);
+//!
+//! let sm = sm.then_if(Break, |_| false);
+//! assert!(sm.is_err());
+//! # }
+//! ```
+//!
+//! When a transition depends on a condition known at the call site, such as
+//! the result of a check made by the caller, `transition_with()` only
+//! transitions the machine if the closure returns `true`, and returns the
+//! untouched machine otherwise:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let authorized = false;
+//!
+//! let sm = Machine::new(Locked);
+//! let sm = sm.transition_with(TurnKey, || authorized).unwrap_err();
+//! assert_eq!(sm.state(), Locked);
+//! # }
+//! ```
+//!
+//! To check the state of a machine, without matching on its enum variant,
+//! the `is()` method compares it to a state type. There is also an `is_<state>()`
+//! method for every state, with the name of the state in snake case. Both are
+//! available on the machine and on its `Variant` enum:
+//!
+//! ```rust
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Locked);
+//! assert!(sm.is::<Locked>());
+//!
+//! let sm = sm.transition(Break).as_enum();
+//! assert!(sm.is_broken());
+//! # }
+//! ```
+//!
+//! #### A word about Type-Safety and Ownership
+//!
+//! It's important to realise that we've _consumed_ the original machine in the
+//! above example when we transitioned the machine to a different state, and got
+//! a newly initialised machine back in the `Unlocked` state.
+//!
+//! This allows us to safely use the machine without having to worry about
+//! multiple readers using the machine in different states.
+//!
+//! All these checks are applied on compile-time, so the following example would
+//! fail to compile:
+//!
+//! ```rust,compile_fail
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! # let sm = Machine::new(Locked);
+//! let sm2 = sm.transition(TurnKey);
+//! assert_eq!(sm.state(), Locked);
+//! # }
+//! ```
+//!
+//! This fails with the following compilation error:
+//!
+//! ```text
+//! error[E0382]: use of moved value: `sm`
+//!   --> src/lib.rs:315:12
+//!    |
+//! 22 | let sm2 = sm.transition(TurnKey);
+//!    |           -- value moved here
+//! 23 | assert_eq!(sm.state(), Locked);
+//!    |            ^^ value used here after move
+//!    |
+//!    = note: move occurs because `sm` has type `Lock::Machine<Lock::Locked>`, which does not implement the `Copy` trait
+//! ```
+//!
+//! Similarly, we cannot execute undefined transitions, these are also caught by
+//! the compiler:
+//!
+//! ```rust,compile_fail
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! # let sm = Machine::new(Locked);
+//! # let sm = sm.transition(Break);
+//! assert_eq!(sm.state(), Broken);
+//!
+//! let sm = sm.transition(TurnKey);
+//! # }
+//! ```
+//!
+//! This fails with the following compilation error:
+//!
+//! ```text
+//! error[E0599]: no method named `transition` found for type `Lock::Machine<Lock::Broken>` in the current scope
+//!   --> src/lib.rs:360:13
+//!    |
+//! 4  | sm! {
+//!    | --- method `transition` not found for this
+//! ...
+//! 25 | let sm = sm.transition(TurnKey);
+//!    |             ^^^^^^^^^^
+//!    |
+//!    = help: items from traits can only be used if the trait is implemented and in scope
+//!    = note: the following trait defines an item `transition`, perhaps you need to implement it:
+//!            candidate #1: `sm::Transition`
+//! ```
+//!
+//! The error message is not great (and can potentially be improved in the
+//! future), but any error telling you `transition` is not implemented, or the
+//! passed in event type is invalid is an indication that you are trying to
+//! execute an illegal state transition.
+//!
+//! Finally, we are confined to initialising a new machine in only the states
+//! that we defined in `InitialStates`:
+//!
+//! ```rust,compile_fail
+//! # extern crate sm;
+//! # use sm::sm;
+//! # sm! {
+//! #   Lock {
+//! #       InitialStates { Locked, Unlocked }
+//! #
+//! #       TurnKey {
+//! #           Locked => Unlocked
+//! #           Unlocked => Locked
+//! #       }
+//! #
+//! #       Break {
+//! #           Locked, Unlocked => Broken
+//! #       }
+//! #   }
+//! # }
+//! #
+//! # fn main() {
+//! # use Lock::*;
+//! let sm = Machine::new(Broken);
+//! # assert_eq!(sm.state(), Broken);
+//! # }
+//! ```
+//!
+//! This results in the following error:
+//!
+//! ```text
 //! error[E0277]: the trait bound `Lock::Broken: sm::InitialState` is not satisfied
 //!   --> src/lib.rs:417:10
 //!    |
@@ -482,14 +1071,828 @@
 //!    = note: required because of the requirements on the impl of `sm::NewMachine<Lock::Broken>` for `Lock::Machine<Lock::Broken>`
 //! ```
 //!
-//! #### The End 👋
+//! #### The End 👋
+//!
+//! And that's it! There's nothing else to it, except a declarative – and easy
+//! to read – state machine construction macro, and a type-safe and
+//! ownership-focused way of dealing with states and transitions, without any
+//! runtime overhead.
+//!
+//! **Go forth and transition!**
+//!
+//! ## Deriving a Machine from an Enum
+//!
+//! Codebases that already model their states as an enum can derive the same
+//! machine from it, by annotating its variants. The machine is generated in a
+//! module named after the enum, suffixed with `Machine`, and the enum can be
+//! created from the `StateId` of the machine:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::{AsEnum, Initializer, StateMachine, Transition};
+//!
+//! #[derive(Debug, PartialEq, StateMachine)]
+//! enum TurnStile {
+//!     #[state(initial)]
+//!     #[transition(on = Coin, to = Unlocked)]
+//!     Locked,
+//!
+//!     #[state]
+//!     #[transition(on = Push, to = Locked)]
+//!     Unlocked,
+//! }
+//!
+//! fn main() {
+//!     use TurnStileMachine::*;
+//!
+//!     let sm = Machine::new(Locked).transition(Coin).as_enum();
+//!     assert_eq!(TurnStile::from(sm.state_id()), TurnStile::Unlocked);
+//! }
+//! ```
+//!
+//! ## Transitions as Methods
+//!
+//! When a transition comes with logic of its own, the `#[sm::machine]`
+//! attribute builds the machine from the methods of an impl block instead.
+//! Every method annotated with `#[transition(From => To)]` becomes an event
+//! named after the method, in camel case, and the machine is generated in a
+//! module named after the type, suffixed with `Machine`. The `from` state of
+//! the first transition is the initial state, unless the initial states are
+//! listed as `#[sm::machine(InitialStates { ... })]`.
+//!
+//! A method of the same name is added to the machine in every `from` state of
+//! the method. It takes the value the impl block is for, and the arguments of
+//! the method, calls the method and returns the transitioned machine – paired
+//! with the value returned by the method, if any:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::{Initializer, Machine as M};
+//!
+//! struct Key;
+//!
+//! #[derive(Default)]
+//! struct Lock {
+//!     turns: u32,
+//! }
+//!
+//! #[sm::machine]
+//! impl Lock {
+//!     #[transition(Locked => Unlocked)]
+//!     #[transition(Unlocked => Locked)]
+//!     fn turn_key(&mut self, _key: Key) {
+//!         self.turns += 1;
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use LockMachine::*;
+//!
+//!     let mut lock = Lock::default();
+//!
+//!     let sm = Machine::new(Locked).turn_key(&mut lock, Key);
+//!     assert_eq!(sm.state(), Unlocked);
+//!
+//!     let sm = sm.turn_key(&mut lock, Key);
+//!     assert_eq!(sm.state(), Locked);
+//!     assert_eq!(lock.turns, 2);
+//! }
+//! ```
+//!
+//! ## Grouping Transitions by State
+//!
+//! Transitions are grouped by event by default. Machines with many states can
+//! be easier to read with their transitions grouped by state instead, using
+//! `state` blocks of `on Event => State;` statements. Both layouts describe
+//! the same machine, and can be mixed:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         state Locked {
+//!             on TurnKey => Unlocked;
+//!             on Break => Broken;
+//!         }
+//!
+//!         state Unlocked {
+//!             on TurnKey => Locked;
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let sm = Machine::new(Locked).transition(TurnKey).transition(TurnKey);
+//!     assert_eq!(sm.state(), Locked);
+//! }
+//! ```
+//!
+//! Whichever layout is used, the types of the states are documented with the
+//! transitions out of them and the transitions leading to them, so the page
+//! `cargo doc` generates for `Locked` above lists `TurnKey` leading to
+//! `Unlocked` and `Break` leading to `Broken`, and `Unlocked` as the state it
+//! is reachable from.
+//!
+//! ## Reusing Existing Enums
+//!
+//! Domains that already have an enum of their states or events can tie it to
+//! the machine, with `States = path;` and `Events = path;` lines before the
+//! initial states. The variants of the enum have to match the states or events
+//! of the machine, and `From` conversions are generated between the enum and
+//! the `StateId` or `EventId` of the machine:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! mod conn {
+//!     #[derive(Debug, PartialEq)]
+//!     pub enum State {
+//!         Idle,
+//!         Connected,
+//!     }
+//! }
+//!
+//! sm! {
+//!     Conn {
+//!         States = conn::State;
+//!         InitialStates { Idle }
+//!
+//!         Connect {
+//!             Idle => Connected
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Conn::*;
+//!
+//!     let sm = Machine::new(Idle).transition(Connect).as_enum();
+//!     assert_eq!(conn::State::from(sm.state_id()), conn::State::Connected);
+//!     assert_eq!(StateId::from(conn::State::Idle), StateId::Idle);
+//! }
+//! ```
+//!
+//! ## Extending Machines
+//!
+//! The `extend!` macro takes the definition of a machine, followed by an
+//! extension adding initial states and transitions to it, and generates the
+//! extended machine under the name of the extension. An extension can't
+//! redefine a transition the machine already has, which is reported when the
+//! macro is expanded.
+//!
+//! A crate can allow other crates to extend its machine, by exporting a macro
+//! passing the definition of the machine to `extend!`. Without an extension,
+//! `extend!` generates the machine as is:
+//!
+//! ```rust
+//! extern crate sm;
+//!
+//! #[macro_export]
+//! macro_rules! lock {
+//!     ($($extension:tt)*) => {
+//!         ::sm::extend! {
+//!             Lock {
+//!                 InitialStates { Locked }
+//!
+//!                 TurnKey {
+//!                     Locked => Unlocked
+//!                     Unlocked => Locked
+//!                 }
+//!             }
+//!
+//!             $($extension)*
+//!         }
+//!     };
+//! }
+//!
+//! // Upstream, the machine itself.
+//! lock!();
+//!
+//! // Downstream, the machine with a `Suspended` state. The module generated
+//! // for a machine is private, so it is used from the module it is generated
+//! // in.
+//! mod plugin {
+//!     lock! {
+//!         SuspendableLock {
+//!             Suspend {
+//!                 Locked, Unlocked => Suspended
+//!             }
+//!
+//!             Resume {
+//!                 Suspended => Locked
+//!             }
+//!         }
+//!     }
+//!
+//!     pub fn suspend() {
+//!         use self::SuspendableLock::*;
+//!
+//!         let sm = Machine::new(Locked).transition(TurnKey).transition(Suspend);
+//!         assert_eq!(sm.state(), Suspended);
+//!     }
+//! }
+//!
+//! fn main() {
+//!     plugin::suspend();
+//! }
+//! ```
+//!
+//! ## Composing Machines
+//!
+//! The `product!` macro composes two machines into their synchronous product,
+//! so a pair of machines – such as the client and server sides of a protocol –
+//! can be analysed and executed as a single machine. Events known to both
+//! machines are shared: they only transition the product when both machines
+//! transition on them. Other events transition the machine they belong to,
+//! leaving the other machine in its state.
+//!
+//! The states of the product pair a state of each machine, and are named after
+//! both. Only the pairs reachable from the initial states are generated:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::product;
+//!
+//! product! {
+//!     Session {
+//!         Client {
+//!             InitialStates { Idle }
+//!
+//!             Connect {
+//!                 Idle => Waiting
+//!             }
+//!
+//!             Accept {
+//!                 Waiting => Connected
+//!             }
+//!         }
+//!
+//!         Server {
+//!             InitialStates { Listening }
+//!
+//!             Accept {
+//!                 Listening => Serving
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Session::*;
+//!
+//!     let sm = Machine::new(IdleListening).transition(Connect).transition(Accept);
+//!     assert_eq!(sm.state(), ConnectedServing);
+//! }
+//! ```
+//!
+//! ## Reversing Machines
+//!
+//! The `reverse!` macro generates a machine, along with a companion machine
+//! named after it, suffixed with `Reversed`, whose transitions go the other way.
+//! Every state is an initial state of the companion, so it can walk back from
+//! any state, to implement "back" in a wizard, or to find the states an error
+//! state can be reached from. A machine reaching the same state on the same
+//! event from two different states can't be reversed, and is rejected:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::reverse;
+//!
+//! reverse! {
+//!     Wizard {
+//!         InitialStates { Name }
+//!
+//!         Next {
+//!             Name => Address
+//!             Address => Confirm
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use WizardReversed::*;
+//!
+//!     let sm = Machine::new(Confirm).transition(Next);
+//!     assert_eq!(sm.state(), Address);
+//! }
+//! ```
+//!
+//! ## Dual Machines
+//!
+//! The `dual!` macro takes the definition of a machine taking part in a
+//! protocol, followed by the name of its peer, and generates both machines.
+//! Every event of the machine has to start with `Send` or `Receive`, and the
+//! peer goes through the same states, receiving what the machine sends and
+//! sending what it receives, so both sides of the protocol are defined once
+//! and can't drift apart. The `EventId` enums of both machines convert into
+//! each other:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::dual;
+//!
+//! dual! {
+//!     Client {
+//!         InitialStates { Idle }
+//!
+//!         SendRequest {
+//!             Idle => Waiting
+//!         }
+//!
+//!         ReceiveResponse {
+//!             Waiting => Idle
+//!         }
+//!     }
+//!
+//!     Server
+//! }
+//!
+//! fn main() {
+//!     let client = Client::Machine::new(Client::Idle).transition(Client::SendRequest);
+//!     let server = Server::Machine::new(Server::Idle).transition(Server::ReceiveRequest);
+//!     assert_eq!(client.state(), Client::Waiting);
+//!     assert_eq!(server.state(), Server::Waiting);
+//!
+//!     let received: Server::EventId = Client::EventId::SendRequest.into();
+//!     assert_eq!(received, Server::EventId::ReceiveRequest);
+//! }
+//! ```
+//!
+//! Marking the machine `#[channel]` also generates typed channels for both
+//! sides, only able to send and receive the events valid in their state, see
+//! the [`channel`](channel/index.html) module.
+//!
+//! ## Restricting Machines
+//!
+//! The `subset!` macro takes the definition of a machine, followed by a
+//! restriction to some of its states and events, and generates the restricted
+//! machine under the name of the restriction, such as a read-only version of a
+//! workflow. Omitting `States` or `Events` keeps all of them. The macro checks
+//! that the listed states and events exist, that every listed event still has
+//! a transition, and that every state is reachable from the initial states
+//! that are kept:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::subset;
+//!
+//! subset! {
+//!     Workflow {
+//!         InitialStates { Draft, Published }
+//!
+//!         Submit {
+//!             Draft => Review
+//!         }
+//!
+//!         Approve {
+//!             Review => Published
+//!         }
+//!
+//!         View {
+//!             Draft, Review, Published => Published
+//!         }
+//!     }
+//!
+//!     ReadOnlyWorkflow {
+//!         States { Draft, Published }
+//!         Events { View }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use ReadOnlyWorkflow::*;
+//!
+//!     let sm = Machine::new(Draft).transition(View);
+//!     assert_eq!(sm.state(), Published);
+//! }
+//! ```
+//!
+//! ## Dynamic Machines
+//!
+//! Every state and trigger combination of a machine is a distinct type, which
+//! adds up to a lot of generated code for machines with many states. On
+//! targets where code size matters more than compile-time checks, a machine
+//! can be marked `#[dynamic]`. Only the `StateId` and `EventId` enums are
+//! generated then, along with a single `Machine` type implementing `Table`,
+//! whose `apply` method checks transitions at runtime:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::{sm, Table};
+//!
+//! sm! {
+//!     #[dynamic]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut sm = Machine::new(StateId::Locked).unwrap();
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!     assert_eq!(sm.state(), StateId::Unlocked);
+//! }
+//! ```
+//!
+//! Machines only ever used through their typed API can be marked
+//! `#[no_enum]` instead, which leaves out the `Variant` enum, its `AsEnum`
+//! implementations, and everything built on top of it, such as the `Table`
+//! implementation and the integrations enabled through Cargo features.
 //!
-//! And that's it! There's nothing else to it, except a declarative – and easy
-//! to read – state machine construction macro, and a type-safe and
-//! ownership-focused way of dealing with states and transitions, without any
-//! runtime overhead.
+//! Machines marked `#[erase_trigger]` keep their states in the type system,
+//! but store the event that triggered the last transition as an `EventId`.
+//! There is then a single `Machine<S>` type, and a single variant of the
+//! `Variant` enum, per state, named after it, instead of one per state and
+//! trigger.
 //!
-//! **Go forth and transition!**
+//! Machines are zero-sized as long as they haven't transitioned, but holding
+//! the trigger in an `Option` takes a byte once they have. Machines marked
+//! `#[zero_sized]` hold the trigger as is instead, a `NoneEvent` for machines
+//! that haven't transitioned yet, so they stay zero-sized. This can't be
+//! combined with `#[erase_trigger]`.
+//!
+//! Every state can be compared to every other state, and every event to every
+//! other event, which takes a number of `PartialEq` implementations growing
+//! with the square of the number of states and events. Machines marked
+//! `#[no_cross_eq]` only compare states and events to themselves.
+//!
+//! Beyond the types of a machine, code is only generated for what a machine
+//! opts into, with an attribute named after it: `#[apply]`, which generates the
+//! `apply` method of the `Variant` enum, `#[fold_events]`, which generates its
+//! `fold_events` method, `#[footprint]` and `#[handler]`, described below,
+//! `#[drive]`, `#[match_macro]`, `#[transition_checked]` and `#[visitor]`,
+//! described above, `#[replay]`, which generates its `replay` function, and
+//! `#[table]`, which implements the `Table` trait the runtime modules of this
+//! crate are built on. Attributes and Cargo features generating code on top of
+//! one of these imply it, so a machine marked `#[undo(n)]` has an `apply`
+//! method and implements `Table`. None of them can be combined with
+//! `#[no_enum]`, unlike `#[validate_sequence]`, described above, which only
+//! needs the `StateId` and `EventId` enums.
+//!
+//! Within the module of a machine, the states, the events, the `Variant` enum
+//! and the transitions are generated in separate inner modules, re-exported
+//! from the machine module. Incremental compilation splits code along module
+//! boundaries, so editing the transitions of a large machine only recompiles
+//! the code depending on them.
+//!
+//! ## Undoing Transitions
+//!
+//! Machines marked `#[undo(n)]` come with a `History` type, wrapping the
+//! `Variant` enum of the machine, or its `Machine` type if it's dynamic. It
+//! applies events the same way the machine does, while remembering up to `n`
+//! of the states the machine went through. Its `undo` method transitions the
+//! machine back to the previous state, if an event leads there from the
+//! current state, and returns that event:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[undo(8)]
+//!     Wizard {
+//!         InitialStates { Name }
+//!
+//!         Next {
+//!             Name => Address
+//!             Address => Payment
+//!         }
+//!
+//!         Back {
+//!             Address => Name
+//!             Payment => Address
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Wizard::*;
+//!
+//!     let mut wizard = History::new(Machine::new(Name).as_enum());
+//!     wizard.apply(EventId::Next).unwrap();
+//!     wizard.apply(EventId::Next).unwrap();
+//!
+//!     assert_eq!(wizard.undo(), Some(EventId::Back));
+//!     assert_eq!(wizard.machine().state_id(), StateId::Address);
+//! }
+//! ```
+//!
+//! Once `n` states are remembered, the oldest ones are forgotten. `undo`
+//! returns `None`, leaving the machine untouched, if there is no state to go
+//! back to, or no event leading back to it.
+//!
+//! ## Pushdown Machines
+//!
+//! Machines marked `#[pushdown(n)]` come with a `Pushdown` type, wrapping the
+//! `Variant` enum of the machine, or its `Machine` type if it's dynamic, with
+//! a stack of up to `n` machines. Its `push` method applies an event, and
+//! pushes the machine it transitioned from on the stack, so `pop` can return
+//! to it later, whichever state it was in, without a transition back to every
+//! state it could have been pushed from:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[pushdown(4)]
+//!     Game {
+//!         InitialStates { Playing }
+//!
+//!         Open {
+//!             Playing => Inventory
+//!         }
+//!
+//!         Pause {
+//!             Playing, Inventory => PausedMenu
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Game::*;
+//!
+//!     let mut game = Pushdown::new(Machine::new(Playing).as_enum());
+//!     game.apply(EventId::Open).unwrap();
+//!     game.push(EventId::Pause).unwrap();
+//!     assert_eq!(game.machine().state_id(), StateId::PausedMenu);
+//!
+//!     assert_eq!(game.pop(), Some(StateId::Inventory));
+//!     assert_eq!(game.pop(), None);
+//! }
+//! ```
+//!
+//! Events applied through `apply` leave the stack untouched. `push` returns a
+//! `TransitionError::StackFull` error, leaving the machine untouched, once `n`
+//! machines are on the stack.
+//!
+//! ## Handling Transitions
+//!
+//! Machines marked `#[handler]` come with a handler trait, named after them,
+//! such as `LockHandler` for `Lock`. It has one method per transition, named
+//! after the state the machine leaves and the event triggering it, and none of
+//! them has a default implementation, so adding a transition to the machine
+//! fails to compile until its handlers are updated. The `apply_with` method of
+//! the `Variant` enum, or of the `Machine` type of a dynamic machine, applies
+//! an event, and calls the method of the handler matching the transition:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[handler]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! use Lock::*;
+//!
+//! #[derive(Default)]
+//! struct Door {
+//!     opened: u32,
+//! }
+//!
+//! impl LockHandler for Door {
+//!     fn on_locked_turn_key(&mut self) {
+//!         self.opened += 1;
+//!     }
+//!
+//!     fn on_unlocked_turn_key(&mut self) {}
+//! }
+//!
+//! fn main() {
+//!     let mut door = Door::default();
+//!     let mut sm = Machine::new(Locked).as_enum();
+//!
+//!     sm.apply_with(EventId::TurnKey, &mut door).unwrap();
+//!     sm.apply_with(EventId::TurnKey, &mut door).unwrap();
+//!     assert_eq!(door.opened, 1);
+//! }
+//! ```
+//!
+//! Events without a transition from the current state are rejected, as they
+//! are by `apply`, without calling the handler.
+//!
+//! ## Memory Footprint
+//!
+//! Machines marked `#[footprint]` come with constants holding the size in bytes
+//! of their `StateId` and `EventId` types, as `STATE_ID_SIZE` and
+//! `EVENT_ID_SIZE`, of their `Variant` enum, or their `Machine` type if they're
+//! dynamic, as `MACHINE_SIZE`, and of their `History` type if they have one, as
+//! `HISTORY_SIZE`. The `assert_max_size!` macro fails the build if a type, such
+//! as a queue of events, grows beyond a budget:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::{assert_max_size, sm};
+//!
+//! sm! {
+//!     #[footprint]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! assert_max_size!(Lock::Variant, 8);
+//! assert_max_size!([Lock::EventId; 16], 16 * Lock::EVENT_ID_SIZE);
+//!
+//! fn main() {
+//!     assert!(Lock::MACHINE_SIZE <= 8);
+//! }
+//! ```
+//!
+//! The number of states and events of a machine are available as well, as
+//! `STATE_COUNT` and `EVENT_COUNT`, to size arrays indexed by their ids, such
+//! as the counters of the [`stats`](stats/index.html) module.
+//!
+//! ## Simulating Machines
+//!
+//! Transitions can be given a weight, written after the state they lead to, to
+//! walk the machine at random for load testing and Monte Carlo simulations
+//! using the [`simulate`](simulate/index.html) module, which, like the other
+//! runtime modules of this crate, works with machines marked `#[table]`.
+//! Transitions without a weight have a weight of `1`, and transitions with a
+//! weight of `0` are never taken by a simulation. Weights don't affect the
+//! machine otherwise:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::simulate::Simulate;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked @ 9
+//!             Unlocked => Locked
+//!         }
+//!
+//!         Break {
+//!             Locked => Broken @ 0
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut rng = || 7;
+//!     let sm = Machine::new(Locked).as_enum();
+//!     assert_eq!(sm.simulate_random(&mut rng, 3).count(), 3);
+//! }
+//! ```
+//!
+//! ## Machines in Separate Files
+//!
+//! Large machines can be kept out of the Rust source, in a file using the same
+//! syntax as the `sm!` macro. The path is relative to the directory containing
+//! the `Cargo.toml` of the crate, and the crate is recompiled whenever the
+//! file changes:
+//!
+//! ```text
+//! // machines/lock.sm
+//! Lock {
+//!     InitialStates { Locked }
+//!
+//!     TurnKey {
+//!         Locked => Unlocked
+//!         Unlocked => Locked
+//!     }
+//! }
+//! ```
+//!
+//! ```rust,ignore
+//! sm!(path = "machines/lock.sm");
+//! ```
+//!
+//! ## Cargo Features
+//!
+//! The following optional features integrate the generated machines with other
+//! crates. None of them are enabled by default.
+//!
+//! - `atomic` — adds the [`atomic`](atomic/index.html) module, to share the
+//!   state of a machine between interrupt handlers and the main loop.
+//! - `audit` — adds the [`audit`](audit/index.html) module, to log every
+//!   transition of a machine as a line of JSON. Implies `std`.
+//! - `critical-section` — adds the [`shared`](shared/index.html) module, to
+//!   share a machine between interrupt handlers and thread context on targets
+//!   without atomics.
+//! - `driver` — adds the [`driver`](driver/index.html) module, to drive a
+//!   machine from events received through any runtime, or from any input
+//!   mapped to events by a classifier. Combined with `tokio`, `async-std` or
+//!   `std`, a driver for the channels of that runtime is provided as well.
+//! - `embassy` — adds a driver for `embassy-sync` channels to the `driver`
+//!   module, for async embedded targets.
+//! - `embedded-hal` — adds the [`input`](input/index.html) module, to turn the
+//!   edges of `embedded-hal` digital inputs into events.
+//! - `flash` — adds a store for NOR flash to the [`persist`](persist/index.html)
+//!   module, using the `embedded-storage` traits. Implies `persist`.
+//! - `futures` — adds the [`stream`](stream/index.html) module, to drive a
+//!   machine from a stream of events, or to forward a stream of events into a
+//!   machine through a sink.
+//! - `persist` — adds the [`persist`](persist/index.html) module, and the
+//!   `save_to` and `load_from` methods on the `Variant` type of every machine,
+//!   to persist machines across restarts. Combined with `std`, converts
+//!   machines to and from the `DynMachine` of the `builder` module.
+//! - `postcard` — adds the [`postcard`](postcard/index.html) module, to encode
+//!   a machine in a few bytes on `no_std` targets. Implies `serde`.
+//! - `rtic` — adds the [`rtic`](rtic/index.html) module, to share a machine
+//!   between RTIC tasks. Implies `driver`.
+//! - `serde` — implements `Serialize` and `Deserialize` for the `StateId`,
+//!   `EventId` and `Variant` types of every machine, see the
+//!   [`serde`](serde/index.html) module.
+//! - `stats` — adds the [`stats`](stats/index.html) module, to count how many
+//!   times a machine entered each of its states.
+//! - `std` — links the standard library, for the blocking driver and the
+//!   terminal future of the `driver` module, the provided persistence stores,
+//!   the [`builder`](builder/index.html) module to assemble machines at
+//!   runtime, the [`exec`](exec/index.html) module to route events across
+//!   machines of different types, and the [`registry`](registry/index.html)
+//!   module, implements `std::error::Error` for `InvalidAt`, `ReplayError`
+//!   and `TransitionError`, and adds the `Explorer` of the `simulate` module and
+//!   the `SystemClock` of the `timed` module.
+//! - `timed` — adds the [`timed`](timed/index.html) module, to track the time
+//!   a machine has spent in its current state, using a pluggable clock, and
+//!   record histograms of the time spent in each state.
+//! - `tokio` — adds the [`watch`](watch/index.html) module, to publish state
+//!   changes on a watch channel.
+//! - `actix` — generates an `Actor` for every machine, handling each event of
+//!   the machine as a message.
+//! - `bevy` — generates a Bevy `Plugin` for every machine, storing the machine
+//!   in a `Component`, and applying the `Trigger` events sent to an entity to
+//!   its machine.
+//! - `const-fn` — generates a `next_state` constant function for every
+//!   machine, returning the state reached from a `StateId` on an `EventId`,
+//!   so the transition table can be queried in constant expressions.
+//! - `defmt` — implements `defmt::Format` for `InvalidAt`, `ReplayError` and
+//!   `TransitionError`, and for the states, events, `StateId`, `EventId` and
+//!   `Variant` types of every machine.
+//! - `ffi` — makes the `StateId` and `EventId` enums of every machine
+//!   `#[repr(C)]`, and generates an `FfiMachine` struct with `extern "C"`
+//!   functions to create it, apply events to it and read its state, named
+//!   after the machine, e.g. `turn_stile_new`, `turn_stile_apply` and
+//!   `turn_stile_state`.
+//! - `log` — logs every transition, and every rejected event, at the debug
+//!   level using the `log` crate. The target is the path of the module
+//!   generated for the machine, so logging can be enabled per machine. Every
+//!   event handled by a [`driver`](driver/index.html) is logged at the trace
+//!   level.
+//! - `metrics` — increments the `sm_transition` counter of the `metrics` crate
+//!   on every transition, labeled with the machine, the state it transitioned
+//!   from, and the event. Combined with `timed`, the `Metrics` recorder of the
+//!   `timed` module records the time spent in each state in the
+//!   `sm_dwell_seconds` histogram. Implies `std`.
+//! - `no-panic` — generates a `panic_free` module for every machine, with a
+//!   shim marked `#[no_panic]` around each of its runtime functions, such as
+//!   `apply`, `apply_named` and `replay`, taking slices instead of iterators.
+//!   A build with optimizations fails to link if any of them can panic.
+//! - `python` — generates a PyO3 `PyMachine` class for every machine marked
+//!   `#[python]`, exposed to Python under the name of the machine, with a
+//!   constructor taking the name of an initial state, and `apply`, `state`
+//!   and `permitted_events` methods. The class still has to be added to a
+//!   `#[pymodule]`. The code generated by PyO3 refers to `::core`, so crates
+//!   on the 2015 edition have to declare `extern crate core;`.
+//! - `tracing` — emits a `tracing` event at the debug level on every
+//!   transition, with the machine, `from`, `event` and `to` fields, and adds
+//!   a `span` method to the `StateId` type of every machine, returning a debug
+//!   span named after the state. The caller owns the span, and can enter it
+//!   for as long as the machine stays in that state.
+//! - `ufmt` — implements `ufmt::uDisplay` for `InvalidAt`, `ReplayError` and
+//!   `TransitionError`, and `ufmt::uDebug` and `ufmt::uDisplay` for the
+//!   states, events, `StateId`, `EventId` and `Variant` types of every
+//!   machine.
+//! - `wasm` — generates a `wasm-bindgen` class for every machine, exported
+//!   under the name of the machine, with a constructor taking the name of an
+//!   initial state, and `apply`, `state` and `permittedEvents` methods.
 
 #![no_std]
 #![forbid(
@@ -507,7 +1910,6 @@
     variant_size_differences,
 )]
 #![warn(
-    non_snake_case,
     rust_2018_idioms,
     unused_import_braces,
     unused_lifetimes,
@@ -515,7 +1917,12 @@
     unused_results,
     unused,
 )]
-#![feature(tool_lints)]
+#![cfg_attr(feature = "unstable", feature(tool_lints))]
+#![cfg_attr(all(feature = "unstable", feature = "atomic"), feature(integer_atomics))]
+#![cfg_attr(
+    all(feature = "unstable", any(feature = "atomic", feature = "critical-section")),
+    feature(min_const_fn)
+)]
 #![deny(clippy::all)]
 
 use core::fmt;
@@ -524,6 +1931,123 @@ use core::fmt;
 extern crate sm_macro;
 #[cfg(feature = "macro")]
 pub use sm_macro::sm;
+#[cfg(feature = "macro")]
+pub use sm_macro::dual;
+#[cfg(feature = "macro")]
+pub use sm_macro::extend;
+#[cfg(feature = "macro")]
+pub use sm_macro::machine;
+#[cfg(feature = "macro")]
+pub use sm_macro::product;
+#[cfg(feature = "macro")]
+pub use sm_macro::reverse;
+#[cfg(feature = "macro")]
+pub use sm_macro::subset;
+#[cfg(feature = "macro")]
+pub use sm_macro::StateMachine;
+#[cfg(feature = "critical-section")]
+extern crate critical_section;
+#[cfg(feature = "defmt")]
+extern crate defmt_crate as defmt;
+#[cfg(feature = "embedded-hal")]
+extern crate embedded_hal;
+#[cfg(feature = "embedded-storage")]
+extern crate embedded_storage;
+#[cfg(feature = "futures")]
+extern crate futures;
+#[cfg(feature = "postcard")]
+extern crate postcard_crate;
+#[cfg(feature = "serde")]
+extern crate serde_crate;
+#[cfg(feature = "std")]
+extern crate std;
+#[cfg(feature = "tokio")]
+extern crate tokio;
+#[cfg(feature = "ufmt")]
+extern crate ufmt_crate as ufmt;
+
+#[cfg(feature = "atomic")]
+pub mod atomic;
+#[cfg(feature = "audit")]
+pub mod audit;
+#[cfg(feature = "std")]
+pub mod builder;
+pub mod channel;
+#[cfg(feature = "driver")]
+pub mod driver;
+#[cfg(feature = "std")]
+pub mod exec;
+#[cfg(feature = "embedded-hal")]
+pub mod input;
+#[cfg(feature = "persist")]
+pub mod persist;
+#[cfg(feature = "postcard")]
+pub mod postcard;
+#[cfg(feature = "rtic")]
+pub mod rtic;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "serde")]
+pub mod serde;
+#[cfg(feature = "critical-section")]
+pub mod shared;
+pub mod simulate;
+#[cfg(feature = "stats")]
+pub mod stats;
+#[cfg(feature = "futures")]
+pub mod stream;
+#[cfg(feature = "timed")]
+pub mod timed;
+#[cfg(feature = "tokio")]
+pub mod watch;
+
+/// Items used by the code generated by the `sm!` macro. Not public API.
+#[doc(hidden)]
+pub mod export {
+    pub use core::{fmt, mem};
+
+    #[cfg(feature = "actix")]
+    pub extern crate actix_crate as actix;
+    #[cfg(feature = "bevy")]
+    pub extern crate bevy_app;
+    #[cfg(feature = "bevy")]
+    pub extern crate bevy_ecs;
+    #[cfg(feature = "defmt")]
+    pub extern crate defmt_crate as defmt;
+    #[cfg(feature = "log")]
+    pub extern crate log_crate as log;
+    #[cfg(feature = "metrics")]
+    pub extern crate metrics_crate as metrics;
+    #[cfg(feature = "no-panic")]
+    pub extern crate no_panic_crate as no_panic;
+    #[cfg(feature = "python")]
+    pub extern crate pyo3;
+    #[cfg(feature = "tracing")]
+    pub extern crate tracing_crate as tracing;
+    #[cfg(feature = "ufmt")]
+    pub extern crate ufmt_crate as ufmt;
+    #[cfg(feature = "wasm")]
+    pub extern crate wasm_bindgen;
+}
+
+/// assert_max_size fails the build if the size in bytes of a type exceeds the
+/// provided budget.
+///
+/// ```rust,compile_fail
+/// # extern crate sm;
+/// # use sm::assert_max_size;
+/// assert_max_size!(u64, 4);
+/// # fn main() {}
+/// ```
+#[macro_export]
+macro_rules! assert_max_size {
+    ($ty:ty, $max:expr) => {
+        const _: () = assert!(
+            $crate::export::mem::size_of::<$ty>() <= $max,
+            concat!("the size of `", stringify!($ty), "` exceeds ", stringify!($max), " bytes")
+        );
+    };
+}
 
 /// State is a custom [marker trait][m] that allows [unit-like structs][u] to be
 /// used as states in a state machine.
@@ -578,6 +2102,16 @@ pub trait Machine: fmt::Debug + Eq {
     /// initial state, caused by initialisation, not by an even-based
     /// transition.
     fn trigger(&self) -> Option<Self::Event>;
+
+    /// same_state returns whether both machines are in the same state,
+    /// regardless of the events that triggered it.
+    #[inline]
+    fn same_state<M: Machine>(&self, other: &M) -> bool
+    where
+        Self::State: PartialEq<M::State>,
+    {
+        self.state() == other.state()
+    }
 }
 
 /// Initializer defines the `new` method on a machine, that accepts any state
@@ -608,6 +2142,472 @@ pub trait Transition<E: Event>: fmt::Debug {
     /// transition consumes the state machine and returns a new machine in the
     /// correct state, based on the passed in event.
     fn transition(self, event: E) -> Self::Machine;
+
+    /// transition_with transitions the machine if the guard returns `true`.
+    /// Otherwise, the untouched machine is returned as the error.
+    fn transition_with<G>(self, event: E, guard: G) -> Result<Self::Machine, Self>
+    where
+        Self: Sized,
+        G: FnOnce() -> bool,
+    {
+        if guard() {
+            Ok(self.transition(event))
+        } else {
+            Err(self)
+        }
+    }
+
+    /// then transitions the machine, the same as `transition` does. It reads
+    /// better when chaining multiple transitions into a single flow, such as
+    /// `Machine::new(Draft).then(Submit).then(Approve)`.
+    #[inline]
+    fn then(self, event: E) -> Self::Machine
+    where
+        Self: Sized,
+    {
+        self.transition(event)
+    }
+
+    /// then_if transitions the machine if the guard accepts its current
+    /// state. If the guard rejects it, the machine is returned untouched, as
+    /// an enum variant, so the flow can carry on from there.
+    fn then_if<G>(self, event: E, guard: G) -> Result<Self::Machine, <Self as AsEnum>::Enum>
+    where
+        Self: AsEnum + Sized,
+        G: FnOnce(&Self) -> bool,
+    {
+        if guard(&self) {
+            Ok(self.transition(event))
+        } else {
+            Err(self.as_enum())
+        }
+    }
+}
+
+/// Accepts is implemented by a state for every event it has a transition on,
+/// so generic code can require a state to accept an event, such as
+/// `S: Accepts<Coin>`. The machine in that state then implements
+/// `Transition<Coin>`, leading to the `Next` state.
+///
+/// If you are using the `sm!` macro, then there is no need to implement this
+/// trait.
+pub trait Accepts<E: Event>: State {
+    /// Next is the state the event leads to.
+    type Next: State;
+}
+
+/// TryTransition provides the method required to transition a machine using an
+/// event that is only known at runtime.
+///
+/// Contrary to `Transition`, this trait is implemented on the `Variant` enum of
+/// a machine, for every event of that machine, and for its `EventId` enum. It
+/// allows you to apply any event to the machine, regardless of its current
+/// state, at the cost of having to handle invalid transitions at runtime.
+///
+/// If you are using the `sm!` macro, then there is no need to implement this
+/// trait.
+pub trait TryTransition<E>: Sized {
+    /// try_transition consumes the machine and returns a new machine in the
+    /// state the event leads to. If the event has no valid transition from the
+    /// current state, the untouched machine is returned as the error.
+    fn try_transition(self, event: E) -> Result<Self, Self>;
+
+    /// prepare computes the machine the event leads to, without changing the
+    /// current machine. The returned `Prepared` value has to be committed for
+    /// the transition to take effect. If the event has no valid transition from
+    /// the current state, `None` is returned.
+    ///
+    /// This allows side effects of a transition, such as an asynchronous action,
+    /// to run between both phases: if they are cancelled before the transition
+    /// is committed, the machine is left in its previous state.
+    fn prepare(&mut self, event: E) -> Option<Prepared<'_, Self>>
+    where
+        Self: Clone,
+    {
+        let next = self.clone().try_transition(event).ok()?;
+
+        Some(Prepared {
+            machine: self,
+            next,
+        })
+    }
+}
+
+/// Prepared is a transition that has been computed by `TryTransition::prepare`,
+/// but not yet applied. Dropping it without calling `commit` leaves the machine
+/// untouched.
+#[derive(Debug)]
+#[must_use = "the transition is only applied after calling `commit`"]
+#[allow(explicit_outlives_requirements)]
+pub struct Prepared<'a, V: 'a> {
+    machine: &'a mut V,
+    next: V,
+}
+
+impl<'a, V> Prepared<'a, V> {
+    /// next returns the machine that will be stored when committing.
+    pub fn next(&self) -> &V {
+        &self.next
+    }
+
+    /// commit applies the transition, and returns a reference to the updated
+    /// machine.
+    pub fn commit(self) -> &'a mut V {
+        *self.machine = self.next;
+        self.machine
+    }
+}
+
+/// Snapshots is the iterator returned by the `drive` method of the `Variant`
+/// enum of a machine. It applies the events one after the other, and yields a
+/// copy of the machine after every applied event. Events without a valid
+/// transition from the current state are skipped.
+#[derive(Clone, Debug)]
+pub struct Snapshots<V, I> {
+    machine: V,
+    events: I,
+}
+
+impl<V, I> Snapshots<V, I> {
+    /// new returns an iterator applying the events to the machine.
+    pub fn new(machine: V, events: I) -> Self {
+        Snapshots { machine, events }
+    }
+
+    /// into_machine consumes the iterator, and returns the machine with the
+    /// events applied so far.
+    pub fn into_machine(self) -> V {
+        self.machine
+    }
+}
+
+impl<V, I> Iterator for Snapshots<V, I>
+where
+    V: TryTransition<I::Item> + Clone,
+    I: Iterator,
+{
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        for event in &mut self.events {
+            if let Some(prepared) = self.machine.prepare(event) {
+                return Some(prepared.commit().clone());
+            }
+        }
+
+        None
+    }
+}
+
+/// Id is implemented by the `StateId` and `EventId` enums of a machine, which
+/// identify its states and events at runtime.
+///
+/// If you are using the `sm!` macro, then there is no need to implement this
+/// trait.
+pub trait Id: fmt::Debug + Copy + Eq + 'static {
+    /// ALL contains every id of this type, in the order in which they were
+    /// declared.
+    const ALL: &'static [Self];
+
+    /// NAMES contains the name of every id of this type, in the same order as
+    /// `ALL`.
+    const NAMES: &'static [&'static str];
+
+    /// index returns the position of the id in `ALL`.
+    fn index(self) -> usize;
+
+    /// name returns the name of the id, as declared in the machine. An id
+    /// whose index is out of the bounds of `NAMES` has an empty name.
+    fn name(self) -> &'static str {
+        Self::NAMES.get(self.index()).copied().unwrap_or("")
+    }
+
+    /// from_name returns the id with the provided name, if any.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.iter().cloned().find(|id| id.name() == name)
+    }
+}
+
+/// Identify is implemented by the states and events of a machine, and links
+/// each of them to its `StateId` or `EventId`, so the state of a machine can be
+/// checked against a state type without an instance of it.
+///
+/// If you are using the `sm!` macro, then there is no need to implement this
+/// trait.
+pub trait Identify {
+    /// Id is the type identifying this type at runtime.
+    type Id: Id;
+
+    /// ID is the runtime identifier of this type.
+    const ID: Self::Id;
+}
+
+/// Table describes the transitions of a state machine in terms of its runtime
+/// identifiers, allowing generic code to reason about a machine without
+/// knowing its concrete types.
+///
+/// The `sm!` macro implements this trait on the `Variant` enum of a machine.
+pub trait Table {
+    /// StateId identifies the states of the machine.
+    type StateId: Id;
+
+    /// EventId identifies the events of the machine.
+    type EventId: Id;
+
+    /// NAME is the name of the machine, as declared in the macro.
+    const NAME: &'static str;
+
+    /// state returns the current state of the machine.
+    fn state(&self) -> Self::StateId;
+
+    /// next returns the state the machine transitions to when the event is
+    /// triggered in the provided state, or `None` if no such transition exists.
+    fn next(state: Self::StateId, event: Self::EventId) -> Option<Self::StateId>;
+
+    /// weight returns the weight of the transition triggered by the event in
+    /// the provided state, declared as `State => Next @ weight` in the macro,
+    /// and `1` by default. Weights are only used when simulating the machine.
+    fn weight(_: Self::StateId, _: Self::EventId) -> u32 {
+        1
+    }
+
+    /// is_terminal returns `true` if no event can transition the machine out
+    /// of the provided state.
+    fn is_terminal(state: Self::StateId) -> bool {
+        Self::EventId::ALL
+            .iter()
+            .all(|&event| Self::next(state, event).is_none())
+    }
+}
+
+/// ReplayError is returned by the `replay` method of the `Variant` enum of a
+/// machine, when a log of events cannot be replayed.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReplayError<S, E> {
+    /// NotInitial is returned when the log starts from a state that is not an
+    /// initial state of the machine.
+    NotInitial(S),
+
+    /// Rejected is returned when an event of the log has no valid transition
+    /// from the state the machine is in at that point.
+    Rejected {
+        /// step is the position of the rejected event in the log.
+        step: usize,
+
+        /// state is the state of the machine when the event was rejected.
+        state: S,
+
+        /// event is the rejected event.
+        event: E,
+    },
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for ReplayError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotInitial(state) => write!(f, "{:?} is not an initial state", state),
+            ReplayError::Rejected { step, state, event } => write!(
+                f,
+                "event {:?} at step {} has no transition from {:?}",
+                event, step, state
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for ReplayError<S, E> {}
+
+#[cfg(feature = "defmt")]
+impl<S: defmt::Format, E: defmt::Format> defmt::Format for ReplayError<S, E> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            ReplayError::NotInitial(state) => defmt::write!(f, "{} is not an initial state", state),
+            ReplayError::Rejected { step, state, event } => defmt::write!(
+                f,
+                "event {} at step {} has no transition from {}",
+                event,
+                step,
+                state
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<S: ufmt::uDebug, E: ufmt::uDebug> ufmt::uDisplay for ReplayError<S, E> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        match self {
+            ReplayError::NotInitial(state) => ufmt::uwrite!(f, "{:?} is not an initial state", state),
+            ReplayError::Rejected { step, state, event } => ufmt::uwrite!(
+                f,
+                "event {:?} at step {} has no transition from {:?}",
+                event,
+                step,
+                state
+            ),
+        }
+    }
+}
+
+/// InvalidAt is returned by the `validate_sequence` function generated for
+/// every machine, when an event of a sequence has no valid transition from the
+/// state reached by the events before it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct InvalidAt<S, E> {
+    /// step is the position of the invalid event in the sequence.
+    pub step: usize,
+
+    /// state is the state reached before the invalid event.
+    pub state: S,
+
+    /// event is the invalid event.
+    pub event: E,
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for InvalidAt<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "event {:?} at step {} has no transition from {:?}",
+            self.event, self.step, self.state
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for InvalidAt<S, E> {}
+
+#[cfg(feature = "defmt")]
+impl<S: defmt::Format, E: defmt::Format> defmt::Format for InvalidAt<S, E> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        defmt::write!(
+            f,
+            "event {} at step {} has no transition from {}",
+            self.event,
+            self.step,
+            self.state
+        )
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<S: ufmt::uDebug, E: ufmt::uDebug> ufmt::uDisplay for InvalidAt<S, E> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(
+            f,
+            "event {:?} at step {} has no transition from {:?}",
+            self.event,
+            self.step,
+            self.state
+        )
+    }
+}
+
+/// TransitionError describes why an event could not be applied to a machine,
+/// in terms of its runtime identifiers. It is returned by the `apply` method
+/// of the `Variant` enum of a machine, and by the runtime APIs that take
+/// events on behalf of a machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TransitionError<S, E> {
+    /// Invalid is returned when the event has no valid transition from the
+    /// current state of the machine.
+    Invalid {
+        /// state is the state of the machine when the event was rejected.
+        state: S,
+
+        /// event is the rejected event.
+        event: E,
+    },
+
+    /// QueueFull is returned when the event could not be queued, because the
+    /// queue of pending events is full.
+    QueueFull(E),
+
+    /// StackFull is returned when the event could not be pushed, because the
+    /// stack of a pushdown machine is full.
+    StackFull(E),
+
+    /// Replay is returned when a log of events could not be replayed.
+    Replay(ReplayError<S, E>),
+
+    /// UnknownEvent is returned when an event is looked up by a name that
+    /// isn't the name of any event of the machine.
+    UnknownEvent,
+}
+
+impl<S, E> From<ReplayError<S, E>> for TransitionError<S, E> {
+    fn from(err: ReplayError<S, E>) -> Self {
+        TransitionError::Replay(err)
+    }
+}
+
+impl<S: fmt::Debug, E: fmt::Debug> fmt::Display for TransitionError<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionError::Invalid { state, event } => {
+                write!(f, "event {:?} has no transition from {:?}", event, state)
+            }
+            TransitionError::QueueFull(event) => {
+                write!(f, "event {:?} was rejected by a full queue", event)
+            }
+            TransitionError::StackFull(event) => {
+                write!(f, "event {:?} was rejected by a full stack", event)
+            }
+            TransitionError::Replay(err) => write!(f, "replay failed: {}", err),
+            TransitionError::UnknownEvent => write!(f, "unknown event"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<S: fmt::Debug, E: fmt::Debug> std::error::Error for TransitionError<S, E> {}
+
+#[cfg(feature = "defmt")]
+impl<S: defmt::Format, E: defmt::Format> defmt::Format for TransitionError<S, E> {
+    fn format(&self, f: defmt::Formatter<'_>) {
+        match self {
+            TransitionError::Invalid { state, event } => {
+                defmt::write!(f, "event {} has no transition from {}", event, state)
+            }
+            TransitionError::QueueFull(event) => {
+                defmt::write!(f, "event {} was rejected by a full queue", event)
+            }
+            TransitionError::StackFull(event) => {
+                defmt::write!(f, "event {} was rejected by a full stack", event)
+            }
+            TransitionError::Replay(err) => defmt::write!(f, "replay failed: {}", err),
+            TransitionError::UnknownEvent => defmt::write!(f, "unknown event"),
+        }
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl<S: ufmt::uDebug, E: ufmt::uDebug> ufmt::uDisplay for TransitionError<S, E> {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        match self {
+            TransitionError::Invalid { state, event } => {
+                ufmt::uwrite!(f, "event {:?} has no transition from {:?}", event, state)
+            }
+            TransitionError::QueueFull(event) => {
+                ufmt::uwrite!(f, "event {:?} was rejected by a full queue", event)
+            }
+            TransitionError::StackFull(event) => {
+                ufmt::uwrite!(f, "event {:?} was rejected by a full stack", event)
+            }
+            TransitionError::Replay(err) => ufmt::uwrite!(f, "replay failed: {}", err),
+            TransitionError::UnknownEvent => f.write_str("unknown event"),
+        }
+    }
 }
 
 /// AsEnum provides the method to convert a state machine instance to an enum
@@ -622,6 +2622,7 @@ pub trait AsEnum: fmt::Debug {
 
     /// as_enum consumes the state machine and returns a new enum variant that
     /// represents the consumed state machine.
+    #[allow(clippy::wrong_self_convention)]
     fn as_enum(self) -> Self::Enum;
 }
 
@@ -0,0 +1,731 @@
+//! Persist machines across restarts.
+//!
+//! A `Store` saves and loads `Snapshot`s of machines, keyed by a machine id of
+//! your choosing. With the `persist` feature enabled, the `sm!` macro adds
+//! `save_to` and `load_from` methods to the `Variant` type of every machine,
+//! which use any store.
+//!
+//! A `Journal` is a store that also records the events applied to a machine
+//! since its last snapshot. `Journaled` combines both: it journals every
+//! applied event, takes a new snapshot at a fixed interval, and recovers a
+//! machine by loading its snapshot and replaying the events journaled since.
+//!
+//! With the `std` feature enabled, an in-memory store (`Memory`) and a
+//! file-based store (`Files`) are provided. Both are journals as well. With the
+//! `flash` feature enabled, a store for NOR flash (`Flash`) is provided.
+
+use core::fmt;
+
+use crate::{Id, Table, TryTransition};
+
+/// Snapshot is the persisted form of a machine: its current state, and the
+/// event that triggered it, if any.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Snapshot<S, E> {
+    /// state is the current state of the machine.
+    pub state: S,
+
+    /// trigger is the event that caused the current state, or `None` for a
+    /// machine still in its initial state.
+    pub trigger: Option<E>,
+}
+
+/// Store saves and loads snapshots of machines, keyed by a machine id.
+pub trait Store<S: Id, E: Id> {
+    /// Error is returned when the store fails to save or load a snapshot.
+    type Error;
+
+    /// save stores the snapshot under the given key, replacing any previous
+    /// snapshot stored under that key.
+    fn save(&mut self, key: &str, snapshot: Snapshot<S, E>) -> Result<(), Self::Error>;
+
+    /// load returns the snapshot stored under the given key, or `None` if no
+    /// snapshot was saved under that key.
+    fn load(&self, key: &str) -> Result<Option<Snapshot<S, E>>, Self::Error>;
+}
+
+/// Journal is a store that also records, for every key, the events applied
+/// to the machine since its last snapshot.
+///
+/// Saving a snapshot has to discard the events journaled under that key, in
+/// the same atomic operation: a crash must either leave the previous snapshot
+/// and all its events in place, or the new snapshot without any events.
+pub trait Journal<S: Id, E: Id>: Store<S, E> {
+    /// append records an event under the given key, and returns the number of
+    /// events journaled since the last snapshot.
+    fn append(&mut self, key: &str, event: E) -> Result<usize, Self::Error>;
+
+    /// events calls `f` with every event journaled under the given key since
+    /// the last snapshot, in the order in which they were appended.
+    fn events<F: FnMut(E)>(&self, key: &str, f: F) -> Result<(), Self::Error>;
+}
+
+/// Restore converts a machine to and from a `Snapshot`.
+///
+/// If you are using the `sm!` macro with the `persist` feature enabled, then
+/// there is no need to implement this trait.
+pub trait Restore: Table + TryTransition<<Self as Table>::EventId> + Clone {
+    /// snapshot returns the snapshot of the machine.
+    fn snapshot(&self) -> Snapshot<Self::StateId, Self::EventId>;
+
+    /// restore returns the machine described by the snapshot, or `None` if the
+    /// snapshot doesn't describe a machine that can exist.
+    fn restore(snapshot: Snapshot<Self::StateId, Self::EventId>) -> Option<Self>;
+}
+
+/// Error is returned when loading a machine from a store fails.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Error<E> {
+    /// Store is returned when the store itself failed.
+    Store(E),
+
+    /// Invalid is returned when the loaded snapshot doesn't describe a machine
+    /// that can exist, for example because the trigger cannot lead to the
+    /// state, or when a journaled event cannot be replayed.
+    Invalid,
+}
+
+impl<E: fmt::Display> fmt::Display for Error<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Store(err) => write!(f, "store error: {}", err),
+            Error::Invalid => f.write_str("the snapshot does not describe a valid machine"),
+        }
+    }
+}
+
+/// Journaled persists machines using a journal, taking a new snapshot every
+/// `interval` applied events. With the `std` feature, the in-memory store
+/// (`Memory`) can be used as the journal.
+#[derive(Debug)]
+pub struct Journaled<J> {
+    journal: J,
+    interval: usize,
+}
+
+impl<J> Journaled<J> {
+    /// new wraps the journal, taking a new snapshot every `interval` applied
+    /// events. An interval of zero is treated as one.
+    pub fn new(journal: J, interval: usize) -> Self {
+        Journaled {
+            journal,
+            interval: interval.max(1),
+        }
+    }
+
+    /// journal returns a reference to the wrapped journal.
+    pub fn journal(&self) -> &J {
+        &self.journal
+    }
+
+    /// into_journal consumes the wrapper, and returns the wrapped journal.
+    pub fn into_journal(self) -> J {
+        self.journal
+    }
+
+    /// start stores the first snapshot of a machine, discarding anything
+    /// previously stored under the same key.
+    pub fn start<V>(&mut self, key: &str, machine: &V) -> Result<(), J::Error>
+    where
+        V: Restore,
+        J: Journal<V::StateId, V::EventId>,
+    {
+        self.journal.save(key, machine.snapshot())
+    }
+
+    /// apply transitions the machine using the event, and journals the event.
+    /// It returns `false`, leaving the machine untouched, if the event has no
+    /// valid transition from the current state.
+    ///
+    /// The machine is only transitioned once the event is journaled. If that
+    /// fails, the machine is left untouched as well.
+    pub fn apply<V>(&mut self, key: &str, machine: &mut V, event: V::EventId) -> Result<bool, J::Error>
+    where
+        V: Restore,
+        J: Journal<V::StateId, V::EventId>,
+    {
+        let prepared = match machine.prepare(event) {
+            Some(prepared) => prepared,
+            None => return Ok(false),
+        };
+
+        let journaled = self.journal.append(key, event)?;
+        let machine = prepared.commit();
+
+        if journaled >= self.interval {
+            self.journal.save(key, machine.snapshot())?;
+        }
+
+        Ok(true)
+    }
+
+    /// recover loads the last snapshot stored under the given key, and replays
+    /// the events journaled since. It returns `None` if no snapshot was stored
+    /// under that key.
+    pub fn recover<V>(&self, key: &str) -> Result<Option<V>, Error<J::Error>>
+    where
+        V: Restore,
+        J: Journal<V::StateId, V::EventId>,
+    {
+        let snapshot = match self.journal.load(key).map_err(Error::Store)? {
+            Some(snapshot) => snapshot,
+            None => return Ok(None),
+        };
+
+        let mut machine = Some(V::restore(snapshot).ok_or(Error::Invalid)?);
+        self.journal
+            .events(key, |event| {
+                machine = machine.take().and_then(|m| m.try_transition(event).ok());
+            })
+            .map_err(Error::Store)?;
+
+        machine.map(Some).ok_or(Error::Invalid)
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::std_stores::{Files, Memory};
+
+#[cfg(feature = "std")]
+mod std_stores {
+    use core::convert::Infallible;
+    use std::collections::HashMap;
+    use std::fs::{self, OpenOptions};
+    use std::io::{Error, ErrorKind, Result, Write};
+    use std::path::PathBuf;
+    use std::string::String;
+    use std::vec::Vec;
+
+    use super::{Journal, Snapshot, Store};
+    use crate::Id;
+
+    /// Memory keeps snapshots and journals in memory. This is mostly useful in
+    /// tests, or for machines that only need to survive being dropped and
+    /// recreated.
+    ///
+    /// ```rust
+    /// extern crate sm;
+    /// use sm::persist::Memory;
+    /// use sm::sm;
+    ///
+    /// sm! {
+    ///     Lock {
+    ///         InitialStates { Locked }
+    ///
+    ///         TurnKey {
+    ///             Locked => Unlocked
+    ///             Unlocked => Locked
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     use Lock::*;
+    ///
+    ///     let mut store = Memory::new();
+    ///     let sm = Machine::new(Locked).transition(TurnKey).as_enum();
+    ///     sm.save_to(&mut store, "front-door").unwrap();
+    ///
+    ///     let sm = Variant::load_from(&store, "front-door").unwrap().unwrap();
+    ///     assert_eq!(sm.state_id(), StateId::Unlocked);
+    ///     assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+    ///
+    ///     assert!(Variant::load_from(&store, "back-door").unwrap().is_none());
+    /// }
+    /// ```
+    ///
+    /// It is a journal as well, so it can back a `Journaled` wrapper:
+    ///
+    /// ```rust
+    /// extern crate sm;
+    /// use sm::persist::{Journaled, Memory};
+    /// use sm::sm;
+    ///
+    /// sm! {
+    ///     Lock {
+    ///         InitialStates { Locked }
+    ///
+    ///         TurnKey {
+    ///             Locked => Unlocked
+    ///             Unlocked => Locked
+    ///         }
+    ///
+    ///         Break {
+    ///             Locked => Broken
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// fn main() {
+    ///     use Lock::*;
+    ///
+    ///     let mut journaled = Journaled::new(Memory::new(), 2);
+    ///     let mut sm = Machine::new(Locked).as_enum();
+    ///     journaled.start("front-door", &sm).unwrap();
+    ///
+    ///     assert_eq!(journaled.apply("front-door", &mut sm, EventId::TurnKey), Ok(true));
+    ///     assert_eq!(journaled.apply("front-door", &mut sm, EventId::Break), Ok(false));
+    ///     assert_eq!(journaled.apply("front-door", &mut sm, EventId::TurnKey), Ok(true));
+    ///     assert_eq!(journaled.apply("front-door", &mut sm, EventId::Break), Ok(true));
+    ///
+    ///     let recovered: Variant = journaled.recover("front-door").unwrap().unwrap();
+    ///     assert_eq!(recovered.state_id(), StateId::Broken);
+    /// }
+    /// ```
+    #[derive(Debug)]
+    pub struct Memory<S, E> {
+        snapshots: HashMap<String, (Snapshot<S, E>, Vec<E>)>,
+    }
+
+    impl<S, E> Memory<S, E> {
+        /// new creates an empty store.
+        pub fn new() -> Self {
+            Memory {
+                snapshots: HashMap::new(),
+            }
+        }
+    }
+
+    impl<S, E> Default for Memory<S, E> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<S: Id, E: Id> Store<S, E> for Memory<S, E> {
+        type Error = Infallible;
+
+        fn save(&mut self, key: &str, snapshot: Snapshot<S, E>) -> core::result::Result<(), Infallible> {
+            let _ = self.snapshots.insert(String::from(key), (snapshot, Vec::new()));
+            Ok(())
+        }
+
+        fn load(&self, key: &str) -> core::result::Result<Option<Snapshot<S, E>>, Infallible> {
+            Ok(self.snapshots.get(key).map(|(snapshot, _)| *snapshot))
+        }
+    }
+
+    /// Events appended under a key without a snapshot are discarded.
+    impl<S: Id, E: Id> Journal<S, E> for Memory<S, E> {
+        fn append(&mut self, key: &str, event: E) -> core::result::Result<usize, Infallible> {
+            Ok(match self.snapshots.get_mut(key) {
+                Some((_, events)) => {
+                    events.push(event);
+                    events.len()
+                }
+                None => 0,
+            })
+        }
+
+        fn events<F: FnMut(E)>(&self, key: &str, f: F) -> core::result::Result<(), Infallible> {
+            if let Some((_, events)) = self.snapshots.get(key) {
+                events.iter().cloned().for_each(f);
+            }
+
+            Ok(())
+        }
+    }
+
+    /// Files keeps every snapshot in its own file, named after its key, in the
+    /// given directory. The first line of the file contains the name of the
+    /// state, followed by the name of the trigger, if any. Every journaled
+    /// event is appended on its own line. Keys have to be valid file names.
+    ///
+    /// Snapshots are written to a temporary file first, and then moved in
+    /// place, so a crash while saving leaves the previous snapshot and its
+    /// journal intact. Appending an event under a key without a snapshot fails.
+    #[derive(Debug)]
+    pub struct Files {
+        dir: PathBuf,
+    }
+
+    impl Files {
+        /// new creates a store keeping its files in the given directory, which
+        /// has to exist.
+        pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+            Files { dir: dir.into() }
+        }
+
+        fn read(&self, key: &str) -> Result<Option<String>> {
+            match fs::read_to_string(self.dir.join(key)) {
+                Ok(contents) => Ok(Some(contents)),
+                Err(ref err) if err.kind() == ErrorKind::NotFound => Ok(None),
+                Err(err) => Err(err),
+            }
+        }
+    }
+
+    impl<S: Id, E: Id> Store<S, E> for Files {
+        type Error = Error;
+
+        fn save(&mut self, key: &str, snapshot: Snapshot<S, E>) -> Result<()> {
+            let mut contents = String::from(snapshot.state.name());
+            if let Some(trigger) = snapshot.trigger {
+                contents.push(' ');
+                contents.push_str(trigger.name());
+            }
+            contents.push('\n');
+
+            let path = self.dir.join(key);
+            let tmp = path.with_extension("tmp");
+            fs::write(&tmp, contents)?;
+            fs::rename(&tmp, &path)
+        }
+
+        fn load(&self, key: &str) -> Result<Option<Snapshot<S, E>>> {
+            let contents = match self.read(key)? {
+                Some(contents) => contents,
+                None => return Ok(None),
+            };
+
+            let mut names = contents.lines().next().unwrap_or("").split_whitespace();
+            let state = names.next().and_then(parse::<S>);
+            let trigger = names.next().map(parse::<E>);
+
+            match (state, trigger, names.next()) {
+                (Some(state), None, None) => Ok(Some(Snapshot {
+                    state,
+                    trigger: None,
+                })),
+                (Some(state), Some(Some(trigger)), None) => Ok(Some(Snapshot {
+                    state,
+                    trigger: Some(trigger),
+                })),
+                _ => Err(malformed()),
+            }
+        }
+    }
+
+    impl<S: Id, E: Id> Journal<S, E> for Files {
+        fn append(&mut self, key: &str, event: E) -> Result<usize> {
+            let path = self.dir.join(key);
+            let mut file = OpenOptions::new().append(true).open(&path)?;
+            writeln!(file, "{}", event.name())?;
+            file.sync_data()?;
+
+            let contents = fs::read_to_string(&path)?;
+            Ok(contents.lines().count().saturating_sub(1))
+        }
+
+        fn events<F: FnMut(E)>(&self, key: &str, mut f: F) -> Result<()> {
+            let contents = match self.read(key)? {
+                Some(contents) => contents,
+                None => return Ok(()),
+            };
+
+            for line in contents.lines().skip(1) {
+                f(parse::<E>(line.trim()).ok_or_else(malformed)?);
+            }
+
+            Ok(())
+        }
+    }
+
+    fn parse<I: Id>(name: &str) -> Option<I> {
+        I::ALL.iter().cloned().find(|id| id.name() == name)
+    }
+
+    fn malformed() -> Error {
+        Error::new(ErrorKind::InvalidData, "malformed snapshot")
+    }
+}
+
+#[cfg(feature = "flash")]
+pub use self::flash_store::{Flash, FlashError};
+
+#[cfg(feature = "flash")]
+mod flash_store {
+    use core::cell::RefCell;
+    use core::fmt;
+    use embedded_storage::nor_flash::NorFlash;
+
+    use super::{Snapshot, Store};
+    use crate::Id;
+
+    const MAGIC: u8 = 0x5a;
+    const RECORD: usize = 12;
+    const MAX_SLOT: usize = 64;
+
+    /// FlashError is returned when a `Flash` store fails.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum FlashError<E> {
+        /// Flash is returned when the flash itself failed.
+        Flash(E),
+
+        /// Unsupported is returned when saving a machine with more than 255
+        /// states or events, which don't fit in a record.
+        Unsupported,
+
+        /// Corrupt is returned when the stored record doesn't describe a state
+        /// or an event of the machine.
+        Corrupt,
+    }
+
+    impl<E: fmt::Debug> fmt::Display for FlashError<E> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                FlashError::Flash(err) => write!(f, "flash error: {:?}", err),
+                FlashError::Unsupported => f.write_str("the machine has too many states or events"),
+                FlashError::Corrupt => f.write_str("the stored record is corrupt"),
+            }
+        }
+    }
+
+    /// Flash keeps the snapshot of a single machine in a region of NOR flash,
+    /// using the [`embedded-storage`][es] traits. Saving under a different key
+    /// replaces the stored snapshot, and loading under any other key than the
+    /// last one saved returns `None`.
+    ///
+    /// Every save appends a small record to the region, instead of rewriting
+    /// the same location, spreading the wear over all of its erase pages. A
+    /// page is only erased once the records wrap around to it, and the region
+    /// spans at least two pages, so a power loss at any point leaves the last
+    /// complete record readable.
+    ///
+    /// ```rust
+    /// extern crate embedded_storage;
+    /// extern crate sm;
+    /// # use embedded_storage::nor_flash::{ErrorType, NorFlash, NorFlashErrorKind, ReadNorFlash};
+    /// use sm::persist::Flash;
+    /// use sm::sm;
+    ///
+    /// sm! {
+    ///     #[apply]
+    ///     Lock {
+    ///         InitialStates { Locked }
+    ///
+    ///         TurnKey {
+    ///             Locked => Unlocked
+    ///             Unlocked => Locked
+    ///         }
+    ///     }
+    /// }
+    /// #
+    /// # struct Ram([u8; 256]);
+    /// #
+    /// # impl ErrorType for Ram {
+    /// #     type Error = NorFlashErrorKind;
+    /// # }
+    /// #
+    /// # impl ReadNorFlash for Ram {
+    /// #     const READ_SIZE: usize = 1;
+    /// #
+    /// #     fn read(&mut self, offset: u32, bytes: &mut [u8]) -> Result<(), Self::Error> {
+    /// #         let offset = offset as usize;
+    /// #         bytes.copy_from_slice(&self.0[offset..offset + bytes.len()]);
+    /// #         Ok(())
+    /// #     }
+    /// #
+    /// #     fn capacity(&self) -> usize {
+    /// #         self.0.len()
+    /// #     }
+    /// # }
+    /// #
+    /// # impl NorFlash for Ram {
+    /// #     const WRITE_SIZE: usize = 4;
+    /// #     const ERASE_SIZE: usize = 64;
+    /// #
+    /// #     fn erase(&mut self, from: u32, to: u32) -> Result<(), Self::Error> {
+    /// #         self.0[from as usize..to as usize].iter_mut().for_each(|b| *b = 0xff);
+    /// #         Ok(())
+    /// #     }
+    /// #
+    /// #     fn write(&mut self, offset: u32, bytes: &[u8]) -> Result<(), Self::Error> {
+    /// #         let offset = offset as usize;
+    /// #         self.0[offset..offset + bytes.len()].copy_from_slice(bytes);
+    /// #         Ok(())
+    /// #     }
+    /// # }
+    ///
+    /// fn main() {
+    ///     use Lock::*;
+    ///
+    ///     // Two erase pages of a flash chip, starting at offset 0.
+    ///     let mut store = Flash::new(Ram([0xff; 256]), 0, 128);
+    ///
+    ///     let mut sm = Machine::new(Locked).as_enum();
+    ///     for _ in 0..25 {
+    ///         sm.apply(EventId::TurnKey).unwrap();
+    ///         sm.save_to(&mut store, "front-door").unwrap();
+    ///     }
+    ///
+    ///     let sm = Variant::load_from(&store, "front-door").unwrap().unwrap();
+    ///     assert_eq!(sm.state_id(), StateId::Unlocked);
+    /// }
+    /// ```
+    ///
+    /// [es]: https://docs.rs/embedded-storage
+    #[derive(Debug)]
+    pub struct Flash<F> {
+        flash: RefCell<F>,
+        start: u32,
+        end: u32,
+        slot: u32,
+        head: Option<(u32, u32)>,
+    }
+
+    #[derive(Clone, Copy)]
+    struct Record {
+        sequence: u32,
+        key: u32,
+        state: u8,
+        trigger: u8,
+    }
+
+    impl<F: NorFlash> Flash<F> {
+        /// new creates a store using the region of the flash from `start` up
+        /// to, but not including, `end`.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the region isn't aligned to erase pages, or spans less
+        /// than two of them.
+        pub fn new(flash: F, start: u32, end: u32) -> Self {
+            let page = F::ERASE_SIZE as u32;
+            let align = F::WRITE_SIZE.max(F::READ_SIZE);
+            let slot = RECORD.max(align).next_power_of_two();
+
+            assert!(start.is_multiple_of(page) && end.is_multiple_of(page), "the region has to be aligned to erase pages");
+            assert!(end >= start + 2 * page, "the region has to span at least two erase pages");
+            assert!(slot <= MAX_SLOT && page.is_multiple_of(slot as u32), "unsupported flash geometry");
+
+            Flash {
+                flash: RefCell::new(flash),
+                start,
+                end,
+                slot: slot as u32,
+                head: None,
+            }
+        }
+
+        /// into_inner consumes the store, and returns the flash.
+        pub fn into_inner(self) -> F {
+            self.flash.into_inner()
+        }
+
+        fn last(&self) -> Result<Option<(u32, Record)>, F::Error> {
+            let mut flash = self.flash.borrow_mut();
+            let mut buf = [0; MAX_SLOT];
+            let mut last: Option<(u32, Record)> = None;
+
+            for offset in (self.start..self.end).step_by(self.slot as usize) {
+                let buf = &mut buf[..self.slot as usize];
+                flash.read(offset, buf)?;
+
+                if let Some(record) = decode(buf) {
+                    if last.is_none_or(|(_, last)| record.sequence > last.sequence) {
+                        last = Some((offset, record));
+                    }
+                }
+            }
+
+            Ok(last)
+        }
+
+        fn after(&self, offset: u32) -> u32 {
+            if offset + 2 * self.slot > self.end {
+                self.start
+            } else {
+                offset + self.slot
+            }
+        }
+    }
+
+    impl<F: NorFlash, S: Id, E: Id> Store<S, E> for Flash<F> {
+        type Error = FlashError<F::Error>;
+
+        fn save(&mut self, key: &str, snapshot: Snapshot<S, E>) -> Result<(), Self::Error> {
+            let state = snapshot.state.index();
+            let trigger = snapshot.trigger.map_or(0, |trigger| trigger.index() + 1);
+            if state > 0xff || trigger > 0xff {
+                return Err(FlashError::Unsupported);
+            }
+
+            let (offset, sequence) = match self.head {
+                Some(head) => head,
+                None => match self.last().map_err(FlashError::Flash)? {
+                    Some((offset, last)) => (self.after(offset), last.sequence.wrapping_add(1)),
+                    None => (self.start, 0),
+                },
+            };
+
+            let record = Record {
+                sequence,
+                key: hash(key),
+                state: state as u8,
+                trigger: trigger as u8,
+            };
+
+            let mut buf = [0xff; MAX_SLOT];
+            let buf = &mut buf[..self.slot as usize];
+            encode(record, buf);
+
+            let flash = self.flash.get_mut();
+            if (offset - self.start).is_multiple_of(F::ERASE_SIZE as u32) {
+                flash
+                    .erase(offset, offset + F::ERASE_SIZE as u32)
+                    .map_err(FlashError::Flash)?;
+            }
+            flash.write(offset, buf).map_err(FlashError::Flash)?;
+
+            self.head = Some((self.after(offset), sequence.wrapping_add(1)));
+            Ok(())
+        }
+
+        fn load(&self, key: &str) -> Result<Option<Snapshot<S, E>>, Self::Error> {
+            let record = match self.last().map_err(FlashError::Flash)? {
+                Some((_, record)) if record.key == hash(key) => record,
+                _ => return Ok(None),
+            };
+
+            let state = *S::ALL.get(record.state as usize).ok_or(FlashError::Corrupt)?;
+            let trigger = match record.trigger {
+                0 => None,
+                trigger => Some(*E::ALL.get(trigger as usize - 1).ok_or(FlashError::Corrupt)?),
+            };
+
+            Ok(Some(Snapshot { state, trigger }))
+        }
+    }
+
+    fn encode(record: Record, buf: &mut [u8]) {
+        buf[0] = MAGIC;
+        buf[1] = record.state;
+        buf[2] = record.trigger;
+        buf[4..8].copy_from_slice(&record.sequence.to_le_bytes());
+        buf[8..12].copy_from_slice(&record.key.to_le_bytes());
+        buf[3] = checksum(buf);
+    }
+
+    fn decode(buf: &[u8]) -> Option<Record> {
+        if buf[0] != MAGIC || buf[3] != checksum(buf) {
+            return None;
+        }
+
+        let mut sequence = [0; 4];
+        let mut key = [0; 4];
+        sequence.copy_from_slice(&buf[4..8]);
+        key.copy_from_slice(&buf[8..12]);
+
+        Some(Record {
+            sequence: u32::from_le_bytes(sequence),
+            key: u32::from_le_bytes(key),
+            state: buf[1],
+            trigger: buf[2],
+        })
+    }
+
+    fn checksum(buf: &[u8]) -> u8 {
+        !buf[..RECORD]
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| i != 3)
+            .fold(0u8, |sum, (_, b)| sum.wrapping_add(*b))
+    }
+
+    /// hash is the 32-bit FNV-1a hash of the key.
+    fn hash(key: &str) -> u32 {
+        key.bytes()
+            .fold(0x811c_9dc5, |hash, b| (hash ^ u32::from(b)).wrapping_mul(0x0100_0193))
+    }
+}
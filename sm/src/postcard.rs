@@ -0,0 +1,58 @@
+//! Encode machines in a few bytes, using [postcard][p].
+//!
+//! A machine is encoded as the index of its current state, followed by the
+//! index of the event that triggered it, if any. Each index takes a single
+//! byte for machines with up to 127 states or events, so most machines fit in
+//! two or three bytes, small enough to checkpoint into EEPROM or to send in a
+//! radio packet. No allocator is required.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::postcard::{from_bytes, to_slice, MAX_SIZE};
+//! use sm::sm;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let sm = Machine::new(Locked).transition(TurnKey).as_enum();
+//!
+//!     let mut buf = [0; MAX_SIZE];
+//!     let bytes = to_slice(&sm, &mut buf).unwrap();
+//!     assert_eq!(bytes, &[1, 1, 0]);
+//!
+//!     let sm: Variant = from_bytes(bytes).unwrap();
+//!     assert_eq!(sm.state_id(), StateId::Unlocked);
+//!     assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+//! }
+//! ```
+//!
+//! [p]: https://docs.rs/postcard
+
+pub use postcard_crate::Error;
+
+use crate::serde::{Deserialize, Serialize};
+
+/// MAX_SIZE is the maximum number of bytes needed to encode any machine.
+pub const MAX_SIZE: usize = 11;
+
+/// to_slice encodes the machine into the provided buffer, and returns the part
+/// of the buffer that was used.
+pub fn to_slice<'a, V: Serialize>(machine: &V, buf: &'a mut [u8]) -> Result<&'a mut [u8], Error> {
+    postcard_crate::to_slice(machine, buf)
+}
+
+/// from_bytes decodes a machine previously encoded using `to_slice`.
+pub fn from_bytes<'a, V: Deserialize<'a>>(bytes: &'a [u8]) -> Result<V, Error> {
+    postcard_crate::from_bytes(bytes)
+}
@@ -0,0 +1,200 @@
+//! Manage many instances of the same machine.
+//!
+//! A `Registry` keeps machines keyed by an id of your choosing, such as an
+//! order or a connection id, routes events to the machine they are meant for,
+//! and evicts machines once they reach a terminal state, a state without any
+//! outgoing transitions. The ids of the machines are indexed by state, and the
+//! index is updated on every transition, so finding the machines in a given
+//! state, such as the orders awaiting payment, doesn't scan every machine.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::registry::Registry;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Order {
+//!         InitialStates { Placed }
+//!
+//!         Pay {
+//!             Placed => Paid
+//!         }
+//!
+//!         Ship {
+//!             Paid => Shipped
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Order::*;
+//!
+//!     let mut orders = Registry::new();
+//!     for id in 1..=3 {
+//!         let _ = orders.insert(id, Machine::new(Placed).as_enum());
+//!     }
+//!
+//!     assert_eq!(orders.route(&1, EventId::Pay), Some(Ok(())));
+//!     assert_eq!(orders.route(&1, EventId::Ship), Some(Ok(())));
+//!     assert_eq!(orders.route(&2, EventId::Pay), Some(Ok(())));
+//!     assert!(orders.route(&3, EventId::Ship).unwrap().is_err());
+//!     assert_eq!(orders.route(&4, EventId::Pay), None);
+//!
+//!     assert_eq!(orders.in_state(StateId::Paid).count(), 1);
+//!     assert_eq!(orders.count_in_state(StateId::Placed), 1);
+//!
+//!     let shipped = orders.evict_terminal();
+//!     assert_eq!(shipped.len(), 1);
+//!     assert_eq!(shipped[0].0, 1);
+//!     assert_eq!(orders.len(), 2);
+//! }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::vec::Vec;
+
+use crate::{Id, Table, TransitionError, TryTransition};
+
+/// RouteError is the error returned by `Registry::route` for machines of type
+/// `V`.
+type RouteError<V> = TransitionError<<V as Table>::StateId, <V as Table>::EventId>;
+
+/// Registry holds machines of the same type, keyed by id.
+#[derive(Debug)]
+pub struct Registry<K, V> {
+    machines: HashMap<K, V>,
+    index: Vec<HashSet<K>>,
+}
+
+impl<K: Eq + Hash, V> Registry<K, V> {
+    /// new creates an empty registry.
+    pub fn new() -> Self {
+        Registry {
+            machines: HashMap::new(),
+            index: Vec::new(),
+        }
+    }
+
+    /// get returns the machine registered under the given id, if any.
+    pub fn get(&self, id: &K) -> Option<&V> {
+        self.machines.get(id)
+    }
+
+    /// len returns the number of registered machines.
+    pub fn len(&self) -> usize {
+        self.machines.len()
+    }
+
+    /// is_empty returns `true` if no machines are registered.
+    pub fn is_empty(&self) -> bool {
+        self.machines.is_empty()
+    }
+
+    /// iter returns an iterator over all registered machines and their ids, in
+    /// arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.machines.iter()
+    }
+}
+
+impl<K: Eq + Hash, V> Default for Registry<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Eq + Hash + Clone, V: Table> Registry<K, V> {
+    /// insert adds a machine under the given id, and returns the machine that
+    /// was previously registered under that id, if any.
+    pub fn insert(&mut self, id: K, machine: V) -> Option<V> {
+        let previous = self.remove(&id);
+
+        let _ = self.indexed(machine.state()).insert(id.clone());
+        let _ = self.machines.insert(id, machine);
+
+        previous
+    }
+
+    /// remove removes the machine registered under the given id, and returns
+    /// it, if any.
+    pub fn remove(&mut self, id: &K) -> Option<V> {
+        let machine = self.machines.remove(id)?;
+        let _ = self.indexed(machine.state()).remove(id);
+
+        Some(machine)
+    }
+
+    /// in_state returns an iterator over the machines currently in the given
+    /// state, and their ids, in arbitrary order.
+    pub fn in_state(&self, state: V::StateId) -> impl Iterator<Item = (&K, &V)> {
+        self.index
+            .get(state.index())
+            .into_iter()
+            .flatten()
+            .filter_map(move |id| self.machines.get_key_value(id))
+    }
+
+    /// count_in_state returns the number of machines currently in the given
+    /// state.
+    pub fn count_in_state(&self, state: V::StateId) -> usize {
+        self.index.get(state.index()).map_or(0, HashSet::len)
+    }
+
+    /// evict_terminal removes the machines in a terminal state, and returns
+    /// them with their ids.
+    pub fn evict_terminal(&mut self) -> Vec<(K, V)> {
+        let mut evicted = Vec::new();
+
+        for &state in V::StateId::ALL {
+            if !V::is_terminal(state) {
+                continue;
+            }
+
+            for id in self.indexed(state).drain().collect::<Vec<K>>() {
+                if let Some(machine) = self.machines.remove(&id) {
+                    evicted.push((id, machine));
+                }
+            }
+        }
+
+        evicted
+    }
+
+    fn indexed(&mut self, state: V::StateId) -> &mut HashSet<K> {
+        if self.index.is_empty() {
+            self.index = V::StateId::ALL.iter().map(|_| HashSet::new()).collect();
+        }
+
+        &mut self.index[state.index()]
+    }
+}
+
+impl<K, V> Registry<K, V>
+where
+    K: Eq + Hash + Clone,
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    /// route applies the event to the machine registered under the given id,
+    /// and moves its id to the index of the state it transitioned to. It
+    /// returns `None` if no machine is registered under that id, and an error,
+    /// leaving the machine untouched, if the event has no valid transition
+    /// from its current state.
+    pub fn route(&mut self, id: &K, event: V::EventId) -> Option<Result<(), RouteError<V>>> {
+        let machine = self.machines.get_mut(id)?;
+        let state = machine.state();
+
+        let next = match machine.prepare(event) {
+            Some(prepared) => prepared.commit().state(),
+            None => return Some(Err(TransitionError::Invalid { state, event })),
+        };
+
+        if next != state {
+            let _ = self.indexed(state).remove(id);
+            let _ = self.indexed(next).insert(id.clone());
+        }
+
+        Some(Ok(()))
+    }
+}
@@ -0,0 +1,135 @@
+//! Share a machine between [RTIC][rtic] tasks.
+//!
+//! A `Resource` holds a machine together with a fixed-capacity queue of
+//! events, and is meant to be stored as an RTIC shared resource. Hardware
+//! tasks post events, which only takes the resource lock for as long as it
+//! takes to queue them, and a software task evaluates the queued events
+//! against the machine. A `Resource` is `Send` as long as its machine and
+//! events are, which is the case for the types generated by the `sm!` macro,
+//! so no `unsafe` is needed to share it:
+//!
+//! ```rust,ignore
+//! #[rtic::app(device = pac, dispatchers = [SWI0_EGU0])]
+//! mod app {
+//!     use sm::driver::Backpressure;
+//!     use sm::rtic::Resource;
+//!     use crate::Lock::*;
+//!
+//!     #[shared]
+//!     struct Shared {
+//!         lock: Resource<Variant, EventId, 8>,
+//!     }
+//!
+//!     #[task(binds = GPIOTE, shared = [lock])]
+//!     fn button(mut cx: button::Context) {
+//!         let _ = cx.shared.lock.lock(|lock| lock.post(EventId::TurnKey));
+//!         let _ = eval::spawn();
+//!     }
+//!
+//!     #[task(shared = [lock])]
+//!     async fn eval(mut cx: eval::Context) {
+//!         cx.shared.lock.lock(|lock| lock.eval());
+//!     }
+//! }
+//! ```
+//!
+//! Outside of RTIC, the same resource can be used directly:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::driver::Backpressure;
+//! use sm::rtic::Resource;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut lock = Resource::<_, _, 4>::new(Machine::new(Locked).as_enum(), Backpressure::Error);
+//!     assert_eq!(lock.post(EventId::TurnKey), Ok(()));
+//!     assert_eq!(lock.eval(), 1);
+//!
+//!     assert_eq!(lock.machine().state_id(), StateId::Unlocked);
+//! }
+//! ```
+//!
+//! [rtic]: https://rtic.rs
+
+use crate::driver::{Backpressure, Queue};
+use crate::{Table, TransitionError, TryTransition};
+
+/// Resource holds a machine and the events posted to it, waiting to be
+/// evaluated.
+#[derive(Debug)]
+pub struct Resource<V, E, const N: usize> {
+    machine: V,
+    queue: Queue<E, N>,
+}
+
+impl<V, E, const N: usize> Resource<V, E, N> {
+    /// new wraps the provided machine, with room for `N` pending events. Once
+    /// full, the provided policy decides what happens to newly posted events.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` is zero.
+    pub fn new(machine: V, backpressure: Backpressure) -> Self {
+        Resource {
+            machine,
+            queue: Queue::new(backpressure),
+        }
+    }
+
+    /// machine returns the current machine.
+    pub fn machine(&self) -> &V {
+        &self.machine
+    }
+
+    /// pending returns the number of events waiting to be evaluated.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+}
+
+impl<V: Table, E, const N: usize> Resource<V, E, N> {
+    /// post queues an event, to be applied on the next call to `eval`. If the
+    /// queue is full and the policy is `Backpressure::Error`, the event is
+    /// returned in a `TransitionError::QueueFull` error.
+    pub fn post(&mut self, event: E) -> Result<(), TransitionError<V::StateId, E>> {
+        self.queue.push(event).map_err(TransitionError::QueueFull)
+    }
+}
+
+impl<V, E, const N: usize> Resource<V, E, N>
+where
+    V: TryTransition<E> + Clone,
+{
+    /// eval applies the pending events to the machine, in the order in which
+    /// they were posted, and returns the number of events that caused a
+    /// transition. Events without a valid transition from the state the
+    /// machine is in at that point are discarded.
+    pub fn eval(&mut self) -> usize {
+        let mut applied = 0;
+
+        while let Some(event) = self.queue.pop() {
+            if let Some(prepared) = self.machine.prepare(event) {
+                let _ = prepared.commit();
+                applied += 1;
+            }
+        }
+
+        applied
+    }
+}
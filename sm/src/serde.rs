@@ -0,0 +1,151 @@
+//! Persist machines using [serde][s].
+//!
+//! With the `serde` feature enabled, the `sm!` macro implements `Serialize`
+//! and `Deserialize` for the `StateId`, `EventId` and `Variant` types of every
+//! machine. A `Variant` is serialized as its current state, together with the
+//! event that triggered it, so a restored machine reports the same `trigger()`
+//! as the original one:
+//!
+//! ```rust
+//! extern crate serde_json;
+//! extern crate sm;
+//! use sm::sm;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let sm = Machine::new(Locked).transition(TurnKey).as_enum();
+//!     let json = serde_json::to_string(&sm).unwrap();
+//!     assert_eq!(json, r#"["Unlocked","TurnKey"]"#);
+//!
+//!     match serde_json::from_str(&json).unwrap() {
+//!         Variant::UnlockedByTurnKey(m) => assert_eq!(m.trigger(), Some(TurnKey)),
+//!         _ => unreachable!(),
+//!     }
+//!
+//!     assert!(serde_json::from_str::<Variant>(r#"["Locked",null]"#).is_ok());
+//!     assert!(serde_json::from_str::<Variant>(r#"["Unlocked",null]"#).is_err());
+//! }
+//! ```
+//!
+//! The items of this module are used by the generated implementations, and
+//! don't need to be used directly.
+//!
+//! [s]: https://serde.rs
+
+use core::fmt;
+use core::marker::PhantomData;
+use core::str;
+
+pub use serde_crate::de;
+pub use serde_crate::{Deserialize, Deserializer, Serialize, Serializer};
+
+use self::de::{DeserializeSeed, EnumAccess, Unexpected, VariantAccess, Visitor};
+use crate::Id;
+
+/// serialize_id serializes an id as a unit variant of the enum with the given
+/// name.
+pub fn serialize_id<I: Id, S: Serializer>(
+    id: I,
+    name: &'static str,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_unit_variant(name, id.index() as u32, id.name())
+}
+
+/// deserialize_id deserializes an id from a unit variant of the enum with the
+/// given name. Both the name and the index of the variant are accepted.
+pub fn deserialize_id<'de, I: Id, D: Deserializer<'de>>(
+    name: &'static str,
+    deserializer: D,
+) -> Result<I, D::Error> {
+    deserializer.deserialize_enum(name, I::NAMES, IdVisitor(PhantomData))
+}
+
+/// invalid_machine returns the error to report when a deserialized state and
+/// trigger do not match any variant of the machine.
+pub fn invalid_machine<S: Id, T: Id, E: de::Error>(state: S, trigger: Option<T>) -> E {
+    match trigger {
+        Some(trigger) => E::custom(format_args!(
+            "state `{}` cannot be reached through event `{}`",
+            state.name(),
+            trigger.name()
+        )),
+        None => E::custom(format_args!(
+            "state `{}` is not an initial state",
+            state.name()
+        )),
+    }
+}
+
+#[derive(Debug)]
+struct IdVisitor<I>(PhantomData<I>);
+
+impl<'de, I: Id> Visitor<'de> for IdVisitor<I> {
+    type Value = I;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("one of ")?;
+
+        for (i, name) in I::NAMES.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+
+            write!(f, "`{}`", name)?;
+        }
+
+        Ok(())
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<I, E> {
+        I::ALL
+            .get(value as usize)
+            .cloned()
+            .ok_or_else(|| E::invalid_value(Unexpected::Unsigned(value), &self))
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<I, E> {
+        I::ALL
+            .iter()
+            .cloned()
+            .find(|id| id.name() == value)
+            .ok_or_else(|| E::unknown_variant(value, I::NAMES))
+    }
+
+    fn visit_bytes<E: de::Error>(self, value: &[u8]) -> Result<I, E> {
+        match str::from_utf8(value) {
+            Ok(value) => self.visit_str(value),
+            Err(_) => Err(E::invalid_value(Unexpected::Bytes(value), &self)),
+        }
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<I, A::Error> {
+        let (id, variant) = data.variant_seed(IdSeed(PhantomData))?;
+        variant.unit_variant()?;
+
+        Ok(id)
+    }
+}
+
+#[derive(Debug)]
+struct IdSeed<I>(PhantomData<I>);
+
+impl<'de, I: Id> DeserializeSeed<'de> for IdSeed<I> {
+    type Value = I;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<I, D::Error> {
+        deserializer.deserialize_identifier(IdVisitor(PhantomData))
+    }
+}
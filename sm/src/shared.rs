@@ -0,0 +1,97 @@
+//! Share a machine between interrupt handlers and thread context, using a
+//! [critical section][cs].
+//!
+//! Contrary to the `atomic` module, `Shared` does not depend on atomic
+//! instructions, and stores the complete `Variant` of the machine, including
+//! its trigger. Every access happens inside a critical section, as provided by
+//! the `critical-section` implementation of your target.
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//! use sm::shared::Shared;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let lock = Shared::new(Machine::new(Locked).as_enum());
+//!     let _ = lock.transition(TurnKey);
+//!
+//!     assert_eq!(lock.get().state_id(), StateId::Unlocked);
+//! }
+//! ```
+//!
+//! [cs]: https://docs.rs/critical-section
+
+use core::cell::RefCell;
+use core::fmt;
+use critical_section::{with, Mutex};
+
+use crate::TryTransition;
+
+/// Shared holds a machine that can be accessed and transitioned from any
+/// execution context.
+pub struct Shared<V> {
+    machine: Mutex<RefCell<V>>,
+}
+
+impl<V> Shared<V> {
+    /// new wraps the provided machine. This can be used to initialise a
+    /// `static` value.
+    pub const fn new(machine: V) -> Self {
+        Shared {
+            machine: Mutex::new(RefCell::new(machine)),
+        }
+    }
+
+    /// replace swaps the current machine for the provided one, and returns the
+    /// previous machine.
+    pub fn replace(&self, machine: V) -> V {
+        with(|cs| self.machine.borrow(cs).replace(machine))
+    }
+}
+
+impl<V: Clone> Shared<V> {
+    /// get returns a copy of the current machine.
+    pub fn get(&self) -> V {
+        with(|cs| self.machine.borrow(cs).borrow().clone())
+    }
+
+    /// transition applies the event to the current machine, and returns the
+    /// new machine. If the event has no valid transition from the current
+    /// state, the machine is left untouched, and returned as the error.
+    pub fn transition<E>(&self, event: E) -> Result<V, V>
+    where
+        V: TryTransition<E>,
+    {
+        with(|cs| {
+            let mut machine = self.machine.borrow(cs).borrow_mut();
+
+            let next = machine.clone().try_transition(event)?;
+            *machine = next.clone();
+
+            Ok(next)
+        })
+    }
+}
+
+impl<V: fmt::Debug> fmt::Debug for Shared<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        with(|cs| {
+            f.debug_struct("Shared")
+                .field("machine", self.machine.borrow(cs))
+                .finish()
+        })
+    }
+}
@@ -0,0 +1,390 @@
+//! Walk a machine at random, for load testing and Monte Carlo simulations.
+//!
+//! Transitions can be given a weight in the `sm!` macro, written after the
+//! state they lead to, such as `Browsing => Shopping @ 2`, and default to a
+//! weight of `1`. `Simulate::simulate_random` walks a machine by picking, at
+//! every step, one of the transitions out of the current state, with a
+//! probability proportional to its weight. A transition with a weight of `0`
+//! is never picked. Weights only affect simulations: events are applied to
+//! the machine as usual, by the `apply` method or any other API.
+//!
+//! Randomness is provided by a `Random` source, which is implemented by every
+//! closure returning a random `u32`, so any random number generator can be
+//! used:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::simulate::Simulate;
+//! use sm::sm;
+//!
+//! sm! {
+//!     #[table]
+//!     Visitor {
+//!         InitialStates { Browsing }
+//!
+//!         View {
+//!             Browsing => Browsing @ 8
+//!         }
+//!
+//!         AddToCart {
+//!             Browsing => Shopping @ 2
+//!         }
+//!
+//!         Checkout {
+//!             Shopping => Paid
+//!         }
+//!
+//!         Leave {
+//!             Browsing, Shopping => Gone @ 0
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Visitor::*;
+//!
+//!     // A xorshift generator, good enough for a simulation.
+//!     let mut seed = 0x2545_f491u32;
+//!     let mut rng = move || {
+//!         seed ^= seed << 13;
+//!         seed ^= seed >> 17;
+//!         seed ^= seed << 5;
+//!         seed
+//!     };
+//!
+//!     let sm = Machine::new(Browsing).as_enum();
+//!     let last = sm.simulate_random(&mut rng, 1_000).last().unwrap();
+//!
+//!     // Visitors never leave, so they all end up paying.
+//!     assert_eq!(last.state_id(), StateId::Paid);
+//! }
+//! ```
+//!
+//! With the `std` feature, an `Explorer` walks a machine at random to test it:
+//! starting from the provided machines, it applies random events with a valid
+//! transition, and checks an invariant after every step, reporting the
+//! shortest sequence of events found to break it.
+
+#[cfg(feature = "std")]
+use core::fmt;
+#[cfg(feature = "std")]
+use std::boxed::Box;
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+use crate::{Id, Table, TryTransition};
+
+/// Random is a source of random numbers.
+pub trait Random {
+    /// next_u32 returns a random number, uniformly distributed over all values
+    /// of `u32`.
+    fn next_u32(&mut self) -> u32;
+}
+
+impl<F: FnMut() -> u32> Random for F {
+    fn next_u32(&mut self) -> u32 {
+        self()
+    }
+}
+
+/// Simulate is implemented by every machine implementing `Table` and
+/// `TryTransition`, such as the `Variant` enum generated by the `sm!` macro.
+pub trait Simulate: Sized {
+    /// simulate_random returns an iterator walking the machine for up to
+    /// `steps` steps, picking the transitions according to their weights, and
+    /// yielding the machine after every step. It ends early once no
+    /// transition with a weight above `0` leaves the current state.
+    fn simulate_random<R: Random>(self, rng: R, steps: usize) -> Simulation<Self, R>;
+}
+
+impl<V> Simulate for V
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    fn simulate_random<R: Random>(self, rng: R, steps: usize) -> Simulation<Self, R> {
+        Simulation {
+            machine: self,
+            rng,
+            steps,
+        }
+    }
+}
+
+/// Simulation is the iterator returned by `Simulate::simulate_random`.
+#[derive(Clone, Debug)]
+pub struct Simulation<V, R> {
+    machine: V,
+    rng: R,
+    steps: usize,
+}
+
+impl<V, R> Simulation<V, R> {
+    /// into_machine consumes the iterator, and returns the machine in the
+    /// state reached so far.
+    pub fn into_machine(self) -> V {
+        self.machine
+    }
+}
+
+impl<V, R> Iterator for Simulation<V, R>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+    R: Random,
+{
+    type Item = V;
+
+    fn next(&mut self) -> Option<V> {
+        if self.steps == 0 {
+            return None;
+        }
+
+        let state = self.machine.state();
+        let weighted = || {
+            V::EventId::ALL
+                .iter()
+                .filter(move |&&event| V::next(state, event).is_some())
+                .map(move |&event| (event, u64::from(V::weight(state, event))))
+        };
+
+        let total: u64 = weighted().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            self.steps = 0;
+            return None;
+        }
+
+        // Scales the random number down to `0..total`.
+        let mut pick = ((u128::from(self.rng.next_u32()) * u128::from(total)) >> 32) as u64;
+        let event = weighted()
+            .find(|&(_, weight)| {
+                if pick < weight {
+                    return true;
+                }
+
+                pick -= weight;
+                false
+            })
+            .map(|(event, _)| event)?;
+
+        self.steps -= 1;
+        self.machine.prepare(event).map(|prepared| prepared.commit().clone())
+    }
+}
+
+/// ExplorerGuard is the signature of the closures registered by
+/// `Explorer::guard`.
+#[cfg(feature = "std")]
+type ExplorerGuard<V> = dyn FnMut(&V, <V as Table>::EventId) -> bool;
+
+/// Explorer walks machines at random, checking an invariant after every step.
+///
+/// ```rust
+/// extern crate sm;
+/// use sm::simulate::Explorer;
+/// use sm::sm;
+///
+/// sm! {
+///     #[table]
+///     Lock {
+///         InitialStates { Locked }
+///
+///         TurnKey {
+///             Locked => Unlocked
+///             Unlocked => Locked
+///         }
+///
+///         Break {
+///             Unlocked => Broken
+///         }
+///     }
+/// }
+///
+/// fn main() {
+///     use Lock::*;
+///
+///     let mut seed = 0x2545_f491u32;
+///     let rng = move || {
+///         seed ^= seed << 13;
+///         seed ^= seed >> 17;
+///         seed ^= seed << 5;
+///         seed
+///     };
+///
+///     let report = Explorer::new(rng)
+///         .runs(50)
+///         .depth(10)
+///         .explore(&[Machine::new(Locked).as_enum()], |sm| sm.state_id() != StateId::Broken);
+///
+///     let violation = report.shortest.unwrap();
+///     assert_eq!(violation.events, vec![EventId::TurnKey, EventId::Break]);
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub struct Explorer<V: Table, R> {
+    rng: R,
+    runs: usize,
+    depth: usize,
+    guard: Option<Box<ExplorerGuard<V>>>,
+}
+
+#[cfg(feature = "std")]
+impl<V, R> Explorer<V, R>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+    R: Random,
+{
+    /// new creates an explorer drawing random numbers from the provided
+    /// source, performing 100 walks of up to 32 steps by default.
+    pub fn new(rng: R) -> Self {
+        Explorer {
+            rng,
+            runs: 100,
+            depth: 32,
+            guard: None,
+        }
+    }
+
+    /// runs sets the number of walks to perform.
+    pub fn runs(mut self, runs: usize) -> Self {
+        self.runs = runs;
+        self
+    }
+
+    /// depth sets the maximum number of steps of a walk.
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    /// guard sets a guard, deciding whether an event with a valid transition
+    /// can be applied to the machine. A guard can decide based on resources
+    /// generated by the caller, to only explore the transitions a real system
+    /// would take.
+    pub fn guard<G>(mut self, guard: G) -> Self
+    where
+        G: FnMut(&V, V::EventId) -> bool + 'static,
+    {
+        self.guard = Some(Box::new(guard));
+        self
+    }
+
+    /// explore walks the provided machines in turn, starting each walk from a
+    /// copy of one of them, and returns a report of the violations of the
+    /// invariant. A walk ends once the invariant is violated, once no event
+    /// can be applied, or after the configured number of steps.
+    pub fn explore<F>(&mut self, starts: &[V], mut invariant: F) -> Report<V::StateId, V::EventId>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let mut report = Report {
+            runs: 0,
+            steps: 0,
+            violations: 0,
+            shortest: None,
+        };
+
+        for start in starts.iter().cycle().take(self.runs) {
+            let mut machine = start.clone();
+            let mut events = Vec::new();
+            report.runs += 1;
+
+            loop {
+                if !invariant(&machine) {
+                    report.violations += 1;
+
+                    if report.shortest.as_ref().is_none_or(|v| events.len() < v.events.len()) {
+                        report.shortest = Some(Violation {
+                            initial: start.state(),
+                            events,
+                            state: machine.state(),
+                        });
+                    }
+
+                    break;
+                }
+
+                // A walk longer than the shortest violation found so far can't
+                // find a shorter one.
+                let depth = report.shortest.as_ref().map_or(self.depth, |v| v.events.len().min(self.depth));
+                if events.len() >= depth {
+                    break;
+                }
+
+                let event = match self.pick(&machine) {
+                    Some(event) => event,
+                    None => break,
+                };
+
+                if let Some(prepared) = machine.prepare(event) {
+                    let _ = prepared.commit();
+                }
+
+                events.push(event);
+                report.steps += 1;
+            }
+        }
+
+        report
+    }
+
+    fn pick(&mut self, machine: &V) -> Option<V::EventId> {
+        let state = machine.state();
+        let guard = &mut self.guard;
+        let allowed: Vec<V::EventId> = V::EventId::ALL
+            .iter()
+            .cloned()
+            .filter(|&event| {
+                V::next(state, event).is_some() && guard.as_mut().is_none_or(|guard| guard(machine, event))
+            })
+            .collect();
+
+        if allowed.is_empty() {
+            return None;
+        }
+
+        let pick = (u64::from(self.rng.next_u32()) * allowed.len() as u64) >> 32;
+        allowed.get(pick as usize).cloned()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<V: Table, R> fmt::Debug for Explorer<V, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Explorer")
+            .field("runs", &self.runs)
+            .field("depth", &self.depth)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Report summarizes the walks performed by `Explorer::explore`.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Report<S, E> {
+    /// runs is the number of walks performed.
+    pub runs: usize,
+
+    /// steps is the number of events applied across all walks.
+    pub steps: usize,
+
+    /// violations is the number of walks which violated the invariant.
+    pub violations: usize,
+
+    /// shortest is the violation reached by the shortest sequence of events,
+    /// if any.
+    pub shortest: Option<Violation<S, E>>,
+}
+
+/// Violation is a sequence of events leading a machine to violate the
+/// invariant.
+#[cfg(feature = "std")]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation<S, E> {
+    /// initial is the state the walk started from.
+    pub initial: S,
+
+    /// events are the events applied, in order, from the initial state.
+    pub events: Vec<E>,
+
+    /// state is the state in which the invariant was violated.
+    pub state: S,
+}
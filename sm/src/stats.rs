@@ -0,0 +1,129 @@
+//! Count how many times a machine entered each of its states.
+//!
+//! `Visits` wraps a machine, and counts the times it entered each of its
+//! states, including transitions leading back to the same state, in a fixed
+//! array with one counter per state. The size of the array is the number of
+//! states of the machine, generated by the `sm!` macro as `STATE_COUNT`.
+//! Snapshots of the counters can be taken at any time, to spot loops running
+//! hotter than expected, or paths that shouldn't be taken at all:
+//!
+//! ```rust
+//! extern crate sm;
+//! use sm::sm;
+//! use sm::stats::Visits;
+//!
+//! sm! {
+//!     #[table]
+//!     #[footprint]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut sm = Visits::<_, { Lock::STATE_COUNT }>::new(Machine::new(Locked).as_enum());
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!
+//!     assert_eq!(sm.count(StateId::Locked), 2);
+//!     assert_eq!(sm.count(StateId::Unlocked), 1);
+//!     assert_eq!(sm.snapshot(), [2, 1]);
+//! }
+//! ```
+
+use crate::{Id, Table, TransitionError, TryTransition};
+
+/// Visits applies events to a machine, and counts the times it entered each of
+/// its `N` states. Counters saturate at `u32::MAX`.
+#[derive(Clone, Debug)]
+pub struct Visits<V, const N: usize> {
+    machine: V,
+    counts: [u32; N],
+}
+
+impl<V: Table, const N: usize> Visits<V, N> {
+    /// new wraps the provided machine, which enters its current state once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `N` isn't the number of states of the machine.
+    pub fn new(machine: V) -> Self {
+        assert_eq!(
+            N,
+            V::StateId::ALL.len(),
+            "a counter is needed for every state of the machine"
+        );
+
+        let mut visits = Visits {
+            machine,
+            counts: [0; N],
+        };
+
+        let state = visits.machine.state();
+        visits.enter(state);
+        visits
+    }
+
+    /// machine returns a reference to the current machine.
+    pub fn machine(&self) -> &V {
+        &self.machine
+    }
+
+    /// into_machine consumes the wrapper, and returns the current machine.
+    pub fn into_machine(self) -> V {
+        self.machine
+    }
+
+    /// count returns the number of times the machine entered the state.
+    pub fn count(&self, state: V::StateId) -> u32 {
+        self.counts.get(state.index()).cloned().unwrap_or(0)
+    }
+
+    /// snapshot returns a copy of the counters, indexed by the position of
+    /// the states in `StateId::ALL`.
+    pub fn snapshot(&self) -> [u32; N] {
+        self.counts
+    }
+
+    /// iter returns an iterator over the states of the machine, and the
+    /// number of times the machine entered each of them.
+    pub fn iter(&self) -> impl Iterator<Item = (V::StateId, u32)> + '_ {
+        V::StateId::ALL.iter().cloned().zip(self.counts.iter().cloned())
+    }
+
+    /// reset sets all counters back to zero.
+    pub fn reset(&mut self) {
+        self.counts = [0; N];
+    }
+
+    fn enter(&mut self, state: V::StateId) {
+        if let Some(count) = self.counts.get_mut(state.index()) {
+            *count = count.saturating_add(1);
+        }
+    }
+}
+
+impl<V, const N: usize> Visits<V, N>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+{
+    /// apply applies the event to the machine, and counts the state it
+    /// enters.
+    pub fn apply(&mut self, event: V::EventId) -> Result<(), TransitionError<V::StateId, V::EventId>> {
+        let state = self.machine.state();
+        let next = match self.machine.prepare(event) {
+            Some(prepared) => prepared.commit().state(),
+            None => return Err(TransitionError::Invalid { state, event }),
+        };
+
+        self.enter(next);
+        Ok(())
+    }
+}
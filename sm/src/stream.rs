@@ -0,0 +1,268 @@
+//! Drive a machine from a [`futures::Stream`][s] of events.
+//!
+//! `Transitions` wraps a stream of runtime events, applies each event to a
+//! machine using `TryTransition`, and yields the machine after every applied
+//! transition. This lets a machine take part in combinator-style pipelines:
+//!
+//! ```rust
+//! extern crate futures;
+//! extern crate sm;
+//! use futures::executor::block_on;
+//! use futures::stream::{iter, StreamExt};
+//! use sm::sm;
+//! use sm::stream::{OnInvalid, Transitions};
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!
+//!         Break {
+//!             Locked => Broken
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::Variant::*;
+//!     use Lock::*;
+//!
+//!     let sm = Machine::new(Locked).as_enum();
+//!     let events = iter(vec![EventId::TurnKey, EventId::Break, EventId::TurnKey]);
+//!
+//!     let results: Vec<_> = block_on(
+//!         Transitions::new(sm, events)
+//!             .on_invalid(OnInvalid::Error)
+//!             .collect(),
+//!     );
+//!
+//!     match results.as_slice() {
+//!         [Ok(UnlockedByTurnKey(_)), Err(EventId::Break), Ok(LockedByTurnKey(_))] => {}
+//!         _ => unreachable!(),
+//!     }
+//! }
+//! ```
+//!
+//! In the other direction, `EventSink` is a sink of events, so a stream of
+//! events can be forwarded straight into a machine. Rejected events are
+//! surfaced as the error of the sink, which stops the forwarding:
+//!
+//! ```rust
+//! extern crate futures;
+//! extern crate sm;
+//! use futures::executor::block_on;
+//! use futures::stream::{iter, StreamExt};
+//! use sm::sm;
+//! use sm::stream::{EventSink, OnInvalid};
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!
+//!         Break {
+//!             Locked => Broken
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let mut sink = EventSink::new(Machine::new(Locked).as_enum()).on_invalid(OnInvalid::Error);
+//!     let events = iter(vec![EventId::TurnKey, EventId::Break, EventId::TurnKey]);
+//!
+//!     let result = block_on(events.map(Ok).forward(&mut sink));
+//!
+//!     assert_eq!(result, Err(EventId::Break));
+//!     assert_eq!(sink.machine().state_id(), StateId::Unlocked);
+//! }
+//! ```
+//!
+//! [s]: https://docs.rs/futures/0.3/futures/stream/trait.Stream.html
+
+use core::pin::Pin;
+use futures::sink::Sink;
+use futures::stream::Stream;
+use futures::task::{Context, Poll};
+
+use crate::TryTransition;
+
+const LOST: &str = "machine lost by a panicking transition";
+
+/// OnInvalid determines what `Transitions` and `EventSink` do with an event
+/// that has no valid transition from the current state of the machine.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum OnInvalid {
+    /// Skip ignores the event, leaving the machine untouched, and continues
+    /// with the next event of the stream.
+    Skip,
+
+    /// Error yields the rejected event as an error, leaving the machine
+    /// untouched. The stream can continue to be polled afterwards, and the
+    /// sink can continue to receive events.
+    Error,
+}
+
+/// Transitions is a stream adapter that applies every event of the wrapped
+/// stream to a machine, and yields the machine after each applied transition.
+#[derive(Debug)]
+pub struct Transitions<St, V> {
+    events: St,
+    // The machine is only taken out while an event is applied to it.
+    machine: Option<V>,
+    on_invalid: OnInvalid,
+}
+
+impl<St, V> Transitions<St, V> {
+    /// new creates a new adapter, applying the events of the provided stream
+    /// to the provided machine. Invalid events are skipped, unless configured
+    /// otherwise using `on_invalid`.
+    pub fn new(machine: V, events: St) -> Self {
+        Transitions {
+            events,
+            machine: Some(machine),
+            on_invalid: OnInvalid::Skip,
+        }
+    }
+
+    /// on_invalid configures how the adapter handles events without a valid
+    /// transition.
+    pub fn on_invalid(mut self, on_invalid: OnInvalid) -> Self {
+        self.on_invalid = on_invalid;
+        self
+    }
+
+    /// machine returns a reference to the current machine.
+    pub fn machine(&self) -> &V {
+        self.machine.as_ref().expect(LOST)
+    }
+
+    /// into_machine consumes the adapter, and returns the current machine.
+    pub fn into_machine(self) -> V {
+        self.machine.expect(LOST)
+    }
+}
+
+impl<St, V> Stream for Transitions<St, V>
+where
+    St: Stream + Unpin,
+    St::Item: Clone,
+    V: TryTransition<St::Item> + Clone + Unpin,
+{
+    type Item = Result<V, St::Item>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        loop {
+            let event = match Pin::new(&mut this.events).poll_next(cx) {
+                Poll::Ready(Some(event)) => event,
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            };
+
+            let machine = this.machine.take().expect(LOST);
+            match machine.try_transition(event.clone()) {
+                Ok(machine) => {
+                    let yielded = machine.clone();
+                    this.machine = Some(machine);
+                    return Poll::Ready(Some(Ok(yielded)));
+                }
+                Err(machine) => {
+                    this.machine = Some(machine);
+
+                    match this.on_invalid {
+                        OnInvalid::Skip => continue,
+                        OnInvalid::Error => return Poll::Ready(Some(Err(event))),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// EventSink is a sink that applies every event it receives to a machine.
+#[derive(Debug)]
+pub struct EventSink<V> {
+    // The machine is only taken out while an event is applied to it.
+    machine: Option<V>,
+    on_invalid: OnInvalid,
+}
+
+impl<V> EventSink<V> {
+    /// new creates a new sink, applying the received events to the provided
+    /// machine. Invalid events are skipped, unless configured otherwise using
+    /// `on_invalid`.
+    pub fn new(machine: V) -> Self {
+        EventSink {
+            machine: Some(machine),
+            on_invalid: OnInvalid::Skip,
+        }
+    }
+
+    /// on_invalid configures how the sink handles events without a valid
+    /// transition.
+    pub fn on_invalid(mut self, on_invalid: OnInvalid) -> Self {
+        self.on_invalid = on_invalid;
+        self
+    }
+
+    /// machine returns a reference to the current machine.
+    pub fn machine(&self) -> &V {
+        self.machine.as_ref().expect(LOST)
+    }
+
+    /// into_machine consumes the sink, and returns the current machine.
+    pub fn into_machine(self) -> V {
+        self.machine.expect(LOST)
+    }
+}
+
+impl<V, E> Sink<E> for EventSink<V>
+where
+    E: Clone,
+    V: TryTransition<E> + Unpin,
+{
+    type Error = E;
+
+    fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), E>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, event: E) -> Result<(), E> {
+        let this = self.get_mut();
+
+        let machine = this.machine.take().expect(LOST);
+        match machine.try_transition(event.clone()) {
+            Ok(machine) => {
+                this.machine = Some(machine);
+                Ok(())
+            }
+            Err(machine) => {
+                this.machine = Some(machine);
+
+                match this.on_invalid {
+                    OnInvalid::Skip => Ok(()),
+                    OnInvalid::Error => Err(event),
+                }
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), E>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), E>> {
+        Poll::Ready(Ok(()))
+    }
+}
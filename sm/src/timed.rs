@@ -0,0 +1,343 @@
+//! Keep track of the time a machine has spent in its current state.
+//!
+//! `Timed` wraps a machine and a `Clock`, and records the time at which the
+//! machine entered its current state. Events applied through it that move the
+//! machine to another state reset that time, while events leading back to the
+//! same state don't, so `time_in_current_state` can drive timeouts and retry
+//! policies built on top of the machine.
+//!
+//! The clock is pluggable: `SystemClock` uses the monotonic clock of the
+//! standard library (the `std` feature), and any other source of time, such as
+//! a hardware timer, can implement `Clock` itself:
+//!
+//! ```rust
+//! extern crate sm;
+//! use std::cell::Cell;
+//! use sm::sm;
+//! use sm::timed::{Clock, Timed};
+//!
+//! sm! {
+//!     #[table]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! struct Ticks<'a>(&'a Cell<u64>);
+//!
+//! impl<'a> Clock for Ticks<'a> {
+//!     type Instant = u64;
+//!     type Duration = u64;
+//!
+//!     fn now(&self) -> u64 {
+//!         self.0.get()
+//!     }
+//!
+//!     fn elapsed(&self, since: u64) -> u64 {
+//!         self.0.get() - since
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let ticks = Cell::new(0);
+//!     let mut sm = Timed::new(Machine::new(Locked).as_enum(), Ticks(&ticks));
+//!
+//!     ticks.set(5);
+//!     assert_eq!(sm.time_in_current_state(), 5);
+//!
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!     ticks.set(7);
+//!     assert_eq!(sm.time_in_current_state(), 2);
+//! }
+//! ```
+//!
+//! The time spent in a state can be recorded every time the machine leaves it,
+//! by a `Recorder`. `Histograms` keeps a histogram of these dwell times for
+//! every state, with one counter per bucket, and, with the `metrics` feature,
+//! `Metrics` records them in the `sm_dwell_seconds` histogram of the
+//! [`metrics`][m] crate, labeled with the machine and the state:
+//!
+//! ```rust
+//! extern crate sm;
+//! use std::cell::Cell;
+//! use sm::sm;
+//! use sm::timed::{Clock, Histograms, Timed};
+//!
+//! sm! {
+//!     #[table]
+//!     #[footprint]
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! struct Ticks<'a>(&'a Cell<u64>);
+//!
+//! impl<'a> Clock for Ticks<'a> {
+//!     type Instant = u64;
+//!     type Duration = u64;
+//!
+//!     fn now(&self) -> u64 {
+//!         self.0.get()
+//!     }
+//!
+//!     fn elapsed(&self, since: u64) -> u64 {
+//!         self.0.get() - since
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let ticks = Cell::new(0);
+//!     let histograms = Histograms::<u64, { Lock::STATE_COUNT }, 2>::new([10, 100]);
+//!     let mut sm = Timed::new(Machine::new(Locked).as_enum(), Ticks(&ticks)).with_recorder(histograms);
+//!
+//!     ticks.set(5);
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!     ticks.set(55);
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!     ticks.set(555);
+//!     sm.apply(EventId::TurnKey).unwrap();
+//!
+//!     assert_eq!(sm.recorder().counts(StateId::Locked), &[1, 0]);
+//!     assert_eq!(sm.recorder().overflow(StateId::Locked), 1);
+//!     assert_eq!(sm.recorder().counts(StateId::Unlocked), &[0, 1]);
+//! }
+//! ```
+//!
+//! [m]: https://docs.rs/metrics
+
+use core::fmt;
+
+use crate::{Id, Table, TransitionError, TryTransition};
+
+/// Clock provides the current time to a `Timed` machine.
+pub trait Clock {
+    /// Instant is a point in time, as returned by `now`.
+    type Instant: Copy + fmt::Debug;
+
+    /// Duration is the time elapsed between two instants.
+    type Duration;
+
+    /// now returns the current time.
+    fn now(&self) -> Self::Instant;
+
+    /// elapsed returns the time elapsed between the provided instant and now.
+    fn elapsed(&self, since: Self::Instant) -> Self::Duration;
+}
+
+/// SystemClock is the monotonic clock of the standard library.
+#[cfg(feature = "std")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+#[cfg(feature = "std")]
+impl Clock for SystemClock {
+    type Instant = std::time::Instant;
+    type Duration = std::time::Duration;
+
+    fn now(&self) -> Self::Instant {
+        std::time::Instant::now()
+    }
+
+    fn elapsed(&self, since: Self::Instant) -> Self::Duration {
+        since.elapsed()
+    }
+}
+
+/// Recorder records the time a machine spent in a state, every time it leaves
+/// that state. The unit type records nothing.
+pub trait Recorder<S, D> {
+    /// record records that the machine left the state after the duration.
+    fn record(&mut self, state: S, duration: D);
+}
+
+impl<S, D> Recorder<S, D> for () {
+    fn record(&mut self, _: S, _: D) {}
+}
+
+/// Histograms records the time a machine spent in each of its `S` states in
+/// histograms of `B` buckets. A duration is counted in the first bucket whose
+/// upper bound it doesn't exceed, or as an overflow if it exceeds them all.
+#[derive(Clone, Debug)]
+pub struct Histograms<D, const S: usize, const B: usize> {
+    bounds: [D; B],
+    counts: [[u64; B]; S],
+    overflow: [u64; S],
+}
+
+impl<D, const S: usize, const B: usize> Histograms<D, S, B> {
+    /// new creates empty histograms, with buckets bounded by the provided
+    /// durations, in increasing order.
+    pub fn new(bounds: [D; B]) -> Self {
+        Histograms {
+            bounds,
+            counts: [[0; B]; S],
+            overflow: [0; S],
+        }
+    }
+
+    /// bounds returns the upper bounds of the buckets.
+    pub fn bounds(&self) -> &[D; B] {
+        &self.bounds
+    }
+
+    /// counts returns the number of durations counted in each bucket of the
+    /// histogram of the state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `S` is smaller than the number of states of the machine.
+    pub fn counts<I: Id>(&self, state: I) -> &[u64; B] {
+        &self.counts[state.index()]
+    }
+
+    /// overflow returns the number of durations of the state exceeding the
+    /// upper bound of the last bucket.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `S` is smaller than the number of states of the machine.
+    pub fn overflow<I: Id>(&self, state: I) -> u64 {
+        self.overflow[state.index()]
+    }
+}
+
+impl<I: Id, D: PartialOrd, const S: usize, const B: usize> Recorder<I, D> for Histograms<D, S, B> {
+    fn record(&mut self, state: I, duration: D) {
+        let index = state.index();
+        let count = match self.bounds.iter().position(|bound| duration <= *bound) {
+            Some(bucket) => self.counts.get_mut(index).and_then(|counts| counts.get_mut(bucket)),
+            None => self.overflow.get_mut(index),
+        };
+
+        if let Some(count) = count {
+            *count += 1;
+        }
+    }
+}
+
+/// Metrics records the time a machine spent in each state in the
+/// `sm_dwell_seconds` histogram of the `metrics` crate, labeled with the name
+/// of the machine and of the state.
+#[cfg(feature = "metrics")]
+#[derive(Clone, Copy, Debug)]
+pub struct Metrics {
+    machine: &'static str,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// new creates a recorder for machines of type `V`.
+    pub fn new<V: Table>() -> Self {
+        Metrics { machine: V::NAME }
+    }
+}
+
+#[cfg(feature = "metrics")]
+impl<I: Id> Recorder<I, core::time::Duration> for Metrics {
+    fn record(&mut self, state: I, duration: core::time::Duration) {
+        crate::export::metrics::histogram!(
+            "sm_dwell_seconds",
+            "machine" => self.machine,
+            "state" => state.name()
+        )
+        .record(duration.as_secs_f64());
+    }
+}
+
+/// Timed applies events to a machine, and records the time at which it
+/// entered its current state. The time it spent in the states it leaves is
+/// passed to its `Recorder`.
+#[derive(Debug)]
+pub struct Timed<V, C: Clock, R = ()> {
+    machine: V,
+    clock: C,
+    entered: C::Instant,
+    recorder: R,
+}
+
+impl<V, C: Clock> Timed<V, C> {
+    /// new wraps the provided machine, which enters its current state now.
+    pub fn new(machine: V, clock: C) -> Self {
+        let entered = clock.now();
+
+        Timed {
+            machine,
+            clock,
+            entered,
+            recorder: (),
+        }
+    }
+}
+
+impl<V, C: Clock, R> Timed<V, C, R> {
+    /// with_recorder replaces the recorder of the time spent in each state.
+    pub fn with_recorder<T>(self, recorder: T) -> Timed<V, C, T> {
+        Timed {
+            machine: self.machine,
+            clock: self.clock,
+            entered: self.entered,
+            recorder,
+        }
+    }
+
+    /// recorder returns a reference to the recorder.
+    pub fn recorder(&self) -> &R {
+        &self.recorder
+    }
+
+    /// machine returns a reference to the current machine.
+    pub fn machine(&self) -> &V {
+        &self.machine
+    }
+
+    /// into_machine consumes the wrapper, and returns the current machine.
+    pub fn into_machine(self) -> V {
+        self.machine
+    }
+
+    /// time_in_current_state returns the time elapsed since the machine
+    /// entered its current state.
+    pub fn time_in_current_state(&self) -> C::Duration {
+        self.clock.elapsed(self.entered)
+    }
+}
+
+impl<V, C, R> Timed<V, C, R>
+where
+    V: Table + TryTransition<<V as Table>::EventId> + Clone,
+    C: Clock,
+    R: Recorder<V::StateId, C::Duration>,
+{
+    /// apply applies the event to the machine. If the machine moves to
+    /// another state, the time it spent in the previous state is recorded,
+    /// and it enters the new state now.
+    pub fn apply(&mut self, event: V::EventId) -> Result<(), TransitionError<V::StateId, V::EventId>> {
+        let state = self.machine.state();
+        let prepared = match self.machine.prepare(event) {
+            Some(prepared) => prepared,
+            None => return Err(TransitionError::Invalid { state, event }),
+        };
+
+        if prepared.commit().state() != state {
+            self.recorder.record(state, self.clock.elapsed(self.entered));
+            self.entered = self.clock.now();
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+//! Publish the state of a machine on a [`tokio::sync::watch`][w] channel.
+//!
+//! A `Watch` sits next to a machine, and forwards the state the machine is in
+//! after every transition to all subscribed receivers. Other tasks can then
+//! `await` state changes, instead of polling the machine.
+//!
+//! The published value is anything the states of the machine can be converted
+//! into. The `sm!` macro generates a `StateId` enum for every machine, which is
+//! the obvious candidate:
+//!
+//! ```rust
+//! extern crate sm;
+//! extern crate tokio;
+//! use sm::sm;
+//! use sm::watch::Watch;
+//!
+//! sm! {
+//!     Lock {
+//!         InitialStates { Locked }
+//!
+//!         TurnKey {
+//!             Locked => Unlocked
+//!             Unlocked => Locked
+//!         }
+//!     }
+//! }
+//!
+//! fn main() {
+//!     use Lock::*;
+//!
+//!     let sm = Machine::new(Locked);
+//!     let (watch, receiver) = Watch::<StateId>::new(&sm);
+//!     assert_eq!(*receiver.borrow(), StateId::Locked);
+//!
+//!     let sm = watch.transition(sm, TurnKey);
+//!     assert_eq!(sm.state(), Unlocked);
+//!     assert_eq!(*receiver.borrow(), StateId::Unlocked);
+//! }
+//! ```
+//!
+//! [w]: https://docs.rs/tokio/1/tokio/sync/watch/index.html
+
+use tokio::sync::watch::{channel, Receiver, Sender};
+
+use crate::{Event, Machine, Transition};
+
+/// Watch publishes the current state of a machine to any number of
+/// receivers.
+#[derive(Debug)]
+pub struct Watch<T> {
+    sender: Sender<T>,
+}
+
+impl<T> Watch<T> {
+    /// new creates a new watch, seeded with the current state of the provided
+    /// machine, and returns it together with a first receiver.
+    pub fn new<M>(machine: &M) -> (Self, Receiver<T>)
+    where
+        M: Machine,
+        M::State: Into<T>,
+    {
+        let (sender, receiver) = channel(machine.state().into());
+
+        (Watch { sender }, receiver)
+    }
+
+    /// subscribe returns a new receiver, which sees the most recently published
+    /// state as its initial value.
+    pub fn subscribe(&self) -> Receiver<T> {
+        self.sender.subscribe()
+    }
+
+    /// transition consumes the machine, transitions it using the passed in
+    /// event, and publishes the state of the returned machine.
+    pub fn transition<M, E>(&self, machine: M, event: E) -> M::Machine
+    where
+        M: Transition<E>,
+        E: Event,
+        <M::Machine as Machine>::State: Into<T>,
+    {
+        let machine = machine.transition(event);
+        self.publish(&machine);
+
+        machine
+    }
+
+    /// publish sends the current state of the machine to all receivers.
+    ///
+    /// This is done automatically when using `Watch::transition`, but can be
+    /// used directly when the machine is transitioned elsewhere.
+    pub fn publish<M>(&self, machine: &M)
+    where
+        M: Machine,
+        M::State: Into<T>,
+    {
+        let _ = self.sender.send_replace(machine.state().into());
+    }
+}
@@ -0,0 +1,20 @@
+extern crate sm;
+use sm::dual;
+
+dual! {
+    Client {
+        InitialStates { Idle }
+
+        SendRequest {
+            Idle => Waiting
+        }
+
+        Timeout {
+            Waiting => Idle
+        }
+    }
+
+    Server
+}
+
+fn main() {}
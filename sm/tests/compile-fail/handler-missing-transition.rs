@@ -0,0 +1,22 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[handler]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+struct Door;
+
+impl Lock::LockHandler for Door {
+    fn on_locked_turn_key(&mut self) {}
+}
+
+fn main() {}
@@ -15,6 +15,5 @@ fn main() {
     let sm = Machine::new(Locked);
     while sm.state() == Locked {
         sm = sm.transition(TurnKey);
-        //~^ ERROR mismatched types
     }
 }
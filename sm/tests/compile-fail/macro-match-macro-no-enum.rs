@@ -0,0 +1,16 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[match_macro]
+    #[no_enum]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[pushdown(4)]
+    #[no_enum]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {}
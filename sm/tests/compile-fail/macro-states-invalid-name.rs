@@ -4,6 +4,7 @@ use sm::sm;
 sm!{
     Lock {
         InitialStates { 1, 2 }
-        //~^ ERROR expected identifier
     }
 }
+
+fn main() {}
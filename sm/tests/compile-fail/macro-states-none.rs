@@ -3,5 +3,6 @@ use sm::sm;
 
 sm!{
     Lock {}
-    //~^ ERROR unexpected end of input, expected identifier
 }
+
+fn main() {}
@@ -0,0 +1,15 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[undo(0)]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[match_macro]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    let _ = lock_match!(sm, Locked(_) => 1, Unlocked(_) => 2, Locked(_) => 3);
+}
@@ -0,0 +1,19 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[no_cross_eq]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let _ = Locked == Unlocked;
+}
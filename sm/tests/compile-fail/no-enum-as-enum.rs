@@ -0,0 +1,20 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[no_enum]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+    let sm = Machine::new(Locked);
+
+    sm.as_enum();
+}
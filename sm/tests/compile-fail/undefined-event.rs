@@ -14,6 +14,4 @@ fn main() {
     let sm = Machine::new(Locked);
 
     sm.transition(Invalid);
-    //~^ ERROR cannot find value `Invalid` in this scope
-    //~| ERROR no method named `transition` found for type `Lock::Machine<Lock::Locked, sm::NoneEvent>` in the current scope
 }
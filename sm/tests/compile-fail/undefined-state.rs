@@ -11,5 +11,4 @@ fn main() {
     use Lock::*;
 
     let _ = Machine::new(Invalid);
-    //~^ ERROR cannot find value `Invalid` in this scope
 }
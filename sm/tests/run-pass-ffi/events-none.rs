@@ -0,0 +1,17 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let mut machine: FfiMachine = unsafe { core::mem::zeroed() };
+
+    assert!(lock_new(StateId::Locked, &mut machine));
+    assert_eq!(lock_state(&machine), StateId::Locked);
+}
@@ -0,0 +1,38 @@
+extern crate core;
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[python]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    assert!(PyMachine::new("Unlocked").is_err());
+
+    let mut machine = PyMachine::new("Locked").unwrap();
+    assert_eq!(machine.state(), "Locked");
+    assert_eq!(machine.permitted_events(), ["TurnKey"]);
+
+    machine.apply("TurnKey").unwrap();
+    assert_eq!(machine.state(), "Unlocked");
+    assert_eq!(machine.permitted_events(), ["TurnKey", "Break"]);
+
+    assert!(machine.apply("Push").is_err());
+    machine.apply("Break").unwrap();
+    assert!(machine.apply("TurnKey").is_err());
+    assert!(machine.machine().is_broken());
+}
@@ -0,0 +1,61 @@
+extern crate sm;
+extern crate tracing_crate as tracing;
+use sm::{sm, TryTransition};
+use std::sync::Mutex;
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+static SPANS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+static EVENTS: Mutex<usize> = Mutex::new(0);
+
+struct Recorder;
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut spans = SPANS.lock().unwrap();
+        spans.push(span.metadata().name());
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {
+        *EVENTS.lock().unwrap() += 1;
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+fn main() {
+    use Lock::*;
+
+    tracing::subscriber::with_default(Recorder, || {
+        let sm = Machine::new(Locked).as_enum();
+        let _locked = sm.state_id().span().entered();
+
+        let sm = sm.try_transition(EventId::TurnKey).unwrap();
+        let _unlocked = sm.state_id().span().entered();
+    });
+
+    assert_eq!(*SPANS.lock().unwrap(), ["Locked", "Unlocked"]);
+    assert_eq!(*EVENTS.lock().unwrap(), 1);
+}
@@ -0,0 +1,47 @@
+extern crate sm;
+use sm::{sm, Accepts, Event};
+
+sm!{
+    TurnStile {
+        InitialStates { Locked }
+
+        Coin {
+            Locked => Unlocked
+        }
+
+        Push {
+            Unlocked => Locked
+        }
+    }
+}
+
+use TurnStile::{Coin, Machine, Push};
+
+fn pay<S, E>(sm: Machine<S, E>) -> Machine<S::Next, Coin>
+where
+    S: Accepts<Coin>,
+    E: Event,
+    Machine<S, E>: Transition<Coin, Machine = Machine<S::Next, Coin>>,
+{
+    sm.transition(Coin)
+}
+
+fn pass<S, E>(sm: Machine<S, E>) -> Machine<S::Next, Push>
+where
+    S: Accepts<Push>,
+    E: Event,
+    Machine<S, E>: Transition<Push, Machine = Machine<S::Next, Push>>,
+{
+    sm.transition(Push)
+}
+
+fn main() {
+    use TurnStile::*;
+
+    let sm = pay(Machine::new(Locked));
+    assert_eq!(sm.state(), Unlocked);
+
+    let sm = pass(sm);
+    assert_eq!(sm.state(), Locked);
+    assert_eq!(sm.trigger(), Some(Push));
+}
@@ -0,0 +1,48 @@
+extern crate sm;
+use sm::sm;
+use sm::TransitionError;
+
+sm!{
+    #[dynamic]
+    Door {
+        InitialStates { Closed }
+
+        Open { Closed => Opened }
+        Close { Opened => Closed }
+    }
+
+    #[apply]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    {
+        use Lock::*;
+
+        let mut sm = Machine::new(Locked).as_enum();
+        sm.apply_named("TurnKey").unwrap();
+        assert_eq!(sm.state_id(), StateId::Unlocked);
+        assert_eq!(sm.apply_named("Kick"), Err(TransitionError::UnknownEvent));
+        assert_eq!(sm.state_id(), StateId::Unlocked);
+    }
+
+    {
+        use Door::*;
+
+        let mut sm = Machine::new(StateId::Closed).unwrap();
+        sm.apply_named("Open").unwrap();
+        assert_eq!(sm.state_id(), StateId::Opened);
+        assert_eq!(
+            sm.apply_named("Open"),
+            Err(TransitionError::Invalid { state: StateId::Opened, event: EventId::Open }),
+        );
+        assert_eq!(sm.apply_named("open"), Err(TransitionError::UnknownEvent));
+    }
+}
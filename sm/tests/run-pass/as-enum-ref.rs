@@ -0,0 +1,31 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    match sm.as_enum_ref() {
+        VariantRef::InitialLocked(m) => assert_eq!(m.state(), Locked),
+        _ => panic!("expected InitialLocked"),
+    }
+
+    let sm = sm.transition(TurnKey).as_enum();
+    match sm.as_enum_ref() {
+        VariantRef::UnlockedByTurnKey(m) => assert_eq!(m.trigger(), Some(TurnKey)),
+        _ => panic!("expected UnlockedByTurnKey"),
+    }
+
+    assert_eq!(sm.state_id(), StateId::Unlocked);
+}
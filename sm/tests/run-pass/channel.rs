@@ -0,0 +1,57 @@
+extern crate sm;
+use sm::channel::{ChannelError, Transport};
+use sm::sm;
+
+sm! {
+    #[channel]
+    Client {
+        InitialStates { Idle }
+
+        SendRequest {
+            Idle => Waiting
+        }
+
+        ReceiveResponse {
+            Waiting => Idle
+        }
+
+        ReceiveError {
+            Waiting => Failed
+        }
+    }
+}
+
+use Client::*;
+
+#[derive(Debug, Default)]
+struct Replies(Vec<EventId>);
+
+impl Transport<EventId> for Replies {
+    type Error = ();
+
+    fn send(&mut self, _: EventId) -> Result<(), ()> {
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<EventId, ()> {
+        if self.0.is_empty() {
+            Err(())
+        } else {
+            Ok(self.0.remove(0))
+        }
+    }
+}
+
+fn main() {
+    let replies = Replies(vec![EventId::SendRequest, EventId::ReceiveError]);
+    let channel = Channel::new(Idle, replies).send_request().unwrap();
+
+    let (channel, err) = channel.receive().unwrap_err();
+    assert_eq!(err, ChannelError::Unexpected(EventId::SendRequest));
+
+    let channel = match channel.receive().unwrap() {
+        WaitingReceived::ReceiveResponse(_) => unreachable!(),
+        WaitingReceived::ReceiveError(channel) => channel,
+    };
+    assert_eq!(channel.state(), Failed);
+}
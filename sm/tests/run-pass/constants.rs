@@ -0,0 +1,29 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+const STATES: [Lock::StateId; 2] = [Lock::StateId::Locked, Lock::StateId::Unlocked];
+const INITIAL: Lock::Locked = Lock::LOCKED;
+const KEY: Lock::TurnKey = Lock::TURN_KEY;
+
+fn main() {
+    use Lock::*;
+
+    assert_eq!(INITIAL, Locked);
+    assert_eq!(KEY, TurnKey);
+    assert_eq!(STATES.len(), 2);
+
+    let sm = Machine::new(INITIAL).transition(KEY);
+    assert_eq!(sm.state(), UNLOCKED);
+    assert_eq!(sm.trigger(), Some(TURN_KEY));
+}
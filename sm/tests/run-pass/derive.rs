@@ -0,0 +1,28 @@
+extern crate sm;
+use sm::{AsEnum, Initializer, Machine as M, StateMachine, Transition};
+
+#[derive(Debug, PartialEq, StateMachine)]
+enum Lock {
+    #[state(initial)]
+    #[transition(on = TurnKey, to = Unlocked)]
+    #[transition(on = Break, to = Broken)]
+    Locked,
+
+    #[state]
+    #[transition(on = TurnKey, to = Locked)]
+    Unlocked,
+
+    Broken,
+}
+
+fn main() {
+    use LockMachine::*;
+
+    let sm = Machine::new(Locked);
+    let sm = sm.transition(TurnKey);
+    assert_eq!(Lock::from(sm.as_enum().state_id()), Lock::Unlocked);
+
+    let sm = Machine::new(Locked).transition(Break);
+    assert_eq!(sm.state(), Broken);
+    assert_eq!(Lock::from(StateId::Broken), Lock::Broken);
+}
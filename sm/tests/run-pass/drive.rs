@@ -0,0 +1,36 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[drive]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked).as_enum();
+    let states: Vec<StateId> = sm
+        .drive(vec![EventId::TurnKey, EventId::Break, EventId::TurnKey, EventId::Break])
+        .map(|sm| sm.state_id())
+        .collect();
+    assert_eq!(states, vec![StateId::Unlocked, StateId::Locked, StateId::Broken]);
+
+    let sm = Machine::new(Locked).as_enum();
+    let mut snapshots = sm.drive(vec![EventId::TurnKey, EventId::TurnKey]);
+    let first = snapshots.next().unwrap();
+    assert_eq!(first.state_id(), StateId::Unlocked);
+    assert_eq!(first.trigger_id(), Some(EventId::TurnKey));
+    assert_eq!(snapshots.into_machine().state_id(), first.state_id());
+}
@@ -0,0 +1,36 @@
+extern crate sm;
+use sm::dual;
+
+dual! {
+    Client {
+        InitialStates { Idle }
+
+        SendRequest {
+            Idle => Waiting
+        }
+
+        ReceiveResponse {
+            Waiting => Idle
+        }
+    }
+
+    Server
+}
+
+fn main() {
+    let sm = Client::Machine::new(Client::Idle)
+        .transition(Client::SendRequest)
+        .transition(Client::ReceiveResponse);
+    assert_eq!(sm.state(), Client::Idle);
+
+    let sm = Server::Machine::new(Server::Idle)
+        .transition(Server::ReceiveRequest)
+        .transition(Server::SendResponse);
+    assert_eq!(sm.state(), Server::Idle);
+
+    let event: Server::EventId = Client::EventId::SendRequest.into();
+    assert_eq!(event, Server::EventId::ReceiveRequest);
+
+    let event: Client::EventId = Server::EventId::SendResponse.into();
+    assert_eq!(event, Client::EventId::ReceiveResponse);
+}
@@ -0,0 +1,62 @@
+extern crate sm;
+
+macro_rules! lock {
+    ($($extension:tt)*) => {
+        ::sm::extend! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+
+            $($extension)*
+        }
+    };
+}
+
+// The modules generated by `extend!` are private, so each machine is used
+// from the module it is generated in.
+mod upstream {
+    lock!();
+
+    pub fn check() {
+        use self::Lock::*;
+
+        let sm = Machine::new(Locked).transition(TurnKey);
+        assert_eq!(sm.state(), Unlocked);
+    }
+}
+
+mod downstream {
+    lock! {
+        SuspendableLock {
+            InitialStates { Suspended }
+
+            Suspend {
+                Locked, Unlocked => Suspended
+            }
+
+            Resume {
+                Suspended => Locked
+            }
+        }
+    }
+
+    pub fn check() {
+        use self::SuspendableLock::*;
+
+        let sm = Machine::new(Locked).transition(TurnKey).transition(Suspend);
+        assert_eq!(sm.state(), Suspended);
+
+        let sm = Machine::new(Suspended).transition(Resume);
+        assert_eq!(sm.state(), Locked);
+    }
+}
+
+fn main() {
+    upstream::check();
+    downstream::check();
+}
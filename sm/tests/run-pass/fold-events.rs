@@ -0,0 +1,34 @@
+extern crate sm;
+use sm::sm;
+use sm::TransitionError;
+
+sm!{
+    #[fold_events]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked).as_enum();
+    let sm = sm.fold_events(vec![EventId::TurnKey, EventId::TurnKey, EventId::Break]).unwrap();
+    assert_eq!(sm.state_id(), StateId::Broken);
+    assert_eq!(sm.trigger_id(), Some(EventId::Break));
+
+    let sm = Machine::new(Locked).as_enum();
+    let (sm, err) = sm.fold_events(vec![EventId::TurnKey, EventId::Break, EventId::TurnKey]).unwrap_err();
+    assert_eq!(sm.state_id(), StateId::Unlocked);
+    assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+    assert_eq!(err, TransitionError::Invalid { state: StateId::Unlocked, event: EventId::Break });
+}
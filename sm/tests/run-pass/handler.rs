@@ -0,0 +1,77 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[dynamic]
+    #[handler]
+    Door {
+        InitialStates { Closed }
+
+        Open { Closed => Opened }
+        Close { Opened => Closed }
+    }
+
+    #[handler]
+    TurnStile {
+        InitialStates { Locked }
+
+        Coin { Locked => Unlocked }
+        Push { Unlocked => Locked }
+    }
+}
+
+#[derive(Default)]
+struct Counter {
+    coins: u32,
+    pushes: u32,
+    opened: u32,
+    closed: u32,
+}
+
+impl TurnStile::TurnStileHandler for Counter {
+    fn on_locked_coin(&mut self) {
+        self.coins += 1;
+    }
+
+    fn on_unlocked_push(&mut self) {
+        self.pushes += 1;
+    }
+}
+
+impl Door::DoorHandler for Counter {
+    fn on_closed_open(&mut self) {
+        self.opened += 1;
+    }
+
+    fn on_opened_close(&mut self) {
+        self.closed += 1;
+    }
+}
+
+fn main() {
+    let mut counter = Counter::default();
+
+    {
+        use TurnStile::*;
+
+        let mut sm = Machine::new(Locked).as_enum();
+        sm.apply_with(EventId::Coin, &mut counter).unwrap();
+        assert!(sm.apply_with(EventId::Coin, &mut counter).is_err());
+        sm.apply_with(EventId::Push, &mut counter).unwrap();
+        assert_eq!(sm.state_id(), StateId::Locked);
+    }
+
+    {
+        use Door::*;
+
+        let mut sm = Machine::new(StateId::Closed).unwrap();
+        sm.apply_with(EventId::Open, &mut counter).unwrap();
+        assert!(sm.apply_with(EventId::Open, &mut counter).is_err());
+        sm.apply_with(EventId::Close, &mut counter).unwrap();
+    }
+
+    assert_eq!(counter.coins, 1);
+    assert_eq!(counter.pushes, 1);
+    assert_eq!(counter.opened, 1);
+    assert_eq!(counter.closed, 1);
+}
@@ -0,0 +1,46 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[erase_trigger]
+    Door {
+        InitialStates { Closed, Opened }
+
+        Open { Closed => Opened }
+        Close { Opened => Closed }
+    }
+
+    Lock {
+        InitialStates { Locked, Unlocked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+
+    #[zero_sized]
+    Light {
+        InitialStates { Off }
+
+        Toggle {
+            Off => On
+            On => Off
+        }
+    }
+}
+
+fn main() {
+    let sm = Lock::Machine::locked();
+    assert_eq!(sm, Lock::Machine::new(Lock::Locked));
+
+    let sm = Lock::Machine::unlocked().transition(Lock::TurnKey);
+    assert_eq!(sm.state(), Lock::Locked);
+
+    let sm = Door::Machine::opened();
+    assert_eq!(sm.state(), Door::Opened);
+    assert_eq!(sm.trigger(), None);
+
+    let sm = Light::Machine::off().transition(Light::Toggle);
+    assert_eq!(sm.state(), Light::On);
+}
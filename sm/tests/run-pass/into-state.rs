@@ -0,0 +1,43 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[erase_trigger]
+    Door {
+        InitialStates { Closed }
+
+        Open { Closed => Opened }
+    }
+
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn unlocked(state: impl Into<Lock::Unlocked>) -> Lock::Unlocked {
+    state.into()
+}
+
+fn main() {
+    {
+        use Lock::*;
+
+        let locked: Locked = Machine::new(Locked).into();
+        assert_eq!(locked, Locked);
+
+        assert_eq!(unlocked(Unlocked), Unlocked);
+        assert_eq!(unlocked(Machine::new(Locked).transition(TurnKey)), Unlocked);
+    }
+
+    {
+        use Door::*;
+
+        let opened: Opened = Machine::new(Closed).transition(Open).into();
+        assert_eq!(opened, Opened);
+    }
+}
@@ -0,0 +1,28 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let locked: &'static str = Locked.into();
+    let unlocked: &'static str = Unlocked.into();
+    let turn_key: &'static str = TurnKey.into();
+    assert_eq!(locked, "Locked");
+    assert_eq!(unlocked, "Unlocked");
+    assert_eq!(turn_key, "TurnKey");
+
+    let sm = Machine::new(Locked).transition(TurnKey).as_enum();
+    assert_eq!(<&str>::from(sm.state_id()), "Unlocked");
+    assert_eq!(<&str>::from(EventId::TurnKey), "TurnKey");
+}
@@ -0,0 +1,32 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    assert!(sm.is::<Locked>());
+    assert!(!sm.is::<Unlocked>());
+    assert!(sm.is_locked());
+    assert!(!sm.is_broken());
+
+    let sm = sm.transition(TurnKey).as_enum();
+    assert!(sm.is::<Unlocked>());
+    assert!(sm.is_unlocked());
+    assert!(!sm.is_locked());
+}
@@ -0,0 +1,49 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[erase_trigger]
+    Door {
+        InitialStates { Closed }
+
+        Open { Closed => Opened }
+    }
+
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    {
+        use Lock::*;
+
+        let sm = Machine::new(Locked);
+        assert_eq!(format!("{:?}", sm), "Lock::Machine { state: Locked, trigger: None }");
+
+        let sm = sm.transition(TurnKey);
+        assert_eq!(
+            format!("{:?}", sm),
+            "Lock::Machine { state: Unlocked, trigger: Some(TurnKey) }",
+        );
+        assert_eq!(
+            format!("{:?}", sm.as_enum()),
+            "UnlockedByTurnKey(Lock::Machine { state: Unlocked, trigger: Some(TurnKey) })",
+        );
+    }
+
+    {
+        use Door::*;
+
+        let sm = Machine::new(Closed).transition(Open);
+        assert_eq!(
+            format!("{:?}", sm),
+            "Door::Machine { state: Opened, trigger: Some(Open) }",
+        );
+    }
+}
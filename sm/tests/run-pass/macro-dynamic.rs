@@ -0,0 +1,33 @@
+extern crate sm;
+use sm::sm;
+use sm::Table;
+
+sm!{
+    #[dynamic]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    assert!(Machine::new(StateId::Broken).is_none());
+
+    let mut sm = Machine::new(StateId::Locked).unwrap();
+    sm.apply(EventId::TurnKey).unwrap();
+    assert_eq!(sm.state(), StateId::Unlocked);
+    assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+
+    assert!(sm.apply(EventId::Break).is_err());
+    assert_eq!(sm.state(), StateId::Unlocked);
+}
@@ -0,0 +1,37 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[erase_trigger]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked, Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    assert_eq!(sm.trigger(), None);
+
+    let sm: Machine<Unlocked> = sm.transition(TurnKey);
+    assert_eq!(sm.trigger(), Some(EventId::TurnKey));
+
+    let sm: Machine<Broken> = sm.transition(Break);
+    assert_eq!(sm.state(), Broken);
+    assert_eq!(sm.trigger(), Some(EventId::Break));
+
+    match sm.as_enum() {
+        Variant::Broken(m) => assert_eq!(m.trigger(), Some(EventId::Break)),
+        _ => panic!("expected the broken state"),
+    }
+}
@@ -0,0 +1,43 @@
+extern crate sm;
+use sm::sm;
+
+mod conn {
+    #[derive(Debug, PartialEq)]
+    pub enum State {
+        Idle,
+        Connected,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Command {
+        Connect,
+        Disconnect,
+    }
+}
+
+sm!{
+    Conn {
+        States = conn::State;
+        Events = conn::Command;
+        InitialStates { Idle }
+
+        Connect {
+            Idle => Connected
+        }
+
+        Disconnect {
+            Connected => Idle
+        }
+    }
+}
+
+fn main() {
+    use Conn::*;
+
+    let sm = Machine::new(Idle).as_enum();
+    assert_eq!(conn::State::from(sm.state_id()), conn::State::Idle);
+
+    let event = EventId::from(conn::Command::Connect);
+    assert_eq!(event, EventId::Connect);
+    assert_eq!(conn::Command::from(EventId::Disconnect), conn::Command::Disconnect);
+}
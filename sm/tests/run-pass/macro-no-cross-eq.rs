@@ -0,0 +1,22 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[no_cross_eq]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked).transition(TurnKey);
+    assert_eq!(sm.state(), Unlocked);
+    assert_eq!(sm.as_enum().state_id(), StateId::Unlocked);
+}
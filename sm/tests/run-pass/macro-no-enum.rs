@@ -0,0 +1,22 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[no_enum]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked).transition(TurnKey);
+    assert_eq!(sm.state(), Unlocked);
+    assert_eq!(StateId::from(sm.state()), StateId::Unlocked);
+}
@@ -0,0 +1,59 @@
+extern crate sm;
+use sm::sm;
+use sm::TransitionError;
+
+sm!{
+    #[pushdown(2)]
+    Game {
+        InitialStates { Playing }
+
+        Open {
+            Playing => Inventory
+        }
+
+        Close {
+            Inventory => Playing
+        }
+
+        Pause {
+            Playing, Inventory => PausedMenu
+        }
+    }
+
+    #[dynamic]
+    #[pushdown(1)]
+    Door {
+        InitialStates { Closed }
+
+        Open {
+            Closed => Opened
+        }
+    }
+}
+
+fn main() {
+    use Game::*;
+
+    let mut sm = Pushdown::new(Machine::new(Playing).as_enum());
+    assert_eq!(sm.pop(), None);
+
+    sm.push(EventId::Open).unwrap();
+    sm.push(EventId::Pause).unwrap();
+    assert_eq!(sm.depth(), 2);
+    assert_eq!(sm.push(EventId::Close), Err(TransitionError::StackFull(EventId::Close)));
+    assert_eq!(sm.machine().state_id(), StateId::PausedMenu);
+
+    assert_eq!(sm.pop(), Some(StateId::Inventory));
+    assert_eq!(sm.machine().trigger_id(), Some(EventId::Open));
+
+    sm.apply(EventId::Close).unwrap();
+    assert_eq!(sm.depth(), 1);
+    assert_eq!(sm.pop(), Some(StateId::Playing));
+    assert_eq!(sm.pop(), None);
+    assert_eq!(sm.into_machine().state_id(), StateId::Playing);
+
+    let mut door = Door::Pushdown::new(Door::Machine::new(Door::StateId::Closed).unwrap());
+    door.push(Door::EventId::Open).unwrap();
+    assert_eq!(door.machine().state_id(), Door::StateId::Opened);
+    assert_eq!(door.pop(), Some(Door::StateId::Closed));
+}
@@ -0,0 +1,28 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        state Locked {
+            on TurnKey => Unlocked;
+            on Break => Broken;
+        }
+
+        state Unlocked {
+            on TurnKey => Locked;
+        }
+
+        Repair {
+            Broken => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked).transition(Break).transition(Repair);
+    assert_eq!(sm.state(), Locked);
+}
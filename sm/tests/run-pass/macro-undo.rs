@@ -0,0 +1,58 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[undo(2)]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Unlocked => Broken
+        }
+    }
+
+    #[dynamic]
+    #[undo(1)]
+    Door {
+        InitialStates { Closed }
+
+        Open {
+            Closed => Opened
+        }
+
+        Close {
+            Opened => Closed
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let mut sm = History::new(Machine::new(Locked).as_enum());
+    assert_eq!(sm.undo(), None);
+
+    sm.apply(EventId::TurnKey).unwrap();
+    sm.apply(EventId::TurnKey).unwrap();
+    sm.apply(EventId::TurnKey).unwrap();
+    assert_eq!(sm.machine().state_id(), StateId::Unlocked);
+
+    assert_eq!(sm.undo(), Some(EventId::TurnKey));
+    assert_eq!(sm.undo(), Some(EventId::TurnKey));
+    assert_eq!(sm.undo(), None);
+    assert_eq!(sm.machine().state_id(), StateId::Unlocked);
+
+    sm.apply(EventId::Break).unwrap();
+    assert_eq!(sm.undo(), None);
+    assert_eq!(sm.into_machine().state_id(), StateId::Broken);
+
+    let mut door = Door::History::new(Door::Machine::new(Door::StateId::Closed).unwrap());
+    door.apply(Door::EventId::Open).unwrap();
+    assert_eq!(door.undo(), Some(Door::EventId::Close));
+    assert_eq!(door.machine().state_id(), Door::StateId::Closed);
+}
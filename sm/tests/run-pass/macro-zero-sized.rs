@@ -0,0 +1,33 @@
+extern crate sm;
+use sm::sm;
+use std::mem::size_of_val;
+
+sm!{
+    #[zero_sized]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    assert_eq!(size_of_val(&sm), 0);
+    assert_eq!(sm.trigger(), None);
+
+    let sm = sm.transition(TurnKey);
+    assert_eq!(size_of_val(&sm), 0);
+    assert_eq!(sm.state(), Unlocked);
+    assert_eq!(sm.trigger(), Some(TurnKey));
+
+    match sm.as_enum() {
+        Variant::UnlockedByTurnKey(_) => {}
+        _ => panic!("expected the unlocked state"),
+    }
+}
@@ -0,0 +1,46 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    #[match_macro]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn describe<M: sm::AsEnum<Enum = Lock::Variant>>(sm: M) -> &'static str {
+    lock_match!(sm,
+        Locked(_) => "locked",
+        Unlocked(m) => {
+            assert_eq!(m.state(), Lock::Unlocked);
+            "unlocked"
+        },
+    )
+}
+
+fn is_locked<M: sm::AsEnum<Enum = Lock::Variant>>(sm: M) -> bool {
+    lock_match!(sm,
+        Unlocked(_) => false,
+        Locked(_) => true
+    )
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    assert_eq!(describe(sm.clone()), "locked");
+    assert!(is_locked(sm.clone()));
+
+    let sm = sm.transition(TurnKey);
+    assert_eq!(describe(sm.clone()), "unlocked");
+    assert!(!is_locked(sm.clone()));
+
+    let sm = sm.transition(TurnKey);
+    assert_eq!(describe(sm), "locked");
+}
@@ -0,0 +1,42 @@
+extern crate sm;
+use sm::{Initializer, Machine as M};
+
+#[derive(Debug)]
+struct Key(u32);
+
+#[derive(Debug, Default)]
+struct Door {
+    turns: u32,
+}
+
+#[sm::machine]
+impl Door {
+    #[transition(Locked => Unlocked)]
+    fn turn_key(&mut self, key: Key) -> u32 {
+        self.turns += key.0;
+        self.turns
+    }
+
+    #[transition(Unlocked => Locked)]
+    fn lock(&mut self) {}
+
+    fn turns(&self) -> u32 {
+        self.turns
+    }
+}
+
+fn main() {
+    use DoorMachine::*;
+
+    let mut door = Door::default();
+
+    let sm = Machine::new(Locked);
+    let (sm, turns) = sm.turn_key(&mut door, Key(2));
+    assert_eq!(turns, 2);
+    assert_eq!(sm.state(), Unlocked);
+    assert_eq!(sm.trigger(), Some(TurnKey));
+
+    let sm = sm.lock(&mut door);
+    assert_eq!(sm.state(), Locked);
+    assert_eq!(door.turns(), 2);
+}
@@ -0,0 +1,32 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+mod door {
+    use Lock::prelude::*;
+
+    pub fn unlock() -> Variant {
+        let sm = Machine::new(Locked);
+        let sm = sm.transition(TurnKey);
+        assert_eq!(sm.state(), Unlocked);
+
+        sm.as_enum()
+    }
+}
+
+fn main() {
+    match door::unlock() {
+        Lock::Variant::UnlockedByTurnKey(_) => {}
+        _ => panic!("unexpected variant"),
+    }
+}
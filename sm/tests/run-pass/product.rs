@@ -0,0 +1,44 @@
+extern crate sm;
+use sm::product;
+
+product! {
+    Session {
+        Client {
+            InitialStates { Idle }
+
+            Connect {
+                Idle => Waiting
+            }
+
+            Accept {
+                Waiting => Connected
+            }
+        }
+
+        Server {
+            InitialStates { Listening }
+
+            Accept {
+                Listening => Serving
+            }
+
+            Close {
+                Serving => Listening
+            }
+        }
+    }
+}
+
+fn main() {
+    use Session::*;
+
+    let sm = Machine::new(IdleListening);
+    let sm = sm.transition(Connect);
+    assert_eq!(sm.state(), WaitingListening);
+
+    let sm = sm.transition(Accept);
+    assert_eq!(sm.state(), ConnectedServing);
+
+    let sm = sm.transition(Close);
+    assert_eq!(sm.state(), ConnectedListening);
+}
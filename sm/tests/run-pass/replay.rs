@@ -0,0 +1,37 @@
+extern crate sm;
+use sm::sm;
+use sm::ReplayError;
+
+sm!{
+    #[replay]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Variant::replay(StateId::Locked, vec![EventId::TurnKey, EventId::TurnKey, EventId::Break]).unwrap();
+    assert_eq!(sm.state_id(), StateId::Broken);
+    assert_eq!(sm.trigger_id(), Some(EventId::Break));
+
+    assert_eq!(
+        Variant::replay(StateId::Unlocked, vec![]).unwrap_err(),
+        ReplayError::NotInitial(StateId::Unlocked)
+    );
+
+    assert_eq!(
+        Variant::replay(StateId::Locked, vec![EventId::TurnKey, EventId::Break]).unwrap_err(),
+        ReplayError::Rejected { step: 1, state: StateId::Unlocked, event: EventId::Break }
+    );
+}
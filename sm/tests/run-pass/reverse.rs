@@ -0,0 +1,23 @@
+extern crate sm;
+use sm::reverse;
+
+reverse! {
+    Wizard {
+        InitialStates { Name }
+
+        Next {
+            Name => Address
+            Address => Confirm
+        }
+    }
+}
+
+fn main() {
+    let sm = Wizard::Machine::new(Wizard::Name).transition(Wizard::Next);
+    assert_eq!(sm.state(), Wizard::Address);
+
+    let sm = WizardReversed::Machine::new(WizardReversed::Confirm)
+        .transition(WizardReversed::Next)
+        .transition(WizardReversed::Next);
+    assert_eq!(sm.state(), WizardReversed::Name);
+}
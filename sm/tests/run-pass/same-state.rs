@@ -0,0 +1,35 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked, Unlocked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked, Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let initial = Machine::new(Locked);
+    let turned = Machine::new(Unlocked).transition(TurnKey);
+    assert!(initial.same_state(&turned));
+    assert!(turned.same_state(&initial));
+    assert!(!initial.same_state(&Machine::new(Unlocked)));
+
+    let broken = Machine::new(Locked).transition(Break);
+    assert!(!broken.same_state(&initial));
+
+    let initial = initial.as_enum();
+    let turned = turned.as_enum();
+    assert!(initial.same_state(&turned));
+    assert!(!initial.same_state(&broken.as_enum()));
+}
@@ -0,0 +1,37 @@
+extern crate sm;
+use sm::simulate::Simulate;
+use sm::{sm, Table};
+
+sm!{
+    #[table]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked @ 5
+            Unlocked => Locked @ 0
+        }
+
+        Break {
+            Locked => Broken @ 0
+            Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    assert_eq!(Variant::weight(StateId::Locked, EventId::TurnKey), 5);
+    assert_eq!(Variant::weight(StateId::Unlocked, EventId::Break), 1);
+
+    let sm = Machine::new(Locked).as_enum();
+    let states: Vec<StateId> = sm.simulate_random(|| 42, 10).map(|sm| sm.state_id()).collect();
+    assert_eq!(states, vec![StateId::Unlocked, StateId::Broken]);
+
+    let sm = Machine::new(Locked).as_enum();
+    let mut simulation = sm.simulate_random(|| 42, 1);
+    assert!(simulation.next().is_some());
+    assert!(simulation.next().is_none());
+    assert_eq!(simulation.into_machine().state_id(), StateId::Unlocked);
+}
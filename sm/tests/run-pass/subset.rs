@@ -0,0 +1,32 @@
+extern crate sm;
+use sm::subset;
+
+subset! {
+    Workflow {
+        InitialStates { Draft, Published }
+
+        Submit {
+            Draft => Review
+        }
+
+        Approve {
+            Review => Published
+        }
+
+        View {
+            Draft, Review, Published => Published
+        }
+    }
+
+    ReadOnlyWorkflow {
+        States { Draft, Published }
+        Events { View }
+    }
+}
+
+fn main() {
+    use ReadOnlyWorkflow::*;
+
+    let sm = Machine::new(Draft).transition(View);
+    assert_eq!(sm.state(), Published);
+}
@@ -0,0 +1,34 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Workflow {
+        InitialStates { Draft }
+
+        Submit {
+            Draft => Review
+        }
+
+        Approve {
+            Review => Published
+        }
+    }
+}
+
+fn main() {
+    use Workflow::*;
+
+    let sm = Machine::new(Draft).then(Submit).then(Approve);
+    assert_eq!(sm.state(), Published);
+    assert_eq!(sm.trigger(), Some(Approve));
+
+    let sm = Machine::new(Draft).then(Submit);
+    let sm = match sm.then_if(Approve, |_| false) {
+        Ok(_) => panic!("guard rejected the transition"),
+        Err(Variant::ReviewBySubmit(sm)) => sm,
+        Err(_) => panic!("machine changed state"),
+    };
+
+    let sm = sm.then_if(Approve, |m| m.state() == Review).unwrap();
+    assert_eq!(sm.state(), Published);
+}
@@ -0,0 +1,31 @@
+extern crate sm;
+use sm::sm;
+use sm::TransitionError;
+
+sm!{
+    #[transition_checked]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Unlocked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    let err = sm.clone().transition_checked(EventId::Break).unwrap_err();
+    assert_eq!(err, TransitionError::Invalid { state: StateId::Locked, event: EventId::Break });
+
+    let sm = sm.transition_checked(EventId::TurnKey).unwrap();
+    assert_eq!(sm.state_id(), StateId::Unlocked);
+    assert_eq!(sm.trigger_id(), Some(EventId::TurnKey));
+}
@@ -0,0 +1,26 @@
+extern crate sm;
+use sm::sm;
+
+sm!{
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+fn main() {
+    use Lock::*;
+
+    let sm = Machine::new(Locked);
+    let sm = sm.transition_with(TurnKey, || false).unwrap_err();
+    assert_eq!(sm.state(), Locked);
+    assert_eq!(sm.trigger(), None);
+
+    let sm = sm.transition_with(TurnKey, || true).unwrap();
+    assert_eq!(sm.state(), Unlocked);
+    assert_eq!(sm.trigger(), Some(TurnKey));
+}
@@ -0,0 +1,73 @@
+extern crate sm;
+use sm::sm;
+use sm::{TransitionError, TryTransition};
+
+sm!{
+    #[apply]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+
+        Break {
+            Locked => Broken
+        }
+    }
+}
+
+fn main() {
+    use Lock::Variant::*;
+    use Lock::*;
+
+    let sm = Machine::new(Locked).as_enum();
+
+    let sm = match sm.try_transition(TurnKey) {
+        Ok(UnlockedByTurnKey(m)) => m.as_enum(),
+        _ => unreachable!(),
+    };
+
+    let sm = match sm.try_transition(EventId::Break) {
+        Err(UnlockedByTurnKey(m)) => m.as_enum(),
+        _ => unreachable!(),
+    };
+
+    let mut sm = match sm.try_transition(EventId::TurnKey) {
+        Ok(m @ LockedByTurnKey(_)) => m,
+        _ => unreachable!(),
+    };
+
+    let prepared = sm.prepare(EventId::TurnKey).unwrap();
+    match prepared.next() {
+        UnlockedByTurnKey(_) => (),
+        _ => unreachable!(),
+    }
+    drop(prepared);
+
+    match sm {
+        LockedByTurnKey(_) => (),
+        _ => unreachable!(),
+    }
+
+    assert!(sm.prepare(EventId::Break).is_some());
+    match sm.prepare(EventId::Break).unwrap().commit() {
+        BrokenByBreak(_) => (),
+        _ => unreachable!(),
+    }
+
+    assert!(sm.prepare(EventId::TurnKey).is_none());
+
+    assert_eq!(
+        sm.apply(EventId::TurnKey),
+        Err(TransitionError::Invalid {
+            state: StateId::Broken,
+            event: EventId::TurnKey,
+        })
+    );
+
+    let mut sm = Machine::new(Locked).as_enum();
+    assert_eq!(sm.apply(EventId::TurnKey), Ok(()));
+    assert_eq!(sm.state_id(), StateId::Unlocked);
+}
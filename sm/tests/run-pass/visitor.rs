@@ -0,0 +1,51 @@
+extern crate sm;
+use sm::sm;
+use sm::NoneEvent;
+
+sm!{
+    #[visitor]
+    Lock {
+        InitialStates { Locked }
+
+        TurnKey {
+            Locked => Unlocked
+            Unlocked => Locked
+        }
+    }
+}
+
+use Lock::*;
+
+struct Names(Vec<&'static str>);
+
+impl LockVisitor for Names {
+    fn visit_initial_locked(&mut self, m: &Machine<Locked, NoneEvent>) {
+        assert_eq!(m.trigger(), None);
+        self.0.push("initial");
+    }
+
+    fn visit_unlocked_by_turn_key(&mut self, m: &Machine<Unlocked, TurnKey>) {
+        assert_eq!(m.state(), Unlocked);
+        self.0.push("unlocked");
+    }
+
+    fn visit_locked_by_turn_key(&mut self, m: &Machine<Locked, TurnKey>) {
+        assert_eq!(m.state(), Locked);
+        self.0.push("locked");
+    }
+}
+
+fn main() {
+    let mut names = Names(Vec::new());
+
+    let sm = Machine::new(Locked);
+    sm.clone().as_enum().visit(&mut names);
+
+    let sm = sm.transition(TurnKey);
+    sm.clone().as_enum().visit(&mut names as &mut dyn LockVisitor);
+
+    let sm = sm.transition(TurnKey).as_enum();
+    sm.visit(&mut names);
+
+    assert_eq!(names.0, vec!["initial", "unlocked", "locked"]);
+}
@@ -1,20 +1,29 @@
-extern crate compiletest_rs as compiletest;
+extern crate trybuild;
 
-use std::path::PathBuf;
+#[test]
+fn compile_fail() {
+    trybuild::TestCases::new().compile_fail("tests/compile-fail/*.rs");
+}
 
-fn run_mode(mode: &str) {
-    let mut config = compiletest::Config::default();
+#[test]
+fn run_pass() {
+    trybuild::TestCases::new().pass("tests/run-pass/*.rs");
+}
 
-    config.mode = mode.parse().expect("Invalid mode");
-    config.src_base = PathBuf::from(format!("tests/{}", mode));
-    config.target_rustcflags = Some("-L ../target/debug -L ../target/debug/deps".to_string());
-    config.clean_rmeta();
+#[cfg(feature = "tracing")]
+#[test]
+fn run_pass_tracing() {
+    trybuild::TestCases::new().pass("tests/run-pass-tracing/*.rs");
+}
 
-    compiletest::run_tests(&config);
+#[cfg(feature = "python")]
+#[test]
+fn run_pass_python() {
+    trybuild::TestCases::new().pass("tests/run-pass-python/*.rs");
 }
 
+#[cfg(feature = "ffi")]
 #[test]
-fn compile_test() {
-    run_mode("compile-fail");
-    run_mode("run-pass");
+fn run_pass_ffi() {
+    trybuild::TestCases::new().pass("tests/run-pass-ffi/*.rs");
 }
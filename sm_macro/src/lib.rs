@@ -6,9 +6,8 @@
 
 #![no_std]
 // quote! macro needs a higher recursion limit
-#![recursion_limit = "512"]
-#![feature(alloc)]
-#![feature(proc_macro_diagnostic)]
+#![recursion_limit = "1024"]
+#![cfg_attr(feature = "unstable", feature(alloc))]
 #![forbid(
     future_incompatible,
     macro_use_extern_crate,
@@ -23,7 +22,6 @@
 )]
 #![warn(
     missing_docs,
-    non_snake_case,
     rust_2018_idioms,
     single_use_lifetimes,
     unused_import_braces,
@@ -32,16 +30,24 @@
     unused_results,
     unused,
 )]
-#![feature(tool_lints)]
+#![cfg_attr(feature = "unstable", feature(tool_lints))]
 #![deny(clippy::all)]
 
 extern crate alloc;
 extern crate proc_macro;
 extern crate proc_macro2;
 extern crate quote;
+extern crate std;
 extern crate syn;
 
-use crate::sm::machine::Machines;
+use crate::sm::derive::Derive;
+use crate::sm::dual::Dual;
+use crate::sm::extend::Extend;
+use crate::sm::include::Input;
+use crate::sm::methods::Methods;
+use crate::sm::product::Product;
+use crate::sm::reverse::Reverse;
+use crate::sm::subset::Subset;
 use proc_macro::TokenStream;
 use quote::quote;
 use syn::parse_macro_input;
@@ -53,7 +59,85 @@ mod sm;
 /// See the main crate documentation for more details.
 #[proc_macro]
 pub fn sm(input: TokenStream) -> TokenStream {
-    let machines: Machines = parse_macro_input!(input as Machines);
+    let input: Input = parse_macro_input!(input as Input);
 
-    quote!(#machines).into()
+    quote!(#input).into()
+}
+
+/// Generate the synchronous product of two state machines.
+///
+/// See the main crate documentation for more details.
+#[proc_macro]
+pub fn product(input: TokenStream) -> TokenStream {
+    let product: Product = parse_macro_input!(input as Product);
+
+    quote!(#product).into()
+}
+
+/// Generate a state machine, along with a companion machine whose transitions
+/// are reversed.
+///
+/// See the main crate documentation for more details.
+#[proc_macro]
+pub fn reverse(input: TokenStream) -> TokenStream {
+    let reverse: Reverse = parse_macro_input!(input as Reverse);
+
+    quote!(#reverse).into()
+}
+
+/// Generate a state machine taking part in a protocol, along with the machine
+/// of its peer, which receives the events it sends and sends the events it
+/// receives.
+///
+/// See the main crate documentation for more details.
+#[proc_macro]
+pub fn dual(input: TokenStream) -> TokenStream {
+    let dual: Dual = parse_macro_input!(input as Dual);
+
+    quote!(#dual).into()
+}
+
+/// Generate a state machine restricted to some of the states and events of
+/// another one.
+///
+/// See the main crate documentation for more details.
+#[proc_macro]
+pub fn subset(input: TokenStream) -> TokenStream {
+    let subset: Subset = parse_macro_input!(input as Subset);
+
+    quote!(#subset).into()
+}
+
+/// Generate a state machine extending the definition of another one with
+/// additional initial states and transitions.
+///
+/// See the main crate documentation for more details.
+#[proc_macro]
+pub fn extend(input: TokenStream) -> TokenStream {
+    let extend: Extend = parse_macro_input!(input as Extend);
+
+    quote!(#extend).into()
+}
+
+/// Generate a state machine from the states and transitions annotated on the
+/// variants of an enum.
+///
+/// See the main crate documentation for more details.
+#[proc_macro_derive(StateMachine, attributes(state, transition))]
+pub fn state_machine(input: TokenStream) -> TokenStream {
+    let derive: Derive = parse_macro_input!(input as Derive);
+
+    quote!(#derive).into()
+}
+
+/// Generate a state machine from the methods of an impl block annotated with
+/// `#[transition(From => To)]` attributes.
+///
+/// See the main crate documentation for more details.
+#[proc_macro_attribute]
+pub fn machine(args: TokenStream, item: TokenStream) -> TokenStream {
+    match Methods::parse(args.into(), item.into()) {
+        Ok(methods) => quote!(#methods).into(),
+        Err(err) => err.to_compile_error().into(),
+    }
 }
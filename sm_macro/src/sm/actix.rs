@@ -0,0 +1,144 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::sm::machine::Machine;
+
+/// Actix generates an [actix] actor wrapping the `Variant` of a machine, and
+/// turns every event of the machine into a message handled by that actor.
+///
+/// [actix]: https://docs.rs/actix
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Actix<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Actix<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(quote! {
+            #[derive(Debug)]
+            pub struct Actor {
+                machine: Variant,
+            }
+
+            impl Actor {
+                pub fn new(machine: Variant) -> Self {
+                    Actor { machine }
+                }
+
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+
+            impl ::sm::export::actix::Actor for Actor {
+                type Context = ::sm::export::actix::Context<Self>;
+            }
+        });
+
+        for event in &self.machine.events().0 {
+            let event = &event.name;
+
+            tokens.extend(quote! {
+                impl ::sm::export::actix::Message for #event {
+                    type Result = Result<StateId, StateId>;
+                }
+
+                impl ::sm::export::actix::Handler<#event> for Actor {
+                    type Result = Result<StateId, StateId>;
+
+                    fn handle(&mut self, event: #event, _: &mut Self::Context) -> Self::Result {
+                        match self.machine.clone().try_transition(event) {
+                            Result::Ok(machine) => {
+                                self.machine = machine;
+                                Result::Ok(self.machine.state_id())
+                            }
+                            Result::Err(machine) => Result::Err(machine.state_id()),
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_actix_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            #[derive(Debug)]
+            pub struct Actor {
+                machine: Variant,
+            }
+
+            impl Actor {
+                pub fn new(machine: Variant) -> Self {
+                    Actor { machine }
+                }
+
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+
+            impl ::sm::export::actix::Actor for Actor {
+                type Context = ::sm::export::actix::Context<Self>;
+            }
+
+            impl ::sm::export::actix::Message for TurnKey {
+                type Result = Result<StateId, StateId>;
+            }
+
+            impl ::sm::export::actix::Handler<TurnKey> for Actor {
+                type Result = Result<StateId, StateId>;
+
+                fn handle(&mut self, event: TurnKey, _: &mut Self::Context) -> Self::Result {
+                    match self.machine.clone().try_transition(event) {
+                        Result::Ok(machine) => {
+                            self.machine = machine;
+                            Result::Ok(self.machine.state_id())
+                        }
+                        Result::Err(machine) => Result::Err(machine.state_id()),
+                    }
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Actix { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
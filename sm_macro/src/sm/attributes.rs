@@ -0,0 +1,250 @@
+use alloc::format;
+use syn::parse::{ParseStream, Result};
+use syn::{Attribute, Error, Lit, Meta, NestedMeta};
+
+/// Attributes are the options of a machine, set by outer attributes in front
+/// of its name:
+///
+/// - `#[apply]` generates the `apply` and `apply_named` methods of the
+///   `Variant` enum, which transition it in place.
+/// - `#[channel]` generates a `Channel` type, wrapping a transport along with
+///   a state of the machine, with methods to send the events starting with
+///   `Send` valid in that state, and to receive those starting with `Receive`.
+/// - `#[dynamic]` generates a single, non-generic machine type driven by the
+///   transition table, instead of a type per state and trigger. Transitions
+///   are checked at runtime rather than by the compiler, in exchange for much
+///   less generated code.
+/// - `#[drive]` generates the `drive` method of the `Variant` enum, iterating
+///   over the machines an iterator of events leads to.
+/// - `#[erase_trigger]` stores the trigger of a machine as an `EventId`, so
+///   there is a machine type per state rather than per state and trigger.
+/// - `#[zero_sized]` stores the trigger of a machine as is, rather than in an
+///   `Option`, so machines are zero-sized like their states and events.
+/// - `#[fold_events]` generates the `fold_events` method of the `Variant`
+///   enum, applying events until one is rejected.
+/// - `#[footprint]` generates constants holding the size of the machine, and
+///   its number of states and events.
+/// - `#[handler]` generates a trait named after the machine, with a method
+///   per transition, and an `apply_with` method applying events through it.
+/// - `#[match_macro]` generates a declarative macro named after the machine,
+///   e.g. `lock_match!` for `Lock`, matching on the `Variant` enum by state.
+/// - `#[no_enum]` leaves out the `Variant` enum, its `AsEnum` implementations
+///   and everything built on top of it, for machines only ever used through
+///   their typed API.
+/// - `#[no_cross_eq]` only implements `PartialEq` between a state or event and
+///   itself, instead of between every pair of states and every pair of events.
+/// - `#[replay]` generates the `replay` function of the `Variant` enum,
+///   rebuilding a machine from its initial state and the events it received.
+/// - `#[python]` generates a PyO3 `PyMachine` class wrapping the machine.
+///   Requires the `python` feature.
+/// - `#[pushdown(n)]` generates a `Pushdown` wrapper around the machine,
+///   which pushes up to `n` machines on a stack as events are pushed, and
+///   returns to them as they are popped.
+/// - `#[table]` implements the `Table` trait for the `Variant` enum, which the
+///   runtime modules of the crate are built on.
+/// - `#[transition_checked]` generates the `transition_checked` method of
+///   every machine type, returning the rejected event as a `TransitionError`.
+/// - `#[undo(n)]` generates a `History` wrapper around the machine, which
+///   remembers up to `n` previous states, and can transition back to them.
+/// - `#[validate_sequence]` generates the `validate_sequence` function, which
+///   follows a sequence of events through the transition table.
+/// - `#[visitor]` generates a visitor trait named after the machine, with a
+///   method per variant of the `Variant` enum, and its `visit` method.
+///
+/// Machines marked `#[dynamic]` always implement `Table`, and have `apply` and
+/// `apply_named` methods, as they are driven through them.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Attributes {
+    pub apply: bool,
+    pub channel: bool,
+    pub drive: bool,
+    pub dynamic: bool,
+    pub erase_trigger: bool,
+    pub fold_events: bool,
+    pub footprint: bool,
+    pub handler: bool,
+    pub match_macro: bool,
+    pub no_cross_eq: bool,
+    pub no_enum: bool,
+    pub pushdown: Option<usize>,
+    pub python: bool,
+    pub replay: bool,
+    pub table: bool,
+    pub transition_checked: bool,
+    pub undo: Option<usize>,
+    pub validate_sequence: bool,
+    pub visitor: bool,
+    pub zero_sized: bool,
+}
+
+impl Attributes {
+    /// parse reads the outer attributes of a machine.
+    ///
+    /// ```text
+    /// #[apply]
+    /// #[channel]
+    /// #[drive]
+    /// #[dynamic]
+    /// #[erase_trigger]
+    /// #[fold_events]
+    /// #[footprint]
+    /// #[handler]
+    /// #[match_macro]
+    /// #[no_cross_eq]
+    /// #[no_enum]
+    /// #[pushdown(8)]
+    /// #[python]
+    /// #[replay]
+    /// #[table]
+    /// #[transition_checked]
+    /// #[undo(8)]
+    /// #[validate_sequence]
+    /// #[visitor]
+    /// #[zero_sized]
+    /// ```
+    ///
+    pub(crate) fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut attributes = Attributes::default();
+
+        for attr in input.call(Attribute::parse_outer)? {
+            if attr.path.segments.len() != 1 {
+                return Err(Error::new_spanned(&attr, "unknown machine attribute"));
+            }
+
+            let flag = &attr.path.segments[0].ident;
+
+            if flag == "pushdown" {
+                attributes.pushdown = Some(parse_size(&attr, "pushdown")?);
+                continue;
+            }
+
+            if flag == "undo" {
+                attributes.undo = Some(parse_size(&attr, "undo")?);
+                continue;
+            }
+
+            if !attr.tts.is_empty() {
+                return Err(Error::new_spanned(&attr, "unknown machine attribute"));
+            }
+
+            if flag == "apply" {
+                attributes.apply = true;
+            } else if flag == "channel" {
+                attributes.channel = true;
+            } else if flag == "drive" {
+                attributes.drive = true;
+            } else if flag == "dynamic" {
+                attributes.dynamic = true;
+            } else if flag == "erase_trigger" {
+                attributes.erase_trigger = true;
+            } else if flag == "fold_events" {
+                attributes.fold_events = true;
+            } else if flag == "footprint" {
+                attributes.footprint = true;
+            } else if flag == "handler" {
+                attributes.handler = true;
+            } else if flag == "match_macro" {
+                attributes.match_macro = true;
+            } else if flag == "no_cross_eq" {
+                attributes.no_cross_eq = true;
+            } else if flag == "no_enum" {
+                attributes.no_enum = true;
+            } else if flag == "python" {
+                if !cfg!(feature = "python") {
+                    return Err(Error::new(flag.span(), "`#[python]` requires the `python` feature"));
+                }
+
+                attributes.python = true;
+            } else if flag == "replay" {
+                attributes.replay = true;
+            } else if flag == "table" {
+                attributes.table = true;
+            } else if flag == "transition_checked" {
+                attributes.transition_checked = true;
+            } else if flag == "validate_sequence" {
+                attributes.validate_sequence = true;
+            } else if flag == "visitor" {
+                attributes.visitor = true;
+            } else if flag == "zero_sized" {
+                attributes.zero_sized = true;
+            } else {
+                return Err(Error::new(flag.span(), "unknown machine attribute"));
+            }
+        }
+
+        if attributes.erase_trigger && attributes.zero_sized {
+            return Err(input.error("`#[erase_trigger]` and `#[zero_sized]` can't be combined"));
+        }
+
+        if attributes.channel && attributes.dynamic {
+            return Err(input.error("`#[channel]` and `#[dynamic]` can't be combined"));
+        }
+
+        if attributes.undo.is_some() && attributes.no_enum {
+            return Err(input.error("`#[undo]` and `#[no_enum]` can't be combined"));
+        }
+
+        if attributes.pushdown.is_some() && attributes.no_enum {
+            return Err(input.error("`#[pushdown]` and `#[no_enum]` can't be combined"));
+        }
+
+        if attributes.python && (attributes.dynamic || attributes.no_enum) {
+            return Err(input.error("`#[python]` can't be combined with `#[dynamic]` or `#[no_enum]`"));
+        }
+
+        // These are generated for the `Variant` enum only.
+        let variant = [
+            ("drive", attributes.drive),
+            ("fold_events", attributes.fold_events),
+            ("match_macro", attributes.match_macro),
+            ("replay", attributes.replay),
+            ("transition_checked", attributes.transition_checked),
+            ("visitor", attributes.visitor),
+        ];
+
+        for &(name, set) in &variant {
+            if set && (attributes.dynamic || attributes.no_enum) {
+                return Err(input.error(format!(
+                    "`#[{}]` can't be combined with `#[dynamic]` or `#[no_enum]`",
+                    name
+                )));
+            }
+        }
+
+        // These are generated for the `Variant` enum, or for the `Machine`
+        // type of a dynamic machine.
+        let machine = [
+            ("apply", attributes.apply),
+            ("footprint", attributes.footprint),
+            ("handler", attributes.handler),
+            ("table", attributes.table),
+        ];
+
+        for &(name, set) in &machine {
+            if set && attributes.no_enum {
+                return Err(input.error(format!("`#[{}]` and `#[no_enum]` can't be combined", name)));
+            }
+        }
+
+        Ok(attributes)
+    }
+}
+
+/// parse_size reads the number of states remembered by a machine marked
+/// `#[undo(n)]` or `#[pushdown(n)]`, which has to be at least one.
+fn parse_size(attr: &Attribute, name: &str) -> Result<usize> {
+    if let Ok(Meta::List(list)) = attr.parse_meta() {
+        if list.nested.len() == 1 {
+            if let NestedMeta::Literal(Lit::Int(ref size)) = list.nested[0] {
+                if size.value() > 0 {
+                    return Ok(size.value() as usize);
+                }
+            }
+        }
+    }
+
+    Err(Error::new_spanned(
+        attr,
+        format!("expected `#[{}(n)]`, with `n` at least 1", name),
+    ))
+}
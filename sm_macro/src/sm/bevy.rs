@@ -0,0 +1,118 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+/// Bevy generates a [Bevy] plugin for a machine: the `Variant` of the machine
+/// is stored in a `Component`, and `Trigger` events sent to an entity are
+/// applied to its machine by the `apply_triggers` system, which the `Plugin`
+/// adds to the `Update` schedule. `Component` and `Trigger` implement the
+/// traits of `bevy_ecs` by hand, as its derives look the crate up in the
+/// manifest of the crate using the macro.
+///
+/// [Bevy]: https://docs.rs/bevy
+#[derive(Debug)]
+pub(crate) struct Bevy;
+
+impl ToTokens for Bevy {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(quote! {
+            #[derive(Clone, Debug)]
+            pub struct Component(pub Variant);
+
+            impl ::sm::export::bevy_ecs::component::Component for Component {
+                const STORAGE_TYPE: ::sm::export::bevy_ecs::component::StorageType =
+                    ::sm::export::bevy_ecs::component::StorageType::Table;
+                type Mutability = ::sm::export::bevy_ecs::component::Mutable;
+            }
+
+            #[derive(Clone, Copy, Debug)]
+            pub struct Trigger {
+                pub entity: ::sm::export::bevy_ecs::entity::Entity,
+                pub event: EventId,
+            }
+
+            impl ::sm::export::bevy_ecs::event::Event for Trigger {
+                type Traversal = ();
+            }
+
+            pub fn apply_triggers(
+                mut triggers: ::sm::export::bevy_ecs::event::EventReader<Trigger>,
+                mut machines: ::sm::export::bevy_ecs::system::Query<&mut Component>,
+            ) {
+                for trigger in triggers.read() {
+                    if let Result::Ok(mut machine) = machines.get_mut(trigger.entity) {
+                        let _ = machine.0.apply(trigger.event);
+                    }
+                }
+            }
+
+            #[derive(Debug)]
+            pub struct Plugin;
+
+            impl ::sm::export::bevy_app::Plugin for Plugin {
+                fn build(&self, app: &mut ::sm::export::bevy_app::App) {
+                    let _ = app
+                        .add_event::<Trigger>()
+                        .add_systems(::sm::export::bevy_app::Update, apply_triggers);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use proc_macro2::TokenStream;
+
+    #[test]
+    fn test_bevy_to_tokens() {
+        let left = quote! {
+            #[derive(Clone, Debug)]
+            pub struct Component(pub Variant);
+
+            impl ::sm::export::bevy_ecs::component::Component for Component {
+                const STORAGE_TYPE: ::sm::export::bevy_ecs::component::StorageType =
+                    ::sm::export::bevy_ecs::component::StorageType::Table;
+                type Mutability = ::sm::export::bevy_ecs::component::Mutable;
+            }
+
+            #[derive(Clone, Copy, Debug)]
+            pub struct Trigger {
+                pub entity: ::sm::export::bevy_ecs::entity::Entity,
+                pub event: EventId,
+            }
+
+            impl ::sm::export::bevy_ecs::event::Event for Trigger {
+                type Traversal = ();
+            }
+
+            pub fn apply_triggers(
+                mut triggers: ::sm::export::bevy_ecs::event::EventReader<Trigger>,
+                mut machines: ::sm::export::bevy_ecs::system::Query<&mut Component>,
+            ) {
+                for trigger in triggers.read() {
+                    if let Result::Ok(mut machine) = machines.get_mut(trigger.entity) {
+                        let _ = machine.0.apply(trigger.event);
+                    }
+                }
+            }
+
+            #[derive(Debug)]
+            pub struct Plugin;
+
+            impl ::sm::export::bevy_app::Plugin for Plugin {
+                fn build(&self, app: &mut ::sm::export::bevy_app::App) {
+                    let _ = app
+                        .add_event::<Trigger>()
+                        .add_systems(::sm::export::bevy_app::Update, apply_triggers);
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Bevy.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,209 @@
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::ffi::snake_case;
+use crate::sm::machine::Machine;
+
+/// Channel generates the `Channel` type of a machine marked `#[channel]`: a
+/// transport along with a state of the machine. Events starting with `Send`
+/// become methods of the channels in the states they transition from, named
+/// after the event, sending it and returning the channel in the state they
+/// transition to. States with transitions on events starting with `Receive`
+/// get an enum named after the state, such as `WaitingReceived`, with a
+/// variant per event, and channels in those states get a `receive` method
+/// returning it.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Channel<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Channel<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        if !self.machine.attributes.channel {
+            return;
+        }
+
+        tokens.extend(quote! {
+            #[derive(Debug)]
+            pub struct Channel<S: State, T> {
+                state: S,
+                transport: T,
+            }
+
+            impl<S: State, T> Channel<S, T> {
+                #[inline]
+                pub fn new(state: S, transport: T) -> Self
+                where
+                    S: InitialState,
+                {
+                    Channel { state, transport }
+                }
+
+                #[inline]
+                pub fn state(&self) -> S {
+                    self.state.clone()
+                }
+
+                #[inline]
+                pub fn into_transport(self) -> T {
+                    self.transport
+                }
+            }
+        });
+
+        let events = self.machine.events().0.len();
+
+        for state in self.machine.states().0 {
+            let from = &state.name;
+            let received_enum = Ident::new(&format!("{}Received", from), Span::call_site());
+            let mut received = Vec::new();
+            let mut arms = Vec::new();
+
+            for t in &self.machine.transitions.0 {
+                if t.from.name != *from {
+                    continue;
+                }
+
+                let event = &t.event.name;
+                let to = &t.to.name;
+                let name = event.to_string();
+
+                if name.starts_with("Send") {
+                    let method = Ident::new(&snake_case(&name), Span::call_site());
+
+                    tokens.extend(quote! {
+                        impl<T: ::sm::channel::Transport<EventId>> Channel<#from, T> {
+                            pub fn #method(mut self) -> Result<Channel<#to, T>, (Self, ::sm::channel::ChannelError<EventId, T::Error>)> {
+                                match self.transport.send(EventId::#event) {
+                                    Result::Ok(()) => Result::Ok(Channel { state: #to, transport: self.transport }),
+                                    Result::Err(err) => Result::Err((self, ::sm::channel::ChannelError::Transport(err))),
+                                }
+                            }
+                        }
+                    });
+                } else if name.starts_with("Receive") {
+                    received.push(quote! { #event(Channel<#to, T>), });
+                    arms.push(quote! {
+                        Result::Ok(EventId::#event) => {
+                            Result::Ok(#received_enum::#event(Channel { state: #to, transport: self.transport }))
+                        }
+                    });
+                }
+            }
+
+            if received.is_empty() {
+                continue;
+            }
+
+            let fallback = if received.len() < events {
+                quote! {
+                    Result::Ok(event) => Result::Err((self, ::sm::channel::ChannelError::Unexpected(event))),
+                }
+            } else {
+                TokenStream::new()
+            };
+
+            tokens.extend(quote! {
+                #[derive(Debug)]
+                pub enum #received_enum<T> {
+                    #(#received)*
+                }
+
+                impl<T: ::sm::channel::Transport<EventId>> Channel<#from, T> {
+                    pub fn receive(mut self) -> Result<#received_enum<T>, (Self, ::sm::channel::ChannelError<EventId, T::Error>)> {
+                        match self.transport.receive() {
+                            #(#arms)*
+                            #fallback
+                            Result::Err(err) => Result::Err((self, ::sm::channel::ChannelError::Transport(err))),
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_channel_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[channel]
+            Client {
+                InitialStates { Idle }
+
+                SendRequest { Idle => Waiting }
+                ReceiveResponse { Waiting => Idle }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[derive(Debug)]
+            pub struct Channel<S: State, T> {
+                state: S,
+                transport: T,
+            }
+
+            impl<S: State, T> Channel<S, T> {
+                #[inline]
+                pub fn new(state: S, transport: T) -> Self
+                where
+                    S: InitialState,
+                {
+                    Channel { state, transport }
+                }
+
+                #[inline]
+                pub fn state(&self) -> S {
+                    self.state.clone()
+                }
+
+                #[inline]
+                pub fn into_transport(self) -> T {
+                    self.transport
+                }
+            }
+
+            impl<T: ::sm::channel::Transport<EventId>> Channel<Idle, T> {
+                pub fn send_request(mut self) -> Result<Channel<Waiting, T>, (Self, ::sm::channel::ChannelError<EventId, T::Error>)> {
+                    match self.transport.send(EventId::SendRequest) {
+                        Result::Ok(()) => Result::Ok(Channel { state: Waiting, transport: self.transport }),
+                        Result::Err(err) => Result::Err((self, ::sm::channel::ChannelError::Transport(err))),
+                    }
+                }
+            }
+
+            #[derive(Debug)]
+            pub enum WaitingReceived<T> {
+                ReceiveResponse(Channel<Idle, T>),
+            }
+
+            impl<T: ::sm::channel::Transport<EventId>> Channel<Waiting, T> {
+                pub fn receive(mut self) -> Result<WaitingReceived<T>, (Self, ::sm::channel::ChannelError<EventId, T::Error>)> {
+                    match self.transport.receive() {
+                        Result::Ok(EventId::ReceiveResponse) => {
+                            Result::Ok(WaitingReceived::ReceiveResponse(Channel { state: Idle, transport: self.transport }))
+                        }
+                        Result::Ok(event) => Result::Err((self, ::sm::channel::ChannelError::Unexpected(event))),
+                        Result::Err(err) => Result::Err((self, ::sm::channel::ChannelError::Transport(err))),
+                    }
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Channel { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
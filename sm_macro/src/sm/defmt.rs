@@ -0,0 +1,156 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// Defmt generates [defmt] `Format` implementations for the states, events,
+/// `StateId`, `EventId` and `Variant` types of a machine, so they can be
+/// logged using interned strings.
+///
+/// [defmt]: https://docs.rs/defmt
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Defmt<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Defmt<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let states: Vec<Ident> = self.machine.states().0.into_iter().map(|s| s.name).collect();
+        let events: Vec<Ident> = self.machine.events().0.into_iter().map(|e| e.name).collect();
+
+        // The `write!` macro of defmt refers to the crate by its bare name.
+        tokens.extend(quote! {
+            use ::sm::export::defmt;
+        });
+
+        for (id, names) in &[("StateId", &states), ("EventId", &events)] {
+            let id = Ident::new(id, Span::call_site());
+            let mut arms = Vec::new();
+
+            for name in names.iter() {
+                let string: String = name.to_string();
+
+                tokens.extend(quote! {
+                    impl defmt::Format for #name {
+                        fn format(&self, f: defmt::Formatter<'_>) {
+                            defmt::write!(f, #string)
+                        }
+                    }
+                });
+
+                arms.push(quote! {
+                    #id::#name => defmt::write!(f, #string),
+                });
+            }
+
+            tokens.extend(quote! {
+                impl defmt::Format for #id {
+                    fn format(&self, f: defmt::Formatter<'_>) {
+                        match *self {
+                            #(#arms)*
+                        }
+                    }
+                }
+            });
+        }
+
+        tokens.extend(quote! {
+            impl defmt::Format for Variant {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "{}({})", self.state_id(), self.trigger_id())
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_defmt_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            use ::sm::export::defmt;
+
+            impl defmt::Format for Locked {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "Locked")
+                }
+            }
+
+            impl defmt::Format for Unlocked {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "Unlocked")
+                }
+            }
+
+            impl defmt::Format for StateId {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    match *self {
+                        StateId::Locked => defmt::write!(f, "Locked"),
+                        StateId::Unlocked => defmt::write!(f, "Unlocked"),
+                    }
+                }
+            }
+
+            impl defmt::Format for TurnKey {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "TurnKey")
+                }
+            }
+
+            impl defmt::Format for EventId {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    match *self {
+                        EventId::TurnKey => defmt::write!(f, "TurnKey"),
+                    }
+                }
+            }
+
+            impl defmt::Format for Variant {
+                fn format(&self, f: defmt::Formatter<'_>) {
+                    defmt::write!(f, "{}({})", self.state_id(), self.trigger_id())
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Defmt { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
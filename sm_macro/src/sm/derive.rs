@@ -0,0 +1,277 @@
+use alloc::format;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{parenthesized, Attribute, Data, DeriveInput, Error, Fields, Ident, Token};
+
+use crate::sm::event::Event;
+use crate::sm::initial_state::{InitialState, InitialStates};
+use crate::sm::machine::Machine;
+use crate::sm::state::State;
+use crate::sm::transition::{Transition, Transitions};
+
+/// Derive is the input of `#[derive(StateMachine)]`: an enum whose unit
+/// variants are the states of a machine, annotated with `#[state(initial)]`
+/// and `#[transition(on = Event, to = State)]` attributes. The machine is
+/// generated in a module named after the enum, suffixed with `Machine`.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Derive {
+    pub name: Ident,
+    pub machine: Machine,
+}
+
+impl Parse for Derive {
+    /// example enum tokens:
+    ///
+    /// ```text
+    /// enum TurnStile {
+    ///     #[state(initial)]
+    ///     #[transition(on = Coin, to = Unlocked)]
+    ///     Locked,
+    ///
+    ///     #[state]
+    ///     #[transition(on = Push, to = Locked)]
+    ///     Unlocked,
+    /// }
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let input: DeriveInput = input.parse()?;
+
+        let variants = match input.data {
+            Data::Enum(data) => data.variants,
+            _ => {
+                return Err(Error::new(
+                    input.ident.span(),
+                    "`StateMachine` can only be derived for enums",
+                ))
+            }
+        };
+
+        let mut initial_states = Vec::new();
+        let mut transitions = Vec::new();
+
+        for variant in variants {
+            match variant.fields {
+                Fields::Unit => {}
+                _ => {
+                    return Err(Error::new(
+                        variant.ident.span(),
+                        "states have to be unit variants",
+                    ))
+                }
+            }
+
+            for attr in &variant.attrs {
+                if is(attr, "state") {
+                    let state: StateAttr = syn::parse2(attr.tts.clone())?;
+                    if state.initial {
+                        initial_states.push(InitialState {
+                            name: variant.ident.clone(),
+                        });
+                    }
+                } else if is(attr, "transition") {
+                    let transition: TransitionAttr = syn::parse2(attr.tts.clone())?;
+                    transitions.push(Transition {
+                        event: Event {
+                            name: transition.on,
+                        },
+                        from: State {
+                            name: variant.ident.clone(),
+                        },
+                        to: State {
+                            name: transition.to,
+                        },
+                        weight: None,
+                    });
+                }
+            }
+        }
+
+        if initial_states.is_empty() {
+            return Err(Error::new(
+                input.ident.span(),
+                "at least one variant has to be marked `#[state(initial)]`",
+            ));
+        }
+
+        Ok(Derive {
+            machine: Machine {
+                name: Ident::new(&format!("{}Machine", input.ident), Span::call_site()),
+                attributes: Default::default(),
+                externals: Default::default(),
+                initial_states: InitialStates(initial_states),
+                transitions: Transitions(transitions),
+            },
+            name: input.ident,
+        })
+    }
+}
+
+impl ToTokens for Derive {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.name;
+        let machine = &self.machine.name;
+        let mut arms = Vec::new();
+
+        for state in &self.machine.states() {
+            let state = &state.name;
+
+            arms.push(quote! {
+                #machine::StateId::#state => #name::#state,
+            });
+        }
+
+        self.machine.to_tokens(tokens);
+
+        tokens.extend(quote! {
+            impl From<#machine::StateId> for #name {
+                fn from(state: #machine::StateId) -> Self {
+                    match state {
+                        #(#arms)*
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn is(attr: &Attribute, name: &str) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == name
+}
+
+/// StateAttr is the content of a `#[state]` or `#[state(initial)]` attribute.
+struct StateAttr {
+    initial: bool,
+}
+
+impl Parse for StateAttr {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(StateAttr { initial: false });
+        }
+
+        let content;
+        parenthesized!(content in input);
+
+        let flag: Ident = content.parse()?;
+        if flag != "initial" {
+            return Err(Error::new(flag.span(), "expected `initial`"));
+        }
+
+        Ok(StateAttr { initial: true })
+    }
+}
+
+/// TransitionAttr is the content of a `#[transition(on = Event, to = State)]`
+/// attribute.
+struct TransitionAttr {
+    on: Ident,
+    to: Ident,
+}
+
+impl Parse for TransitionAttr {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let content;
+        parenthesized!(content in input);
+
+        let mut on = None;
+        let mut to = None;
+
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            let _: Token![=] = content.parse()?;
+            let value: Ident = content.parse()?;
+
+            if key == "on" {
+                on = Some(value);
+            } else if key == "to" {
+                to = Some(value);
+            } else {
+                return Err(Error::new(key.span(), "expected `on` or `to`"));
+            }
+
+            if !content.is_empty() {
+                let _: Token![,] = content.parse()?;
+            }
+        }
+
+        match (on, to) {
+            (Some(on), Some(to)) => Ok(TransitionAttr { on, to }),
+            _ => Err(content.error("expected `on = Event, to = State`")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_derive_parse() {
+        let left: Derive = syn::parse2(quote! {
+            enum TurnStile {
+                #[state(initial)]
+                #[transition(on = Coin, to = Unlocked)]
+                Locked,
+
+                #[state]
+                #[transition(on = Push, to = Locked)]
+                Unlocked,
+            }
+        })
+        .unwrap();
+
+        let right = Derive {
+            name: parse_quote! { TurnStile },
+            machine: Machine {
+                name: parse_quote! { TurnStileMachine },
+                attributes: Default::default(),
+                externals: Default::default(),
+                initial_states: InitialStates(vec![InitialState {
+                    name: parse_quote! { Locked },
+                }]),
+                transitions: Transitions(vec![
+                    Transition {
+                        event: Event {
+                            name: parse_quote! { Coin },
+                        },
+                        from: State {
+                            name: parse_quote! { Locked },
+                        },
+                        to: State {
+                            name: parse_quote! { Unlocked },
+                        },
+                        weight: None,
+                    },
+                    Transition {
+                        event: Event {
+                            name: parse_quote! { Push },
+                        },
+                        from: State {
+                            name: parse_quote! { Unlocked },
+                        },
+                        to: State {
+                            name: parse_quote! { Locked },
+                        },
+                        weight: None,
+                    },
+                ]),
+            },
+        };
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_derive_parse_struct() {
+        let derive: Result<Derive> = syn::parse2(quote! {
+            struct TurnStile;
+        });
+
+        assert!(derive.is_err());
+    }
+}
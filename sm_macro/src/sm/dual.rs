@@ -0,0 +1,178 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, Ident};
+
+use crate::sm::event::Event;
+use crate::sm::machine::{Machine, Machines};
+use crate::sm::transition::{Transition, Transitions};
+
+/// Dual is the input of the `dual!` macro: the definition of a machine taking
+/// part in a protocol, followed by the name of its peer. Every event of the
+/// machine is either sent or received, its name starting with `Send` or
+/// `Receive`. The peer has the same states and transitions, with every sent
+/// event received and every received event sent, so `SendRequest` becomes
+/// `ReceiveRequest`, and both machines are generated.
+///
+/// The `EventId` enums of both machines convert into each other, an event sent
+/// by one machine being received by the other.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Dual {
+    pub machine: Machine,
+    pub dual: Machine,
+}
+
+impl Parse for Dual {
+    /// example dual tokens:
+    ///
+    /// ```text
+    /// Client {
+    ///     InitialStates { ... }
+    ///
+    ///     SendRequest { ... }
+    ///     ReceiveResponse { ... }
+    /// }
+    ///
+    /// Server
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `Client { ... }`
+        //  ^^^^^^^^^^^^^^
+        let machine = Machine::parse(input)?;
+
+        // `Server`
+        //  ^^^^^^
+        let name: Ident = input.parse()?;
+
+        let mut transitions: Vec<Transition> = Vec::new();
+        for transition in &machine.transitions.0 {
+            transitions.push(Transition {
+                event: Event {
+                    name: mirror(&transition.event.name)?,
+                },
+                from: transition.from.clone(),
+                to: transition.to.clone(),
+                weight: transition.weight,
+            });
+        }
+
+        let dual = Machine {
+            name,
+            attributes: machine.attributes.clone(),
+            externals: Default::default(),
+            initial_states: machine.initial_states.clone(),
+            transitions: Transitions(transitions),
+        };
+
+        Ok(Dual { machine, dual })
+    }
+}
+
+/// mirror returns the name of the event matching the provided one on the other
+/// side of the protocol.
+fn mirror(event: &Ident) -> Result<Ident> {
+    let name = event.to_string();
+
+    let mirrored: Option<String> = if name.starts_with("Send") && name.len() > "Send".len() {
+        Some(format!("Receive{}", &name["Send".len()..]))
+    } else if name.starts_with("Receive") && name.len() > "Receive".len() {
+        Some(format!("Send{}", &name["Receive".len()..]))
+    } else {
+        None
+    };
+
+    match mirrored {
+        Some(mirrored) => Ok(Ident::new(&mirrored, event.span())),
+        None => Err(Error::new(
+            event.span(),
+            format!("`{}` has to start with `Send` or `Receive`", name),
+        )),
+    }
+}
+
+impl ToTokens for Dual {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Machines(vec![self.machine.clone(), self.dual.clone()]).to_tokens(tokens);
+
+        let events = self.machine.events().0;
+        let duals = self.dual.events().0;
+
+        for (from, into, from_events, into_events) in &[
+            (&self.machine.name, &self.dual.name, &events, &duals),
+            (&self.dual.name, &self.machine.name, &duals, &events),
+        ] {
+            let mut arms = Vec::new();
+
+            for (from_event, into_event) in from_events.iter().zip(into_events.iter()) {
+                let from_event = &from_event.name;
+                let into_event = &into_event.name;
+
+                arms.push(quote! {
+                    #from::EventId::#from_event => #into::EventId::#into_event,
+                });
+            }
+
+            tokens.extend(quote! {
+                impl From<#from::EventId> for #into::EventId {
+                    fn from(event: #from::EventId) -> Self {
+                        match event {
+                            #(#arms)*
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dual_parse() {
+        let left: Dual = syn::parse2(quote! {
+            Client {
+                InitialStates { Idle }
+
+                SendRequest { Idle => Waiting }
+                ReceiveResponse { Waiting => Idle }
+            }
+
+            Server
+        })
+        .unwrap();
+
+        let right: Machine = syn::parse2(quote! {
+            Server {
+                InitialStates { Idle }
+
+                ReceiveRequest { Idle => Waiting }
+                SendResponse { Waiting => Idle }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(left.dual, right);
+    }
+
+    #[test]
+    fn test_dual_parse_undirected() {
+        let dual: Result<Dual> = syn::parse2(quote! {
+            Client {
+                InitialStates { Idle }
+
+                Timeout { Waiting => Idle }
+            }
+
+            Server
+        });
+
+        assert!(dual.is_err());
+    }
+}
@@ -0,0 +1,356 @@
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse_quote;
+
+use crate::sm::footprint::Footprint;
+use crate::sm::handler::Handler;
+use crate::sm::history::History;
+use crate::sm::instrument::{self, Instrument};
+use crate::sm::machine::{Machine, MachineTable, NextState, ValidateSequence};
+use crate::sm::no_panic::NoPanic;
+use crate::sm::pushdown::Pushdown;
+
+/// Dynamic generates the module of a machine marked `#[dynamic]`: the
+/// `StateId` and `EventId` enums, and a single `Machine` type holding the
+/// current state and trigger as ids, transitioned through the transition
+/// table. No types are generated for the states and events themselves, and
+/// there is no `Variant` enum, so the amount of generated code grows with the
+/// number of transitions only, at the cost of checking transitions at runtime.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Dynamic<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Dynamic<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.machine.name;
+
+        let mut ids = TokenStream::new();
+        self.machine.states().id_tokens(&mut ids);
+        self.machine.events().id_tokens(&mut ids);
+
+        let table = MachineTable {
+            machine: self.machine,
+            ty: parse_quote! { Machine },
+        };
+
+        let mut initial = Vec::new();
+        for state in &self.machine.initial_states.0 {
+            let state = &state.name;
+            initial.push(quote! { StateId::#state });
+        }
+
+        let fallback = if initial.len() < self.machine.states().0.len() {
+            quote! { _ => Option::None, }
+        } else {
+            TokenStream::new()
+        };
+
+        let history = History {
+            machine: self.machine,
+            ty: parse_quote! { Machine },
+        };
+
+        let pushdown = Pushdown {
+            machine: self.machine,
+            ty: parse_quote! { Machine },
+        };
+
+        let attributes = &self.machine.attributes;
+
+        let handler = if attributes.handler {
+            Handler {
+                machine: self.machine,
+                ty: parse_quote! { Machine },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let footprint = if attributes.footprint {
+            Footprint {
+                machine: self.machine,
+                ty: parse_quote! { Machine },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let validate_sequence = if attributes.validate_sequence {
+            ValidateSequence {
+                machine: self.machine,
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let next_state = if cfg!(feature = "const-fn") {
+            NextState {
+                machine: self.machine,
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let no_panic = if cfg!(feature = "no-panic") {
+            NoPanic {
+                machine: self.machine,
+                ty: parse_quote! { Machine },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let (instrument, record) = if instrument::ENABLED {
+            (
+                Instrument {
+                    machine: self.machine,
+                }
+                .into_token_stream(),
+                quote! { record_transition(self.state, event, to); },
+            )
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+
+        let reject = if instrument::REJECTIONS {
+            quote! {
+                Option::None => {
+                    record_rejection(self.state, event);
+                    Result::Err(TransitionError::Invalid { state: self.state, event })
+                }
+            }
+        } else {
+            quote! { Option::None => Result::Err(TransitionError::Invalid { state: self.state, event }), }
+        };
+
+        tokens.extend(quote! {
+            #[allow(dead_code, non_snake_case)]
+            mod #name {
+                use sm::{Id, Table, TransitionError};
+
+                #ids
+
+                #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                pub struct Machine {
+                    state: StateId,
+                    trigger: Option<EventId>,
+                }
+
+                impl Machine {
+                    #[inline]
+                    pub fn new(state: StateId) -> Option<Self> {
+                        match state {
+                            #(#initial)|* => Option::Some(Machine { state, trigger: Option::None }),
+                            #fallback
+                        }
+                    }
+
+                    #[inline]
+                    pub fn state_id(&self) -> StateId {
+                        self.state
+                    }
+
+                    #[inline]
+                    pub fn trigger_id(&self) -> Option<EventId> {
+                        self.trigger
+                    }
+
+                    #[inline]
+                    pub fn same_state(&self, other: &Machine) -> bool {
+                        self.state == other.state
+                    }
+
+                    pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                        match Machine::next(self.state, event) {
+                            Option::Some(to) => {
+                                #record
+                                self.state = to;
+                                self.trigger = Option::Some(event);
+                                Result::Ok(())
+                            }
+                            #reject
+                        }
+                    }
+
+                    pub fn apply_named(&mut self, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                        match EventId::from_name(name) {
+                            Option::Some(event) => self.apply(event),
+                            Option::None => Result::Err(TransitionError::UnknownEvent),
+                        }
+                    }
+                }
+
+                #table
+                #history
+                #pushdown
+                #handler
+                #footprint
+                #validate_sequence
+                #next_state
+                #instrument
+                #no_panic
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_dynamic_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[dynamic]
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[allow(dead_code, non_snake_case)]
+            mod Lock {
+                use sm::{Id, Table, TransitionError};
+
+                #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                pub enum StateId {
+                    Locked,
+                    Unlocked
+                }
+
+                impl Id for StateId {
+                    const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                    const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                    #[inline]
+                    fn index(self) -> usize {
+                        match self {
+                            StateId::Locked => 0usize,
+                            StateId::Unlocked => 1usize
+                        }
+                    }
+                }
+
+                impl From<StateId> for &'static str {
+                    #[inline]
+                    fn from(id: StateId) -> Self {
+                        id.name()
+                    }
+                }
+
+                #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                pub enum EventId {
+                    TurnKey
+                }
+
+                impl Id for EventId {
+                    const ALL: &'static [Self] = &[EventId::TurnKey];
+                    const NAMES: &'static [&'static str] = &["TurnKey"];
+
+                    #[inline]
+                    fn index(self) -> usize {
+                        match self {
+                            EventId::TurnKey => 0usize
+                        }
+                    }
+                }
+
+                impl From<EventId> for &'static str {
+                    #[inline]
+                    fn from(id: EventId) -> Self {
+                        id.name()
+                    }
+                }
+
+                #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                pub struct Machine {
+                    state: StateId,
+                    trigger: Option<EventId>,
+                }
+
+                impl Machine {
+                    #[inline]
+                    pub fn new(state: StateId) -> Option<Self> {
+                        match state {
+                            StateId::Locked => Option::Some(Machine { state, trigger: Option::None }),
+                            _ => Option::None,
+                        }
+                    }
+
+                    #[inline]
+                    pub fn state_id(&self) -> StateId {
+                        self.state
+                    }
+
+                    #[inline]
+                    pub fn trigger_id(&self) -> Option<EventId> {
+                        self.trigger
+                    }
+
+                    #[inline]
+                    pub fn same_state(&self, other: &Machine) -> bool {
+                        self.state == other.state
+                    }
+
+                    pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                        match Machine::next(self.state, event) {
+                            Option::Some(to) => {
+                                self.state = to;
+                                self.trigger = Option::Some(event);
+                                Result::Ok(())
+                            }
+                            Option::None => Result::Err(TransitionError::Invalid { state: self.state, event }),
+                        }
+                    }
+
+                    pub fn apply_named(&mut self, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                        match EventId::from_name(name) {
+                            Option::Some(event) => self.apply(event),
+                            Option::None => Result::Err(TransitionError::UnknownEvent),
+                        }
+                    }
+                }
+
+                impl Table for Machine {
+                    type StateId = StateId;
+                    type EventId = EventId;
+
+                    const NAME: &'static str = "Lock";
+
+                    #[inline]
+                    fn state(&self) -> StateId {
+                        self.state_id()
+                    }
+
+                    fn next(state: StateId, event: EventId) -> Option<StateId> {
+                        match (state, event) {
+                            (StateId::Locked, EventId::TurnKey) => Option::Some(StateId::Unlocked),
+                            (StateId::Unlocked, EventId::TurnKey) => Option::Some(StateId::Locked),
+                        }
+                    }
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Dynamic { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -1,15 +1,27 @@
+use alloc::string::{String, ToString};
 use alloc::vec::Vec;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::Ident;
 
+use crate::sm::ffi::snake_case;
+
 #[allow(unused)]
 #[derive(Debug)]
 pub(crate) struct Events(pub Vec<Event>);
 
 impl ToTokens for Events {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.eq_tokens(tokens, true);
+    }
+}
+
+impl Events {
+    /// eq_tokens generates the events, the `PartialEq` implementations comparing
+    /// them, and the `EventId` enum. Without `cross_eq`, each of the events
+    /// can only be compared to itself.
+    pub(crate) fn eq_tokens(&self, tokens: &mut TokenStream, cross_eq: bool) {
         for event in &self.0 {
             event.to_tokens(tokens);
 
@@ -18,8 +30,13 @@ impl ToTokens for Events {
                 let other = &other.name;
                 let eq = name == other;
 
+                if !cross_eq && !eq {
+                    continue;
+                }
+
                 tokens.extend(quote! {
                     impl PartialEq<#other> for #name {
+                        #[inline]
                         fn eq(&self, _: & #other) -> bool {
                             #eq
                         }
@@ -27,6 +44,51 @@ impl ToTokens for Events {
                 });
             }
         }
+
+        self.id_tokens(tokens);
+    }
+
+    /// id_tokens generates the `EventId` enum, without the types of the events
+    /// themselves.
+    pub(crate) fn id_tokens(&self, tokens: &mut TokenStream) {
+        let names: Vec<&Ident> = self.0.iter().map(|e| &e.name).collect();
+        let strings: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        let indices: Vec<usize> = (0..names.len()).collect();
+        let names = &names;
+
+        // An enum without variants can't have a `repr`.
+        let repr = if cfg!(feature = "ffi") && !names.is_empty() {
+            quote! { #[repr(C)] }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote! {
+            #repr
+            #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+            pub enum EventId {
+                #(#names),*
+            }
+
+            impl Id for EventId {
+                const ALL: &'static [Self] = &[#(EventId::#names),*];
+                const NAMES: &'static [&'static str] = &[#(#strings),*];
+
+                #[inline]
+                fn index(self) -> usize {
+                    match self {
+                        #(EventId::#names => #indices),*
+                    }
+                }
+            }
+
+            impl From<EventId> for &'static str {
+                #[inline]
+                fn from(id: EventId) -> Self {
+                    id.name()
+                }
+            }
+        });
     }
 }
 
@@ -52,11 +114,35 @@ impl Parse for Event {
 impl ToTokens for Event {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let name = &self.name;
+        let string = name.to_string();
+        let constant = Ident::new(&snake_case(&string).to_uppercase(), Span::call_site());
 
         tokens.extend(quote! {
             #[derive(Clone, Copy, Debug, Eq)]
             pub struct #name;
             impl Event for #name {}
+
+            pub const #constant: #name = #name;
+
+            impl From<#name> for EventId {
+                #[inline]
+                fn from(_: #name) -> Self {
+                    EventId::#name
+                }
+            }
+
+            impl From<#name> for &'static str {
+                #[inline]
+                fn from(_: #name) -> Self {
+                    #string
+                }
+            }
+
+            impl Identify for #name {
+                type Id = EventId;
+
+                const ID: EventId = EventId::#name;
+            }
         });
     }
 }
@@ -89,6 +175,28 @@ mod tests {
             #[derive(Clone, Copy, Debug, Eq)]
             pub struct Push;
             impl Event for Push {}
+
+            pub const PUSH: Push = Push;
+
+            impl From<Push> for EventId {
+                #[inline]
+                fn from(_: Push) -> Self {
+                    EventId::Push
+                }
+            }
+
+            impl From<Push> for &'static str {
+                #[inline]
+                fn from(_: Push) -> Self {
+                    "Push"
+                }
+            }
+
+            impl Identify for Push {
+                type Id = EventId;
+
+                const ID: EventId = EventId::Push;
+            }
         };
 
         let mut right = TokenStream::new();
@@ -113,13 +221,37 @@ mod tests {
             pub struct Push;
             impl Event for Push {}
 
+            pub const PUSH: Push = Push;
+
+            impl From<Push> for EventId {
+                #[inline]
+                fn from(_: Push) -> Self {
+                    EventId::Push
+                }
+            }
+
+            impl From<Push> for &'static str {
+                #[inline]
+                fn from(_: Push) -> Self {
+                    "Push"
+                }
+            }
+
+            impl Identify for Push {
+                type Id = EventId;
+
+                const ID: EventId = EventId::Push;
+            }
+
             impl PartialEq<Push> for Push {
+                #[inline]
                 fn eq(&self, _: &Push) -> bool {
                     true
                 }
             }
 
             impl PartialEq<Coin> for Push {
+                #[inline]
                 fn eq(&self, _: &Coin) -> bool {
                     false
                 }
@@ -129,17 +261,67 @@ mod tests {
             pub struct Coin;
             impl Event for Coin {}
 
+            pub const COIN: Coin = Coin;
+
+            impl From<Coin> for EventId {
+                #[inline]
+                fn from(_: Coin) -> Self {
+                    EventId::Coin
+                }
+            }
+
+            impl From<Coin> for &'static str {
+                #[inline]
+                fn from(_: Coin) -> Self {
+                    "Coin"
+                }
+            }
+
+            impl Identify for Coin {
+                type Id = EventId;
+
+                const ID: EventId = EventId::Coin;
+            }
+
             impl PartialEq<Push> for Coin {
+                #[inline]
                 fn eq(&self, _: & Push) -> bool {
                     false
                 }
             }
 
             impl PartialEq<Coin> for Coin {
+                #[inline]
                 fn eq(&self, _: & Coin) -> bool {
                     true
                 }
             }
+
+            #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+            pub enum EventId {
+                Push,
+                Coin
+            }
+
+            impl Id for EventId {
+                const ALL: &'static [Self] = &[EventId::Push, EventId::Coin];
+                const NAMES: &'static [&'static str] = &["Push", "Coin"];
+
+                #[inline]
+                fn index(self) -> usize {
+                    match self {
+                        EventId::Push => 0usize,
+                        EventId::Coin => 1usize
+                    }
+                }
+            }
+
+            impl From<EventId> for &'static str {
+                #[inline]
+                fn from(id: EventId) -> Self {
+                    id.name()
+                }
+            }
         };
 
         let mut right = TokenStream::new();
@@ -0,0 +1,198 @@
+use alloc::format;
+use alloc::vec;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{braced, Error, Ident};
+
+use crate::sm::initial_state::InitialStates;
+use crate::sm::machine::{Machine, Machines};
+use crate::sm::transition::Transitions;
+
+/// Extend is the input of the `extend!` macro: the definition of a machine,
+/// followed by an extension adding initial states and transitions to it. The
+/// extended machine is generated under the name of the extension.
+///
+/// Extensions can only add to a machine: an extension defining a transition
+/// for a state and event the machine already has a transition for is
+/// rejected.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Extend {
+    pub machine: Machine,
+}
+
+impl Parse for Extend {
+    /// example extend tokens:
+    ///
+    /// ```text
+    /// Lock {
+    ///     InitialStates { ... }
+    ///
+    ///     TurnKey { ... }
+    /// }
+    ///
+    /// SuspendableLock {
+    ///     Suspend { ... }
+    ///     Resume { ... }
+    /// }
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `Lock { ... }`
+        //  ^^^^^^^^^^^^
+        let mut machine = Machine::parse(input)?;
+
+        if input.is_empty() {
+            return Ok(Extend { machine });
+        }
+
+        // `SuspendableLock { ... }`
+        //  ^^^^^^^^^^^^^^^
+        let name: Ident = input.parse()?;
+
+        // `SuspendableLock { ... }`
+        //                    ^^^
+        let block_extension;
+        braced!(block_extension in input);
+
+        // `InitialStates { ... }`
+        //  ^^^^^^^^^^^^^^^^^^^^^
+        let fork = block_extension.fork();
+        let initial_states = match fork.parse::<Ident>() {
+            Ok(ref ident) if ident == "InitialStates" => InitialStates::parse(&block_extension)?,
+            _ => InitialStates(vec![]),
+        };
+
+        // `Suspend { ... }`
+        //  ^^^^^^^^^^^^^^^
+        let transitions = Transitions::parse(&block_extension)?;
+
+        for initial_state in initial_states.0 {
+            if !machine.initial_states.0.iter().any(|i| i.name == initial_state.name) {
+                machine.initial_states.0.push(initial_state);
+            }
+        }
+
+        for transition in transitions.0 {
+            let conflict = machine.transitions.0.iter().any(|t| {
+                t.event.name == transition.event.name && t.from.name == transition.from.name
+            });
+
+            if conflict {
+                return Err(Error::new(
+                    transition.event.name.span(),
+                    format!(
+                        "`{}` already has a transition on `{}` in `{}`",
+                        transition.from.name, transition.event.name, machine.name
+                    ),
+                ));
+            }
+
+            machine.transitions.0.push(transition);
+        }
+
+        machine.name = name;
+
+        Ok(Extend { machine })
+    }
+}
+
+impl ToTokens for Extend {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        // The extended machine is generated like any other machine, including
+        // the imports the `sm!` macro brings in scope.
+        Machines(vec![self.machine.clone()]).to_tokens(tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_extend_parse() {
+        let left: Extend = syn::parse2(quote! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+
+            SuspendableLock {
+                InitialStates { Suspended }
+
+                Suspend {
+                    Locked, Unlocked => Suspended
+                }
+
+                Resume {
+                    Suspended => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        let right: Machine = syn::parse2(quote! {
+            SuspendableLock {
+                InitialStates { Locked, Suspended }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+
+                Suspend {
+                    Locked, Unlocked => Suspended
+                }
+
+                Resume {
+                    Suspended => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(left.machine, right);
+    }
+
+    #[test]
+    fn test_extend_parse_without_extension() {
+        let left: Extend = syn::parse2(quote! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(left.machine.name, "Lock");
+    }
+
+    #[test]
+    fn test_extend_parse_conflict() {
+        let extend: Result<Extend> = syn::parse2(quote! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                }
+            }
+
+            BrokenLock {
+                TurnKey {
+                    Locked => Broken
+                }
+            }
+        });
+
+        assert!(extend.is_err());
+    }
+}
@@ -0,0 +1,209 @@
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, Ident, Path, Token};
+
+use crate::sm::machine::Machine;
+
+/// Externals are the enums defined outside of the machine, whose variants
+/// mirror its states or events. The variants have to match the states or
+/// events of the machine exactly, which is checked by the compiler when the
+/// conversions between the enum and the `StateId` or `EventId` of the machine
+/// are generated.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct Externals {
+    pub states: Option<Path>,
+    pub events: Option<Path>,
+}
+
+impl Parse for Externals {
+    /// example externals tokens:
+    ///
+    /// ```text
+    /// States = conn::State;
+    /// Events = conn::Command;
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let mut externals = Externals::default();
+
+        while input.peek(Ident) && input.peek2(Token![=]) {
+            // `States = conn::State;`
+            //  ^^^^^^
+            let key: Ident = input.parse()?;
+
+            // `States = conn::State;`
+            //         ^
+            let _: Token![=] = input.parse()?;
+
+            // `States = conn::State;`
+            //           ^^^^^^^^^^^
+            let path: Path = input.parse()?;
+
+            // `States = conn::State;`
+            //                      ^
+            let _: Token![;] = input.parse()?;
+
+            let slot = if key == "States" {
+                &mut externals.states
+            } else if key == "Events" {
+                &mut externals.events
+            } else {
+                return Err(Error::new(key.span(), "expected `States` or `Events`"));
+            };
+
+            if slot.is_some() {
+                return Err(Error::new(key.span(), "duplicate external enum"));
+            }
+
+            *slot = Some(path);
+        }
+
+        Ok(externals)
+    }
+}
+
+/// External generates the `From` conversions between the external enums of a
+/// machine and its `StateId` and `EventId` types. It is emitted next to the
+/// machine module, so the paths resolve the same way they do in the macro.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct External<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for External<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.machine.name;
+        let externals = &self.machine.externals;
+
+        let states: Vec<Ident> = self.machine.states().0.into_iter().map(|s| s.name).collect();
+        let events: Vec<Ident> = self.machine.events().0.into_iter().map(|e| e.name).collect();
+
+        for (path, id, names) in &[
+            (&externals.states, quote! { StateId }, &states),
+            (&externals.events, quote! { EventId }, &events),
+        ] {
+            let path = match path {
+                Some(path) => path,
+                None => continue,
+            };
+
+            let mut into = Vec::new();
+            let mut from = Vec::new();
+
+            for variant in names.iter() {
+                into.push(quote! { #name::#id::#variant => #path::#variant, });
+                from.push(quote! { #path::#variant => #name::#id::#variant, });
+            }
+
+            tokens.extend(quote! {
+                impl From<#name::#id> for #path {
+                    fn from(id: #name::#id) -> Self {
+                        match id {
+                            #(#into)*
+                        }
+                    }
+                }
+
+                impl From<#path> for #name::#id {
+                    fn from(variant: #path) -> Self {
+                        match variant {
+                            #(#from)*
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use syn::parse_quote;
+
+    #[test]
+    fn test_externals_parse() {
+        let left: Externals = syn::parse2(quote! {
+            States = conn::State;
+            Events = ::conn::Command;
+        })
+        .unwrap();
+
+        let right = Externals {
+            states: Some(parse_quote! { conn::State }),
+            events: Some(parse_quote! { ::conn::Command }),
+        };
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_externals_parse_unknown() {
+        let externals: Result<Externals> = syn::parse2(quote! {
+            Triggers = conn::Command;
+        });
+
+        assert!(externals.is_err());
+    }
+
+    #[test]
+    fn test_external_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Externals {
+                states: Some(parse_quote! { lock::State }),
+                events: None,
+            },
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            impl From<Lock::StateId> for lock::State {
+                fn from(id: Lock::StateId) -> Self {
+                    match id {
+                        Lock::StateId::Locked => lock::State::Locked,
+                        Lock::StateId::Unlocked => lock::State::Unlocked,
+                    }
+                }
+            }
+
+            impl From<lock::State> for Lock::StateId {
+                fn from(variant: lock::State) -> Self {
+                    match variant {
+                        lock::State::Locked => Lock::StateId::Locked,
+                        lock::State::Unlocked => Lock::StateId::Unlocked,
+                    }
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        External { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,177 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// Ffi generates a C interface for a machine: a `#[repr(C)]` `FfiMachine`
+/// holding the current state, and `extern "C"` functions prefixed with the
+/// snake cased name of the machine to create it, apply events to it and read
+/// its state. The `StateId` and `EventId` enums are `#[repr(C)]` as well, so a
+/// C header can be generated for them using a tool like [cbindgen].
+///
+/// [cbindgen]: https://docs.rs/cbindgen
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Ffi<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Ffi<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let prefix = snake_case(&self.machine.name.to_string());
+        let new = Ident::new(&format!("{}_new", prefix), Span::call_site());
+        let apply = Ident::new(&format!("{}_apply", prefix), Span::call_site());
+        let state = Ident::new(&format!("{}_state", prefix), Span::call_site());
+
+        // A machine without events has nothing to apply, and its `EventId`
+        // enum can't cross the C interface.
+        let apply_fn = if self.machine.events().0.is_empty() {
+            TokenStream::new()
+        } else {
+            quote! {
+                #[no_mangle]
+                pub extern "C" fn #apply(machine: &mut FfiMachine, event: EventId) -> bool {
+                    match Variant::next(machine.state, event) {
+                        Option::Some(state) => {
+                            machine.state = state;
+                            true
+                        }
+                        Option::None => false,
+                    }
+                }
+            }
+        };
+
+        tokens.extend(quote! {
+            #[repr(C)]
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            pub struct FfiMachine {
+                state: StateId,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn #new(initial: StateId, machine: &mut FfiMachine) -> bool {
+                match Variant::from_ids(initial, Option::None) {
+                    Option::Some(_) => {
+                        machine.state = initial;
+                        true
+                    }
+                    Option::None => false,
+                }
+            }
+
+            #apply_fn
+
+            #[no_mangle]
+            pub extern "C" fn #state(machine: &FfiMachine) -> StateId {
+                machine.state
+            }
+        });
+    }
+}
+
+/// snake_case converts a camel cased name, such as the name of a machine, to
+/// snake case, for example `TurnStile` to `turn_stile`.
+pub(crate) fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_snake_case() {
+        assert_eq!(snake_case("Lock"), "lock");
+        assert_eq!(snake_case("TurnStile"), "turn_stile");
+    }
+
+    #[test]
+    fn test_ffi_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { TurnStile },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { Coin },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            #[repr(C)]
+            #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+            pub struct FfiMachine {
+                state: StateId,
+            }
+
+            #[no_mangle]
+            pub extern "C" fn turn_stile_new(initial: StateId, machine: &mut FfiMachine) -> bool {
+                match Variant::from_ids(initial, Option::None) {
+                    Option::Some(_) => {
+                        machine.state = initial;
+                        true
+                    }
+                    Option::None => false,
+                }
+            }
+
+            #[no_mangle]
+            pub extern "C" fn turn_stile_apply(machine: &mut FfiMachine, event: EventId) -> bool {
+                match Variant::next(machine.state, event) {
+                    Option::Some(state) => {
+                        machine.state = state;
+                        true
+                    }
+                    Option::None => false,
+                }
+            }
+
+            #[no_mangle]
+            pub extern "C" fn turn_stile_state(machine: &FfiMachine) -> StateId {
+                machine.state
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Ffi { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,86 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// Footprint generates, for a machine marked `#[footprint]`, constants
+/// holding the size in bytes of the `StateId` and `EventId` types of the
+/// machine, of its `ty` type, of its `History` type if it is marked
+/// `#[undo(n)]`, and of its `Pushdown` type if it is marked `#[pushdown(n)]`,
+/// so the memory used by machines can be budgeted, and checked at compile
+/// time using `sm::assert_max_size!`. The number of states and events of the
+/// machine are generated as well, to size arrays indexed by their ids.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Footprint<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Footprint<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+        let states = self.machine.states().0.len();
+        let events = self.machine.events().0.len();
+
+        tokens.extend(quote! {
+            pub const STATE_COUNT: usize = #states;
+            pub const EVENT_COUNT: usize = #events;
+            pub const STATE_ID_SIZE: usize = ::sm::export::mem::size_of::<StateId>();
+            pub const EVENT_ID_SIZE: usize = ::sm::export::mem::size_of::<EventId>();
+            pub const MACHINE_SIZE: usize = ::sm::export::mem::size_of::<#ty>();
+        });
+
+        if self.machine.attributes.undo.is_some() {
+            tokens.extend(quote! {
+                pub const HISTORY_SIZE: usize = ::sm::export::mem::size_of::<History>();
+            });
+        }
+
+        if self.machine.attributes.pushdown.is_some() {
+            tokens.extend(quote! {
+                pub const PUSHDOWN_SIZE: usize = ::sm::export::mem::size_of::<Pushdown>();
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_footprint_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[undo(4)]
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey { Locked => Unlocked }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            pub const STATE_COUNT: usize = 2usize;
+            pub const EVENT_COUNT: usize = 1usize;
+            pub const STATE_ID_SIZE: usize = ::sm::export::mem::size_of::<StateId>();
+            pub const EVENT_ID_SIZE: usize = ::sm::export::mem::size_of::<EventId>();
+            pub const MACHINE_SIZE: usize = ::sm::export::mem::size_of::<Variant>();
+            pub const HISTORY_SIZE: usize = ::sm::export::mem::size_of::<History>();
+        };
+
+        let mut right = TokenStream::new();
+        Footprint {
+            machine: &machine,
+            ty: parse_quote! { Variant },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,138 @@
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::ffi::snake_case;
+use crate::sm::machine::Machine;
+
+/// Handler generates, for a machine marked `#[handler]`, a trait named after
+/// the machine, e.g. `LockHandler` for `Lock`, with one `on_<state>_<event>`
+/// method for every transition of the machine, named after the state it
+/// leaves and the event triggering it. The methods have no default
+/// implementation, so adding a transition to the machine breaks the
+/// implementations that don't handle it yet.
+///
+/// The `apply_with` method of the `ty` type of the machine applies an event,
+/// and calls the method of the handler matching the transition it performed.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Handler<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Handler<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+        let handler = Ident::new(&format!("{}Handler", self.machine.name), Span::call_site());
+
+        let mut pairs: Vec<(&Ident, &Ident)> = Vec::new();
+        let mut methods = Vec::new();
+        let mut arms = Vec::new();
+
+        for t in &self.machine.transitions.0 {
+            let from = &t.from.name;
+            let event = &t.event.name;
+
+            if pairs.contains(&(from, event)) {
+                continue;
+            }
+
+            pairs.push((from, event));
+
+            let method = Ident::new(
+                &format!(
+                    "on_{}_{}",
+                    snake_case(&from.to_string()),
+                    snake_case(&event.to_string())
+                ),
+                Span::call_site(),
+            );
+
+            methods.push(quote! { fn #method(&mut self); });
+            arms.push(quote! { (StateId::#from, EventId::#event) => handler.#method(), });
+        }
+
+        let combinations = self.machine.states().0.len() * self.machine.events().0.len();
+        let fallback = if pairs.is_empty() || pairs.len() < combinations {
+            quote! { _ => {} }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote! {
+            pub trait #handler {
+                #(#methods)*
+            }
+
+            impl #ty {
+                pub fn apply_with<H: #handler>(&mut self, event: EventId, handler: &mut H) -> Result<(), TransitionError<StateId, EventId>> {
+                    let state = self.state_id();
+                    self.apply(event)?;
+
+                    match (state, event) {
+                        #(#arms)*
+                        #fallback
+                    }
+
+                    Result::Ok(())
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_handler_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            TurnStile {
+                InitialStates { Locked }
+
+                Coin { Locked => Unlocked }
+                Push { Unlocked => Locked }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            pub trait TurnStileHandler {
+                fn on_locked_coin(&mut self);
+                fn on_unlocked_push(&mut self);
+            }
+
+            impl Variant {
+                pub fn apply_with<H: TurnStileHandler>(&mut self, event: EventId, handler: &mut H) -> Result<(), TransitionError<StateId, EventId>> {
+                    let state = self.state_id();
+                    self.apply(event)?;
+
+                    match (state, event) {
+                        (StateId::Locked, EventId::Coin) => handler.on_locked_coin(),
+                        (StateId::Unlocked, EventId::Push) => handler.on_unlocked_push(),
+                        _ => {}
+                    }
+
+                    Result::Ok(())
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Handler {
+            machine: &machine,
+            ty: parse_quote! { Variant },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,191 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// History generates the `History` type of a machine marked `#[undo(n)]`: a
+/// wrapper around the `ty` type of the machine, which remembers up to `n` of
+/// the states it went through. Its `undo` method transitions the machine back
+/// to the previous state, using an event leading there from the current
+/// state, if the transition table has one. The oldest states are forgotten
+/// once `n` states are remembered.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct History<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for History<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+        let size = match self.machine.attributes.undo {
+            Some(size) => size,
+            None => return,
+        };
+
+        tokens.extend(quote! {
+            #[derive(Clone, Debug)]
+            pub struct History {
+                machine: #ty,
+                previous: [Option<StateId>; #size],
+                len: usize,
+            }
+
+            impl History {
+                #[inline]
+                pub fn new(machine: #ty) -> Self {
+                    History {
+                        machine,
+                        previous: [Option::None; #size],
+                        len: 0,
+                    }
+                }
+
+                #[inline]
+                pub fn machine(&self) -> &#ty {
+                    &self.machine
+                }
+
+                #[inline]
+                pub fn into_machine(self) -> #ty {
+                    self.machine
+                }
+
+                pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    let state = self.machine.state_id();
+                    self.machine.apply(event)?;
+
+                    if self.len == #size {
+                        self.previous.rotate_left(1);
+                        self.len -= 1;
+                    }
+
+                    if let Option::Some(slot) = self.previous.get_mut(self.len) {
+                        *slot = Option::Some(state);
+                        self.len += 1;
+                    }
+
+                    Result::Ok(())
+                }
+
+                pub fn undo(&mut self) -> Option<EventId> {
+                    let last = self.len.checked_sub(1)?;
+                    let previous = (*self.previous.get(last)?)?;
+                    let state = self.machine.state_id();
+                    let event = EventId::ALL
+                        .iter()
+                        .cloned()
+                        .find(|&event| #ty::next(state, event) == Option::Some(previous))?;
+
+                    self.machine.apply(event).ok()?;
+                    if let Option::Some(slot) = self.previous.get_mut(last) {
+                        *slot = Option::None;
+                    }
+                    self.len = last;
+
+                    Option::Some(event)
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_history_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[undo(4)]
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[derive(Clone, Debug)]
+            pub struct History {
+                machine: Variant,
+                previous: [Option<StateId>; 4usize],
+                len: usize,
+            }
+
+            impl History {
+                #[inline]
+                pub fn new(machine: Variant) -> Self {
+                    History {
+                        machine,
+                        previous: [Option::None; 4usize],
+                        len: 0,
+                    }
+                }
+
+                #[inline]
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+
+                #[inline]
+                pub fn into_machine(self) -> Variant {
+                    self.machine
+                }
+
+                pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    let state = self.machine.state_id();
+                    self.machine.apply(event)?;
+
+                    if self.len == 4usize {
+                        self.previous.rotate_left(1);
+                        self.len -= 1;
+                    }
+
+                    if let Option::Some(slot) = self.previous.get_mut(self.len) {
+                        *slot = Option::Some(state);
+                        self.len += 1;
+                    }
+
+                    Result::Ok(())
+                }
+
+                pub fn undo(&mut self) -> Option<EventId> {
+                    let last = self.len.checked_sub(1)?;
+                    let previous = (*self.previous.get(last)?)?;
+                    let state = self.machine.state_id();
+                    let event = EventId::ALL
+                        .iter()
+                        .cloned()
+                        .find(|&event| Variant::next(state, event) == Option::Some(previous))?;
+
+                    self.machine.apply(event).ok()?;
+                    if let Option::Some(slot) = self.previous.get_mut(last) {
+                        *slot = Option::None;
+                    }
+                    self.len = last;
+
+                    Option::Some(event)
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        History {
+            machine: &machine,
+            ty: parse_quote! { Variant },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
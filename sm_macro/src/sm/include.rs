@@ -0,0 +1,170 @@
+use alloc::format;
+use alloc::string::String;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use std::path::PathBuf;
+use std::{env, fs};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, Ident, LitStr, Token};
+
+use crate::sm::machine::Machines;
+
+/// Input is the input of the `sm!` macro: either the machines themselves, or
+/// the path of a file containing them.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Input {
+    Inline(Machines),
+    Include(Include),
+}
+
+impl Parse for Input {
+    /// example input tokens:
+    ///
+    /// ```text
+    /// TurnStile { ... }
+    /// ```
+    ///
+    /// or:
+    ///
+    /// ```text
+    /// path = "machines/turnstile.sm"
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        if input.peek(Ident) && input.peek2(Token![=]) {
+            Ok(Input::Include(Include::parse(input)?))
+        } else {
+            Ok(Input::Inline(Machines::parse(input)?))
+        }
+    }
+}
+
+impl ToTokens for Input {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        match self {
+            Input::Inline(machines) => machines.to_tokens(tokens),
+            Input::Include(include) => include.to_tokens(tokens),
+        }
+    }
+}
+
+/// Include holds the machines read from a file, using the same syntax as the
+/// `sm!` macro. The path is relative to the directory of the manifest of the
+/// crate using the macro.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Include {
+    pub path: String,
+    pub machines: Machines,
+}
+
+impl Parse for Include {
+    /// example include tokens:
+    ///
+    /// ```text
+    /// path = "machines/turnstile.sm"
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `path = "machines/turnstile.sm"`
+        //  ^^^^
+        let key: Ident = input.parse()?;
+        if key != "path" {
+            return Err(Error::new(key.span(), "expected `path`"));
+        }
+
+        // `path = "machines/turnstile.sm"`
+        //       ^
+        let _: Token![=] = input.parse()?;
+
+        // `path = "machines/turnstile.sm"`
+        //         ^^^^^^^^^^^^^^^^^^^^^^^
+        let lit: LitStr = input.parse()?;
+
+        let mut path = env::var("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_default();
+        path.push(lit.value());
+
+        let source = fs::read_to_string(&path).map_err(|err| {
+            Error::new(lit.span(), format!("couldn't read {}: {}", path.display(), err))
+        })?;
+
+        let machines = syn::parse_str(&source).map_err(|err| {
+            Error::new(lit.span(), format!("{}: {}", path.display(), err))
+        })?;
+
+        Ok(Include {
+            path: path.to_string_lossy().into_owned(),
+            machines,
+        })
+    }
+}
+
+impl ToTokens for Include {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let path = &self.path;
+
+        // The file is included as a string, so the compiler tracks it, and
+        // recompiles the crate when it changes.
+        let name: String = self
+            .path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+            .collect();
+        let source = Ident::new(&format!("SM_INCLUDE_{}", name), Span::call_site());
+
+        self.machines.to_tokens(tokens);
+
+        tokens.extend(quote! {
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            const #source: &str = include_str!(#path);
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_include_parse() {
+        let path = env::temp_dir().join("sm_macro_test_include_parse.sm");
+        fs::write(
+            &path,
+            "TurnStile { InitialStates { Locked } Coin { Locked => Unlocked } }",
+        )
+        .unwrap();
+
+        let path = path.to_string_lossy().to_string();
+        let left: Input = syn::parse2(quote! { path = #path }).unwrap();
+        let right: Machines = syn::parse2(quote! {
+            TurnStile {
+                InitialStates { Locked }
+
+                Coin {
+                    Locked => Unlocked
+                }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(
+            left,
+            Input::Include(Include {
+                path: path.clone(),
+                machines: right,
+            })
+        );
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_include_parse_missing_file() {
+        let input: Result<Input> = syn::parse2(quote! { path = "does/not/exist.sm" });
+
+        assert!(input.is_err());
+    }
+}
@@ -5,7 +5,7 @@ use syn::parse::{Parse, ParseStream, Result};
 use syn::punctuated::Punctuated;
 use syn::{braced, Ident, Token};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct InitialStates(pub Vec<InitialState>);
 
 impl Parse for InitialStates {
@@ -0,0 +1,274 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::sm::machine::Machine;
+
+/// ENABLED is true when any of the instrumentation features is enabled, in
+/// which case every generated transition calls `record_transition`.
+pub(crate) const ENABLED: bool = cfg!(any(feature = "log", feature = "metrics", feature = "tracing"));
+
+/// REJECTIONS is true when the `log` feature is enabled, in which case every
+/// event rejected by a machine calls `record_rejection`.
+pub(crate) const REJECTIONS: bool = cfg!(feature = "log");
+
+/// Instrument generates the `record_transition` function of a machine, which
+/// reports every transition to the instrumentation crates enabled through
+/// features:
+///
+/// - `log`: logs a line at the debug level using [log]. The target is the path of the
+///   generated module, so logging can be enabled per machine. With this
+///   feature, a `record_rejection` function is generated as well, logging
+///   every event rejected by the machine.
+/// - `metrics`: increments the `sm_transition` [metrics] counter, labeled with
+///   the machine, the state the machine transitioned from, and the event.
+/// - `tracing`: emits a [tracing] event at the debug level, with the machine,
+///   from, event and to fields. With this feature, a `span` method is
+///   generated on `StateId` as well, returning a debug span named after the
+///   state, which the caller can enter for as long as the machine stays in
+///   that state.
+///
+/// [log]: https://docs.rs/log
+/// [metrics]: https://docs.rs/metrics
+/// [tracing]: https://docs.rs/tracing
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Instrument<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> Instrument<'a> {
+    fn log(&self) -> TokenStream {
+        quote! {
+            ::sm::export::log::debug!(
+                "transitioned from {} to {} on {}",
+                from.name(),
+                to.name(),
+                event.name()
+            );
+        }
+    }
+
+    fn log_rejection(&self) -> TokenStream {
+        quote! {
+            fn record_rejection(state: StateId, event: EventId) {
+                ::sm::export::log::debug!("rejected {} in {}", event.name(), state.name());
+            }
+        }
+    }
+
+    fn metrics(&self) -> TokenStream {
+        let name: String = self.machine.name.to_string();
+
+        quote! {
+            ::sm::export::metrics::counter!(
+                "sm_transition",
+                "machine" => #name,
+                "from" => from.name(),
+                "event" => event.name()
+            )
+            .increment(1);
+        }
+    }
+
+    fn tracing(&self) -> TokenStream {
+        let name: String = self.machine.name.to_string();
+
+        quote! {
+            ::sm::export::tracing::debug!(
+                machine = #name,
+                from = from.name(),
+                event = event.name(),
+                to = to.name(),
+                "transition"
+            );
+        }
+    }
+
+    fn tracing_span(&self) -> TokenStream {
+        let name: String = self.machine.name.to_string();
+        let spans: Vec<TokenStream> = self
+            .machine
+            .states()
+            .0
+            .into_iter()
+            .map(|state| {
+                let state = state.name;
+                let span = state.to_string();
+
+                quote! { StateId::#state => ::sm::export::tracing::debug_span!(#span, machine = #name) }
+            })
+            .collect();
+
+        quote! {
+            impl StateId {
+                pub fn span(self) -> ::sm::export::tracing::Span {
+                    match self {
+                        #(#spans,)*
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Instrument<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut body = TokenStream::new();
+
+        if cfg!(feature = "log") {
+            body.extend(self.log());
+        }
+
+        if cfg!(feature = "metrics") {
+            body.extend(self.metrics());
+        }
+
+        if cfg!(feature = "tracing") {
+            body.extend(self.tracing());
+        }
+
+        tokens.extend(quote! {
+            fn record_transition(from: StateId, event: EventId, to: StateId) {
+                let _ = (from, event, to);
+                #body
+            }
+        });
+
+        if cfg!(feature = "log") {
+            tokens.extend(self.log_rejection());
+        }
+
+        if cfg!(feature = "tracing") {
+            tokens.extend(self.tracing_span());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use syn::parse_quote;
+
+    fn machine() -> Machine {
+        Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        }
+    }
+
+    #[test]
+    fn test_instrument_log() {
+        let machine = machine();
+
+        let left = quote! {
+            ::sm::export::log::debug!(
+                "transitioned from {} to {} on {}",
+                from.name(),
+                to.name(),
+                event.name()
+            );
+        };
+
+        let right = Instrument { machine: &machine }.log();
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_instrument_log_rejection() {
+        let machine = machine();
+
+        let left = quote! {
+            fn record_rejection(state: StateId, event: EventId) {
+                ::sm::export::log::debug!("rejected {} in {}", event.name(), state.name());
+            }
+        };
+
+        let right = Instrument { machine: &machine }.log_rejection();
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_instrument_metrics() {
+        let machine = machine();
+
+        let left = quote! {
+            ::sm::export::metrics::counter!(
+                "sm_transition",
+                "machine" => "Lock",
+                "from" => from.name(),
+                "event" => event.name()
+            )
+            .increment(1);
+        };
+
+        let right = Instrument { machine: &machine }.metrics();
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_instrument_tracing() {
+        let machine = machine();
+
+        let left = quote! {
+            ::sm::export::tracing::debug!(
+                machine = "Lock",
+                from = from.name(),
+                event = event.name(),
+                to = to.name(),
+                "transition"
+            );
+        };
+
+        let right = Instrument { machine: &machine }.tracing();
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_instrument_tracing_span() {
+        let machine = machine();
+
+        let left = quote! {
+            impl StateId {
+                pub fn span(self) -> ::sm::export::tracing::Span {
+                    match self {
+                        StateId::Locked => ::sm::export::tracing::debug_span!("Locked", machine = "Lock"),
+                        StateId::Unlocked => ::sm::export::tracing::debug_span!("Unlocked", machine = "Lock"),
+                    }
+                }
+            }
+        };
+
+        let right = Instrument { machine: &machine }.tracing_span();
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
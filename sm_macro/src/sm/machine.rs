@@ -1,16 +1,37 @@
+use alloc::string::{String, ToString};
 use alloc::{format, vec::Vec};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, ToTokens};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::{braced, parse_quote, Ident};
 
+use crate::sm::actix::Actix;
+use crate::sm::attributes::Attributes;
+use crate::sm::bevy::Bevy;
+use crate::sm::channel::Channel;
+use crate::sm::defmt::Defmt;
+use crate::sm::dynamic::Dynamic;
 use crate::sm::event::{Event, Events};
+use crate::sm::external::{External, Externals};
+use crate::sm::ffi::{snake_case, Ffi};
+use crate::sm::footprint::Footprint;
+use crate::sm::handler::Handler;
+use crate::sm::history::History;
 use crate::sm::initial_state::InitialStates;
+use crate::sm::instrument::{self, Instrument};
+use crate::sm::match_macro::MatchMacro;
+use crate::sm::no_panic::NoPanic;
+use crate::sm::persist::Persist;
+use crate::sm::pushdown::Pushdown;
+use crate::sm::python::Python;
+use crate::sm::serde::Serde;
 use crate::sm::state::{State, States};
 use crate::sm::transition::Transitions;
+use crate::sm::ufmt::Ufmt;
+use crate::sm::wasm::Wasm;
 
 #[derive(Debug, PartialEq)]
-pub(crate) struct Machines(Vec<Machine>);
+pub(crate) struct Machines(pub Vec<Machine>);
 
 impl Parse for Machines {
     /// example machines tokens:
@@ -47,15 +68,17 @@ impl ToTokens for Machines {
     }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Machine {
+    pub attributes: Attributes,
     pub name: Ident,
+    pub externals: Externals,
     pub initial_states: InitialStates,
     pub transitions: Transitions,
 }
 
 impl Machine {
-    fn states(&self) -> States {
+    pub(crate) fn states(&self) -> States {
         let mut states: Vec<State> = Vec::new();
 
         for t in &self.transitions.0 {
@@ -79,7 +102,7 @@ impl Machine {
         States(states)
     }
 
-    fn events(&self) -> Events {
+    pub(crate) fn events(&self) -> Events {
         let mut events: Vec<Event> = Vec::new();
 
         for t in &self.transitions.0 {
@@ -90,13 +113,68 @@ impl Machine {
 
         Events(events)
     }
+
+    pub(crate) fn trigger(&self) -> Trigger {
+        if self.attributes.erase_trigger {
+            Trigger::Erased
+        } else if self.attributes.zero_sized {
+            Trigger::ZeroSized
+        } else {
+            Trigger::Typed
+        }
+    }
+
+    /// table returns whether the `Table` trait is implemented for the machine,
+    /// either because it is marked `#[table]`, or because something else
+    /// generated for it is built on top of the trait.
+    pub(crate) fn table(&self) -> bool {
+        let attributes = &self.attributes;
+
+        attributes.dynamic
+            || attributes.table
+            || attributes.undo.is_some()
+            || attributes.python
+            || cfg!(feature = "ffi")
+            || cfg!(feature = "persist")
+            || cfg!(feature = "wasm")
+    }
+
+    /// apply returns whether the `apply` and `apply_named` methods are
+    /// generated for the machine, either because it is marked `#[apply]`, or
+    /// because something else generated for it is built on top of them.
+    pub(crate) fn apply(&self) -> bool {
+        let attributes = &self.attributes;
+
+        attributes.dynamic
+            || attributes.apply
+            || attributes.undo.is_some()
+            || attributes.pushdown.is_some()
+            || attributes.handler
+            || cfg!(feature = "bevy")
+    }
+}
+
+/// Trigger is the way a machine stores the event that triggered its last
+/// transition.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum Trigger {
+    /// `Machine<S, E>(S, Option<E>)`, the default.
+    Typed,
+    /// `Machine<S>(S, Option<EventId>)`, for machines marked
+    /// `#[erase_trigger]`.
+    Erased,
+    /// `Machine<S, E>(S, E)`, for machines marked `#[zero_sized]`, where a
+    /// machine that has not transitioned yet holds a `NoneEvent`.
+    ZeroSized,
 }
 
 impl Parse for Machine {
     /// example machine tokens:
     ///
     /// ```text
+    /// #[...]
     /// TurnStile {
+    ///     States = ...;
     ///     InitialStates { ... }
     ///
     ///     Push { ... }
@@ -105,6 +183,10 @@ impl Parse for Machine {
     /// ```
     ///
     fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `#[dynamic]`
+        //  ^^^^^^^^^^
+        let attributes = Attributes::parse(input)?;
+
         // `TurnStile { ... }`
         //  ^^^^^^^^^
         let name: Ident = input.parse()?;
@@ -114,6 +196,10 @@ impl Parse for Machine {
         let block_machine;
         braced!(block_machine in input);
 
+        // `States = ...;`
+        //  ^^^^^^^^^^^^^
+        let externals = Externals::parse(&block_machine)?;
+
         // `InitialStates { ... }`
         //  ^^^^^^^^^^^^^^^^^^^^^
         let initial_states = InitialStates::parse(&block_machine)?;
@@ -123,7 +209,9 @@ impl Parse for Machine {
         let transitions = Transitions::parse(&block_machine)?;
 
         Ok(Machine {
+            attributes,
             name,
+            externals,
             initial_states,
             transitions,
         })
@@ -132,56 +220,613 @@ impl Parse for Machine {
 
 impl ToTokens for Machine {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        if self.attributes.dynamic {
+            Dynamic { machine: self }.to_tokens(tokens);
+            External { machine: self }.to_tokens(tokens);
+            return;
+        }
+
         let name = &self.name;
         let initial_states = &self.initial_states;
-        let states = &self.states();
-        let events = &self.events();
-        let machine_enum = MachineEnum { machine: &self };
-        let transitions = &self.transitions;
+
+        // Machines marked `#[no_cross_eq]` only compare states and events to
+        // themselves, saving the quadratic number of `PartialEq` impls.
+        let cross_eq = !self.attributes.no_cross_eq;
+
+        let mut states = TokenStream::new();
+        self.states().eq_tokens(&mut states, cross_eq, Some(&self.transitions));
+
+        let mut events = TokenStream::new();
+        self.events().eq_tokens(&mut events, cross_eq);
+
+        let trigger = self.trigger();
+
+        let mut transitions = TokenStream::new();
+        match trigger {
+            Trigger::Typed => self.transitions.to_tokens(&mut transitions),
+            Trigger::Erased => self.transitions.erased_tokens(&mut transitions),
+            Trigger::ZeroSized => self.transitions.zero_sized_tokens(&mut transitions),
+        }
+
+        self.transitions.accepts_tokens(&mut transitions);
+
+        // Everything built on top of the `Variant` enum is left out of
+        // machines marked `#[no_enum]`.
+        let variant = !self.attributes.no_enum;
+
+        let machine_enum = if variant {
+            let machine_enum = MachineEnum {
+                machine: self,
+                trigger,
+            };
+
+            quote! {
+                mod variant {
+                    use super::*;
+
+                    #machine_enum
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let (variant_use, prelude_use) = if variant {
+            (
+                quote! { pub use self::variant::*; },
+                quote! { pub use super::variant::*; },
+            )
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
+
+        let machine_table = if variant && self.table() {
+            MachineTable {
+                machine: self,
+                ty: parse_quote! { Variant },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let history = History {
+            machine: self,
+            ty: parse_quote! { Variant },
+        };
+
+        let pushdown = Pushdown {
+            machine: self,
+            ty: parse_quote! { Variant },
+        };
+
+        let channel = Channel { machine: self };
+
+        let handler = if variant && self.attributes.handler {
+            Handler {
+                machine: self,
+                ty: parse_quote! { Variant },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let footprint = if variant && self.attributes.footprint {
+            Footprint {
+                machine: self,
+                ty: parse_quote! { Variant },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let actix = if variant && cfg!(feature = "actix") {
+            Actix { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let bevy = if variant && cfg!(feature = "bevy") {
+            Bevy.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let defmt = if variant && cfg!(feature = "defmt") {
+            Defmt { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let ffi = if variant && cfg!(feature = "ffi") {
+            Ffi { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let ufmt = if variant && cfg!(feature = "ufmt") {
+            Ufmt { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let next_state = if cfg!(feature = "const-fn") {
+            NextState { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let validate_sequence = if self.attributes.validate_sequence {
+            ValidateSequence { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let instrument = if instrument::ENABLED {
+            Instrument { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let persist = if variant && cfg!(feature = "persist") {
+            Persist.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let no_panic = if variant && cfg!(feature = "no-panic") {
+            NoPanic {
+                machine: self,
+                ty: parse_quote! { Variant },
+            }
+            .into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let python = if self.attributes.python {
+            Python { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let serde = if variant && cfg!(feature = "serde") {
+            Serde.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        let wasm = if variant && cfg!(feature = "wasm") {
+            Wasm { machine: self }.into_token_stream()
+        } else {
+            TokenStream::new()
+        };
+
+        // The machine type is printed along with the name of the machine,
+        // the same way it is referred to from outside of its module.
+        let debug_name = format!("{}::Machine", name);
+
+        // Machines marked `#[erase_trigger]` store their trigger as an
+        // `EventId`, so there is one machine type per state instead of one
+        // per state and trigger.
+        let (imports, machine_type) = if trigger == Trigger::Erased {
+            (
+                quote! {
+                    use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Machine as M, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+                },
+                quote! {
+                    #[derive(Clone, Eq, PartialEq)]
+                    pub struct Machine<S: State>(S, Option<EventId>);
+
+                    impl<S: State> ::sm::export::fmt::Debug for Machine<S> {
+                        fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                            f.debug_struct(#debug_name)
+                                .field("state", &self.0)
+                                .field("trigger", &self.1)
+                                .finish()
+                        }
+                    }
+
+                    impl Event for EventId {}
+
+                    impl<S: State> M for Machine<S> {
+                        type State = S;
+                        type Event = EventId;
+
+                        #[inline]
+                        fn state(&self) -> Self::State {
+                            self.0.clone()
+                        }
+
+                        #[inline]
+                        fn trigger(&self) -> Option<Self::Event> {
+                            self.1
+                        }
+                    }
+
+                    impl<S: InitialState> Machine<S> {
+                        #[inline]
+                        pub fn new(state: S) -> Self {
+                            Machine(state, Option::None)
+                        }
+                    }
+                },
+            )
+        } else if trigger == Trigger::ZeroSized {
+            (
+                quote! {
+                    use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+                },
+                ZeroSizedMachine { machine: self }.into_token_stream(),
+            )
+        } else {
+            (
+                quote! {
+                    use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+                },
+                quote! {
+                    #[derive(Clone, Eq, PartialEq)]
+                    pub struct Machine<S: State, E: Event>(S, Option<E>);
+
+                    impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                        fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                            f.debug_struct(#debug_name)
+                                .field("state", &self.0)
+                                .field("trigger", &self.1)
+                                .finish()
+                        }
+                    }
+
+                    impl<S: State, E: Event> M for Machine<S, E> {
+                        type State = S;
+                        type Event = E;
+
+                        #[inline]
+                        fn state(&self) -> Self::State {
+                            self.0.clone()
+                        }
+
+                        #[inline]
+                        fn trigger(&self) -> Option<Self::Event> {
+                            self.1.clone()
+                        }
+                    }
+
+                    impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
+                        type Machine = Machine<S, NoneEvent>;
+
+                        #[inline]
+                        fn new(state: S) -> Self::Machine {
+                            Machine(state, Option::None)
+                        }
+                    }
+                },
+            )
+        };
+
+        let predicates = Predicates {
+            machine: self,
+            trigger,
+        };
+
+        let constructors = Constructors {
+            machine: self,
+            trigger,
+        };
+
+        let into_state = IntoState {
+            machine: self,
+            trigger,
+        };
 
         tokens.extend(quote! {
-            #[allow(non_snake_case)]
+            #[allow(dead_code, non_snake_case, unused_imports)]
             mod #name {
-                use sm::{AsEnum, Event, InitialState, Initializer, Machine as M, NoneEvent, State, Transition};
+                #imports
 
-                #[derive(Debug, Eq, PartialEq)]
-                pub struct Machine<S: State, E: Event>(S, Option<E>);
+                pub use self::events::*;
+                pub use self::states::*;
+                #variant_use
 
-                impl<S: State, E: Event> M for Machine<S, E> {
-                    type State = S;
-                    type Event = E;
+                #machine_type
+                #constructors
+                #predicates
+                #into_state
+
+                mod states {
+                    use super::*;
+
+                    #states
+                    #initial_states
+                }
+
+                mod events {
+                    use super::*;
+
+                    #events
+                }
+
+                #machine_enum
+
+                mod transitions {
+                    use super::*;
+
+                    #transitions
+                }
+
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    #prelude_use
+                }
+
+                #machine_table
+                #history
+                #pushdown
+                #channel
+                #handler
+                #footprint
+                #validate_sequence
+                #next_state
+                #actix
+                #bevy
+                #defmt
+                #ffi
+                #instrument
+                #no_panic
+                #persist
+                #python
+                #serde
+                #ufmt
+                #wasm
+            }
+        });
+
+        External { machine: self }.to_tokens(tokens);
+
+        if self.attributes.match_macro {
+            MatchMacro { machine: self }.to_tokens(tokens);
+        }
+    }
+}
+
+/// ZeroSizedMachine generates the machine type of a machine marked
+/// `#[zero_sized]`, which holds its trigger without wrapping it in an
+/// `Option`. As the trigger of an initial machine is a `NoneEvent`, the
+/// `Machine` trait is implemented for every combination of state and trigger
+/// the machine can be in, rather than for any state and any event.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+struct ZeroSizedMachine<'a> {
+    machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for ZeroSizedMachine<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let debug_name = format!("{}::Machine", self.machine.name);
+
+        tokens.extend(quote! {
+            #[derive(Clone, Eq, PartialEq)]
+            pub struct Machine<S: State, E: Event>(S, E);
+
+            impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                    f.debug_struct(#debug_name)
+                        .field("state", &self.0)
+                        .field("trigger", &self.1)
+                        .finish()
+                }
+            }
+        });
+
+        for s in &self.machine.initial_states.0 {
+            let state = &s.name;
 
+            tokens.extend(quote! {
+                impl M for Machine<#state, NoneEvent> {
+                    type State = #state;
+                    type Event = NoneEvent;
+
+                    #[inline]
                     fn state(&self) -> Self::State {
                         self.0.clone()
                     }
 
+                    #[inline]
                     fn trigger(&self) -> Option<Self::Event> {
-                        self.1.clone()
+                        Option::None
                     }
                 }
 
-                impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
-                    type Machine = Machine<S, NoneEvent>;
+                impl Initializer<#state> for Machine<#state, NoneEvent> {
+                    type Machine = Machine<#state, NoneEvent>;
 
-                    fn new(state: S) -> Self::Machine {
-                        Machine(state, Option::None)
+                    #[inline]
+                    fn new(state: #state) -> Self::Machine {
+                        Machine(state, NoneEvent)
                     }
                 }
+            });
+        }
 
-                #states
-                #initial_states
-                #events
-                #machine_enum
-                #transitions
+        let mut targets = Vec::new();
+
+        for t in &self.machine.transitions.0 {
+            let state = &t.to.name;
+            let event = &t.event.name;
+
+            if targets.contains(&(state, event)) {
+                continue;
+            }
+
+            targets.push((state, event));
+
+            tokens.extend(quote! {
+                impl M for Machine<#state, #event> {
+                    type State = #state;
+                    type Event = #event;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        Option::Some(self.1.clone())
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// Predicates generates the `is` method of the machine type, checking its
+/// state against a state type, and an `is_<state>` shorthand for each state.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+struct Predicates<'a> {
+    machine: &'a Machine,
+    trigger: Trigger,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Predicates<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let states: Vec<Ident> = self.machine.states().0.into_iter().map(|s| s.name).collect();
+        let predicates: Vec<Ident> = states.iter().map(predicate).collect();
+
+        let generics = if self.trigger == Trigger::Erased {
+            quote! { <S: State + Identify<Id = StateId>> Machine<S> }
+        } else {
+            quote! { <S: State + Identify<Id = StateId>, E: Event> Machine<S, E> }
+        };
+
+        tokens.extend(quote! {
+            impl #generics {
+                #[inline]
+                pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                    S::ID == T::ID
+                }
+
+                #(
+                    #[inline]
+                    pub fn #predicates(&self) -> bool {
+                        self.is::<#states>()
+                    }
+                )*
             }
         });
     }
 }
 
+/// predicate returns the name of the method checking whether a machine is in
+/// the given state, for example `is_locked` for `Locked`.
+fn predicate(state: &Ident) -> Ident {
+    Ident::new(&format!("is_{}", snake_case(&state.to_string())), Span::call_site())
+}
+
+/// Constructors generates a constructor for each initial state of the
+/// machine, named after it, for example `Machine::locked()` for `Locked`.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+struct Constructors<'a> {
+    machine: &'a Machine,
+    trigger: Trigger,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Constructors<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for state in &self.machine.initial_states.0 {
+            let state = &state.name;
+            let snake = snake_case(&state.to_string());
+
+            // A `New` state would clash with the `new` constructor.
+            if snake == "new" {
+                continue;
+            }
+
+            let constructor = Ident::new(&snake, Span::call_site());
+
+            tokens.extend(match self.trigger {
+                Trigger::Typed => quote! {
+                    impl Machine<#state, NoneEvent> {
+                        #[inline]
+                        pub fn #constructor() -> Self {
+                            Machine(#state, Option::None)
+                        }
+                    }
+                },
+                Trigger::Erased => quote! {
+                    impl Machine<#state> {
+                        #[inline]
+                        pub fn #constructor() -> Self {
+                            Machine(#state, Option::None)
+                        }
+                    }
+                },
+                Trigger::ZeroSized => quote! {
+                    impl Machine<#state, NoneEvent> {
+                        #[inline]
+                        pub fn #constructor() -> Self {
+                            Machine(#state, NoneEvent)
+                        }
+                    }
+                },
+            });
+        }
+    }
+}
+
+/// IntoState generates the `From` conversions from the machine type to each of
+/// its states, whatever event triggered it.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+struct IntoState<'a> {
+    machine: &'a Machine,
+    trigger: Trigger,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for IntoState<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for state in self.machine.states().0 {
+            let state = &state.name;
+
+            tokens.extend(if self.trigger == Trigger::Erased {
+                quote! {
+                    impl From<Machine<#state>> for #state {
+                        #[inline]
+                        fn from(machine: Machine<#state>) -> Self {
+                            machine.0
+                        }
+                    }
+                }
+            } else {
+                quote! {
+                    impl<E: Event> From<Machine<#state, E>> for #state {
+                        #[inline]
+                        fn from(machine: Machine<#state, E>) -> Self {
+                            machine.0
+                        }
+                    }
+                }
+            });
+        }
+    }
+}
+
 #[derive(Debug)]
 #[allow(single_use_lifetimes)]
 struct MachineEnum<'a> {
     machine: &'a Machine,
+    trigger: Trigger,
 }
 
 #[allow(single_use_lifetimes)]
@@ -189,51 +834,632 @@ impl<'a> ToTokens for MachineEnum<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let mut variants = Vec::new();
         let mut states = Vec::new();
-        let mut events = Vec::new();
+        let mut machines = Vec::new();
+        let mut triggers = Vec::new();
+        let mut from_ids = Vec::new();
+
+        // With an erased trigger, there is a variant per state, named after
+        // it, holding the trigger at runtime. Otherwise, there is a variant
+        // per state and trigger.
+        let erase = self.trigger == Trigger::Erased;
 
         for s in &self.machine.initial_states.0 {
             let name = s.name.clone();
-            let none = parse_quote! { NoneEvent };
-            let variant = Ident::new(&format!("Initial{}", name), Span::call_site());
+
+            let (variant, machine) = if erase {
+                (name.clone(), quote! { Machine<#name> })
+            } else {
+                let variant = Ident::new(&format!("Initial{}", name), Span::call_site());
+                (variant, quote! { Machine<#name, NoneEvent> })
+            };
+
+            let trigger = if self.trigger == Trigger::ZeroSized {
+                quote! { NoneEvent }
+            } else {
+                quote! { Option::None }
+            };
+
+            from_ids.push(quote! {
+                (StateId::#name, Option::None) => Option::Some(Variant::#variant(Machine(#name, #trigger))),
+            });
+
+            if erase && variants.contains(&variant) {
+                continue;
+            }
+
+            triggers.push(if erase {
+                quote! { Variant::#variant(ref m) => m.1 }
+            } else {
+                quote! { Variant::#variant(_) => Option::None }
+            });
 
             variants.push(variant);
             states.push(name);
-            events.push(none);
+            machines.push(machine);
         }
 
+        let mut targets = Vec::new();
+
         for t in &self.machine.transitions.0 {
             let state = t.to.name.clone();
             let event = t.event.name.clone();
-            let variant = Ident::new(&format!("{}By{}", state, event), Span::call_site());
+
+            if targets.contains(&(state.clone(), event.clone())) {
+                continue;
+            }
+
+            targets.push((state.clone(), event.clone()));
+
+            let (variant, machine, trigger) = match self.trigger {
+                Trigger::Erased => (
+                    state.clone(),
+                    quote! { Machine<#state> },
+                    quote! { Option::Some(EventId::#event) },
+                ),
+                Trigger::Typed => (
+                    Ident::new(&format!("{}By{}", state, event), Span::call_site()),
+                    quote! { Machine<#state, #event> },
+                    quote! { Option::Some(#event) },
+                ),
+                Trigger::ZeroSized => (
+                    Ident::new(&format!("{}By{}", state, event), Span::call_site()),
+                    quote! { Machine<#state, #event> },
+                    quote! { #event },
+                ),
+            };
+
+            from_ids.push(quote! {
+                (StateId::#state, Option::Some(EventId::#event)) => Option::Some(Variant::#variant(Machine(#state, #trigger))),
+            });
 
             if variants.contains(&variant) {
                 continue;
             }
 
+            triggers.push(if erase {
+                quote! { Variant::#variant(ref m) => m.1 }
+            } else {
+                quote! { Variant::#variant(_) => Option::Some(EventId::#event) }
+            });
+
             variants.push(variant);
             states.push(state);
-            events.push(event);
+            machines.push(machine);
         }
 
+        let combinations =
+            self.machine.states().0.len() * (self.machine.events().0.len() + 1);
+        let fallback = if from_ids.len() < combinations {
+            quote! { _ => Option::None, }
+        } else {
+            TokenStream::new()
+        };
+
+        let all: Vec<Ident> = self.machine.states().0.into_iter().map(|s| s.name).collect();
+        let predicates: Vec<Ident> = all.iter().map(predicate).collect();
+
+        let visitor = Ident::new(&format!("{}Visitor", self.machine.name), Span::call_site());
+        let visits: Vec<Ident> = variants
+            .iter()
+            .map(|v| Ident::new(&format!("visit_{}", snake_case(&v.to_string())), Span::call_site()))
+            .collect();
+
         let variants = &variants;
+        let variants2 = variants;
         let states = &states;
-        let events = &events;
+        let machines = &machines;
+        let triggers = &triggers;
+        let from_ids = &from_ids;
+        let visits = &visits;
+
+        let attributes = &self.machine.attributes;
+
+        let (visitor_trait, visit) = if attributes.visitor {
+            (
+                quote! {
+                    pub trait #visitor {
+                        #(fn #visits(&mut self, machine: &#machines);)*
+                    }
+                },
+                quote! {
+                    pub fn visit<V: #visitor + ?Sized>(&self, visitor: &mut V) {
+                        match *self {
+                            #(Variant::#variants(ref m) => visitor.#visits(m)),*
+                        }
+                    }
+                },
+            )
+        } else {
+            (TokenStream::new(), TokenStream::new())
+        };
 
-        tokens.extend(quote!{
-            #[derive(Debug)]
-            pub enum Variant {
-                #(#variants(Machine<#states, #events>)),*
+        let transition_checked = if attributes.transition_checked {
+            let generics = if erase {
+                quote! { <S: State> Machine<S> }
+            } else {
+                quote! { <S: State, E: Event> Machine<S, E> }
+            };
+
+            quote! {
+                impl #generics where Self: AsEnum<Enum = Variant> {
+                    pub fn transition_checked(self, event: EventId) -> Result<Variant, TransitionError<StateId, EventId>> {
+                        self.as_enum().try_transition(event).map_err(|machine| {
+                            TransitionError::Invalid { state: machine.state_id(), event }
+                        })
+                    }
+                }
             }
+        } else {
+            TokenStream::new()
+        };
 
-            #(
-                impl AsEnum for Machine<#states, #events> {
-                    type Enum = Variant;
-
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::#variants(self)
+        let replay = if attributes.replay {
+            quote! {
+                pub fn replay(
+                    initial: StateId,
+                    events: impl IntoIterator<Item = EventId>,
+                ) -> Result<Self, ReplayError<StateId, EventId>> {
+                    let mut machine = Variant::from_ids(initial, Option::None)
+                        .ok_or(ReplayError::NotInitial(initial))?;
+
+                    for (step, event) in events.into_iter().enumerate() {
+                        machine = machine.try_transition(event).map_err(|machine| {
+                            ReplayError::Rejected { step, state: machine.state_id(), event }
+                        })?;
+                    }
+
+                    Result::Ok(machine)
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let fold_events = if attributes.fold_events {
+            quote! {
+                pub fn fold_events(
+                    self,
+                    events: impl IntoIterator<Item = EventId>,
+                ) -> Result<Self, (Self, TransitionError<StateId, EventId>)> {
+                    events.into_iter().try_fold(self, |machine, event| {
+                        machine.try_transition(event).map_err(|machine| {
+                            let state = machine.state_id();
+                            (machine, TransitionError::Invalid { state, event })
+                        })
+                    })
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let drive = if attributes.drive {
+            quote! {
+                pub fn drive<I: IntoIterator<Item = EventId>>(self, events: I) -> ::sm::Snapshots<Self, I::IntoIter> {
+                    ::sm::Snapshots::new(self, events.into_iter())
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let apply = if self.machine.apply() {
+            quote! {
+                pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    let state = self.state_id();
+
+                    match self.prepare(event) {
+                        Option::Some(prepared) => {
+                            let _ = prepared.commit();
+                            Result::Ok(())
+                        }
+                        Option::None => Result::Err(TransitionError::Invalid { state, event }),
+                    }
+                }
+
+                pub fn apply_named(&mut self, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                    match EventId::from_name(name) {
+                        Option::Some(event) => self.apply(event),
+                        Option::None => Result::Err(TransitionError::UnknownEvent),
+                    }
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote!{
+            #[derive(Clone, Debug)]
+            pub enum Variant {
+                #(#variants(#machines)),*
+            }
+
+            #(
+                impl AsEnum for #machines {
+                    type Enum = Variant;
+
+                    #[inline]
+                    fn as_enum(self) -> Self::Enum {
+                        Variant::#variants(self)
+                    }
+                }
+            )*
+
+            #[derive(Clone, Copy, Debug)]
+            pub enum VariantRef<'a> {
+                #(#variants(&'a #machines)),*
+            }
+
+            #(
+                impl #machines {
+                    #[inline]
+                    pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                        VariantRef::#variants(self)
                     }
                 }
             )*
+
+            #visitor_trait
+            #transition_checked
+
+            impl Variant {
+                #[inline]
+                pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                    match *self {
+                        #(Variant::#variants(ref m) => VariantRef::#variants2(m)),*
+                    }
+                }
+
+                #visit
+
+                #[inline]
+                pub fn state_id(&self) -> StateId {
+                    match *self {
+                        #(Variant::#variants(_) => StateId::#states),*
+                    }
+                }
+
+                #[inline]
+                pub fn same_state(&self, other: &Variant) -> bool {
+                    self.state_id() == other.state_id()
+                }
+
+                #[inline]
+                pub fn trigger_id(&self) -> Option<EventId> {
+                    match *self {
+                        #(#triggers),*
+                    }
+                }
+
+                #[inline]
+                pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                    self.state_id() == T::ID
+                }
+
+                #(
+                    #[inline]
+                    pub fn #predicates(&self) -> bool {
+                        self.state_id() == StateId::#all
+                    }
+                )*
+
+                pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                    match (state, trigger) {
+                        #(#from_ids)*
+                        #fallback
+                    }
+                }
+
+                #replay
+                #fold_events
+                #drive
+                #apply
+            }
+        });
+
+        let mut arms = Vec::new();
+
+        for event in &self.machine.events().0 {
+            let event = &event.name;
+            let matched: Vec<&Ident> = variants
+                .iter()
+                .zip(states)
+                .filter(|(_, state)| {
+                    self.machine
+                        .transitions
+                        .0
+                        .iter()
+                        .any(|t| t.event.name == *event && t.from.name == **state)
+                })
+                .map(|(variant, _)| variant)
+                .collect();
+
+            arms.push(quote! {
+                EventId::#event => self.try_transition(#event),
+            });
+
+            if matched.is_empty() {
+                tokens.extend(quote! {
+                    impl TryTransition<#event> for Variant {
+                        fn try_transition(self, _: #event) -> Result<Self, Self> {
+                            Result::Err(self)
+                        }
+                    }
+                });
+
+                continue;
+            }
+
+            let fallback = if matched.len() < variants.len() {
+                quote! { machine => Result::Err(machine), }
+            } else {
+                TokenStream::new()
+            };
+
+            tokens.extend(quote! {
+                impl TryTransition<#event> for Variant {
+                    fn try_transition(self, event: #event) -> Result<Self, Self> {
+                        match self {
+                            #(Variant::#matched(m) => Result::Ok(m.transition(event).as_enum()),)*
+                            #fallback
+                        }
+                    }
+                }
+            });
+        }
+
+        // A machine without events can't reject any.
+        if instrument::REJECTIONS && !arms.is_empty() {
+            tokens.extend(quote! {
+                impl TryTransition<EventId> for Variant {
+                    fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                        let result = match event {
+                            #(#arms)*
+                        };
+
+                        if let Result::Err(ref machine) = result {
+                            record_rejection(machine.state_id(), event);
+                        }
+
+                        result
+                    }
+                }
+            });
+
+            return;
+        }
+
+        tokens.extend(quote! {
+            impl TryTransition<EventId> for Variant {
+                fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                    match event {
+                        #(#arms)*
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// NextState generates the `next_state` constant function of a machine, the
+/// transition table as an array indexed by the `StateId` and `EventId` of a
+/// transition, for constant expressions where `Table::next` can't be called.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct NextState<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for NextState<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let states = self.machine.states().0;
+        let events = self.machine.events().0;
+        let mut next = Vec::new();
+
+        for state in &states {
+            for event in &events {
+                let to = self
+                    .machine
+                    .transitions
+                    .0
+                    .iter()
+                    .find(|t| t.from.name == state.name && t.event.name == event.name)
+                    .map(|t| &t.to.name);
+
+                next.push(match to {
+                    Some(to) => quote! { Option::Some(StateId::#to) },
+                    None => quote! { Option::None },
+                });
+            }
+        }
+
+        let len = next.len();
+        let width = events.len();
+
+        tokens.extend(quote! {
+            pub const fn next_state(state: StateId, event: EventId) -> Option<StateId> {
+                const NEXT: [Option<StateId>; #len] = [#(#next),*];
+
+                NEXT[state as usize * #width + event as usize]
+            }
+        });
+    }
+}
+
+/// ValidateSequence generates the `validate_sequence` function of a machine
+/// marked `#[validate_sequence]`, which follows a sequence of events from a
+/// state through the transition table, without creating a machine.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct ValidateSequence<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for ValidateSequence<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let mut arms = Vec::new();
+
+        for t in &self.machine.transitions.0 {
+            let from = &t.from.name;
+            let event = &t.event.name;
+            let to = &t.to.name;
+
+            arms.push(quote! {
+                (StateId::#from, EventId::#event) => StateId::#to,
+            });
+        }
+
+        // Without transitions, any event is invalid, and a loop matching on
+        // the events would be unreachable code.
+        if arms.is_empty() {
+            tokens.extend(quote! {
+                pub fn validate_sequence(initial: StateId, events: &[EventId]) -> Result<StateId, ::sm::InvalidAt<StateId, EventId>> {
+                    match events.first() {
+                        Option::Some(&event) => Result::Err(::sm::InvalidAt { step: 0, state: initial, event }),
+                        Option::None => Result::Ok(initial),
+                    }
+                }
+            });
+
+            return;
+        }
+
+        // The position of the event is only needed to report an invalid one.
+        let combinations = self.machine.states().0.len() * self.machine.events().0.len();
+        let (events, fallback) = if arms.len() < combinations {
+            (
+                quote! { (step, &event) in events.iter().enumerate() },
+                quote! { _ => return Result::Err(::sm::InvalidAt { step, state, event }), },
+            )
+        } else {
+            (quote! { &event in events }, TokenStream::new())
+        };
+
+        tokens.extend(quote! {
+            pub fn validate_sequence(initial: StateId, events: &[EventId]) -> Result<StateId, ::sm::InvalidAt<StateId, EventId>> {
+                let mut state = initial;
+
+                for #events {
+                    state = match (state, event) {
+                        #(#arms)*
+                        #fallback
+                    };
+                }
+
+                Result::Ok(state)
+            }
+        });
+    }
+}
+
+/// MachineTable implements the `Table` trait for the `ty` type of a machine,
+/// which has to provide a `state_id` method. It is generated for dynamic
+/// machines, and for machines marked `#[table]` or using anything built on
+/// top of the trait.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct MachineTable<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for MachineTable<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name: String = self.machine.name.to_string();
+        let ty = &self.ty;
+        let mut arms = Vec::new();
+        let mut weights = Vec::new();
+
+        for t in &self.machine.transitions.0 {
+            let from = &t.from.name;
+            let event = &t.event.name;
+            let to = &t.to.name;
+
+            arms.push(quote! {
+                (StateId::#from, EventId::#event) => Option::Some(StateId::#to),
+            });
+
+            if let Some(weight) = t.weight {
+                weights.push(quote! {
+                    (StateId::#from, EventId::#event) => #weight,
+                });
+            }
+        }
+
+        if arms.is_empty() {
+            tokens.extend(quote! {
+                impl Table for #ty {
+                    type StateId = StateId;
+                    type EventId = EventId;
+
+                    const NAME: &'static str = #name;
+
+                    #[inline]
+                    fn state(&self) -> StateId {
+                        self.state_id()
+                    }
+
+                    fn next(_: StateId, _: EventId) -> Option<StateId> {
+                        Option::None
+                    }
+                }
+            });
+
+            return;
+        }
+
+        let combinations = self.machine.states().0.len() * self.machine.events().0.len();
+        let fallback = if arms.len() < combinations {
+            quote! { _ => Option::None, }
+        } else {
+            TokenStream::new()
+        };
+
+        // Transitions without a weight keep the default weight of the `Table`
+        // trait, so the method is only generated for weighted machines.
+        let weight = if weights.is_empty() {
+            TokenStream::new()
+        } else {
+            let fallback = if weights.len() < combinations {
+                quote! { _ => 1, }
+            } else {
+                TokenStream::new()
+            };
+
+            quote! {
+                fn weight(state: StateId, event: EventId) -> u32 {
+                    match (state, event) {
+                        #(#weights)*
+                        #fallback
+                    }
+                }
+            }
+        };
+
+        tokens.extend(quote! {
+            impl Table for #ty {
+                type StateId = StateId;
+                type EventId = EventId;
+
+                const NAME: &'static str = #name;
+
+                #[inline]
+                fn state(&self) -> StateId {
+                    self.state_id()
+                }
+
+                fn next(state: StateId, event: EventId) -> Option<StateId> {
+                    match (state, event) {
+                        #(#arms)*
+                        #fallback
+                    }
+                }
+
+                #weight
+            }
         });
     }
 }
@@ -261,6 +1487,8 @@ mod tests {
 
         let right = Machine {
             name: parse_quote! { TurnStile },
+            attributes: Default::default(),
+            externals: Default::default(),
             initial_states: InitialStates(vec![
                 InitialState {
                     name: parse_quote! { Locked },
@@ -280,6 +1508,7 @@ mod tests {
                     to: State {
                         name: parse_quote! { Unlocked },
                     },
+                    weight: None,
                 },
                 Transition {
                     event: Event {
@@ -291,6 +1520,7 @@ mod tests {
                     to: State {
                         name: parse_quote! { Locked },
                     },
+                    weight: None,
                 },
             ]),
         };
@@ -302,6 +1532,8 @@ mod tests {
     fn test_machine_to_tokens() {
         let machine = Machine {
             name: parse_quote! { TurnStile },
+            attributes: Default::default(),
+            externals: Default::default(),
             initial_states: InitialStates(vec![
                 InitialState {
                     name: parse_quote! { Unlocked },
@@ -320,126 +1552,1548 @@ mod tests {
                 to: State {
                     name: parse_quote! { Locked },
                 },
+                weight: None,
             }]),
         };
 
         let left = quote! {
-            #[allow(non_snake_case)]
+            #[allow(dead_code, non_snake_case, unused_imports)]
             mod TurnStile {
-                use sm::{AsEnum, Event, InitialState, Initializer, Machine as M, NoneEvent, State, Transition};
+                use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+
+                pub use self::events::*;
+                pub use self::states::*;
+                pub use self::variant::*;
 
-                #[derive(Debug, Eq, PartialEq)]
+                #[derive(Clone, Eq, PartialEq)]
                 pub struct Machine<S: State, E: Event>(S, Option<E>);
 
+                impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                    fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                        f.debug_struct("TurnStile::Machine")
+                            .field("state", &self.0)
+                            .field("trigger", &self.1)
+                            .finish()
+                    }
+                }
+
                 impl<S: State, E: Event> M for Machine<S, E> {
                     type State = S;
                     type Event = E;
 
-                    fn state(&self) -> Self::State {
-                        self.0.clone()
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        self.1.clone()
+                    }
+                }
+
+                impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
+                    type Machine = Machine<S, NoneEvent>;
+
+                    #[inline]
+                    fn new(state: S) -> Self::Machine {
+                        Machine(state, Option::None)
+                    }
+                }
+
+                impl Machine<Unlocked, NoneEvent> {
+                    #[inline]
+                    pub fn unlocked() -> Self {
+                        Machine(Unlocked, Option::None)
+                    }
+                }
+
+                impl Machine<Locked, NoneEvent> {
+                    #[inline]
+                    pub fn locked() -> Self {
+                        Machine(Locked, Option::None)
+                    }
+                }
+
+                impl <S: State + Identify<Id = StateId>, E: Event> Machine<S, E> {
+                    #[inline]
+                    pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                        S::ID == T::ID
+                    }
+
+                    #[inline]
+                    pub fn is_unlocked(&self) -> bool {
+                        self.is::<Unlocked>()
+                    }
+
+                    #[inline]
+                    pub fn is_locked(&self) -> bool {
+                        self.is::<Locked>()
+                    }
+                }
+
+                impl<E: Event> From<Machine<Unlocked, E>> for Unlocked {
+                    #[inline]
+                    fn from(machine: Machine<Unlocked, E>) -> Self {
+                        machine.0
+                    }
+                }
+
+                impl<E: Event> From<Machine<Locked, E>> for Locked {
+                    #[inline]
+                    fn from(machine: Machine<Locked, E>) -> Self {
+                        machine.0
+                    }
+                }
+
+                mod states {
+                    use super::*;
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `Push` leads to `Locked`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Unlocked;
+                    impl State for Unlocked {}
+
+                    pub const UNLOCKED: Unlocked = Unlocked;
+
+                    impl From<Unlocked> for StateId {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            StateId::Unlocked
+                        }
+                    }
+
+                    impl From<Unlocked> for &'static str {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            "Unlocked"
+                        }
+                    }
+
+                    impl Identify for Unlocked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Unlocked;
+                    }
+
+                    impl PartialEq<Unlocked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Unlocked) -> bool {
+                            true
+                        }
+                    }
+
+                    impl PartialEq<Locked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Locked) -> bool {
+                            false
+                        }
+                    }
+
+                    #[doc = "Terminal: no event transitions out of this state."]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Unlocked` via `Push`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Locked;
+                    impl State for Locked {}
+
+                    pub const LOCKED: Locked = Locked;
+
+                    impl From<Locked> for StateId {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            StateId::Locked
+                        }
+                    }
+
+                    impl From<Locked> for &'static str {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            "Locked"
+                        }
+                    }
+
+                    impl Identify for Locked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Locked;
+                    }
+
+                    impl PartialEq<Unlocked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Unlocked) -> bool {
+                            false
+                        }
+                    }
+
+                    impl PartialEq<Locked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Locked) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum StateId {
+                        Unlocked,
+                        Locked
+                    }
+
+                    impl Id for StateId {
+                        const ALL: &'static [Self] = &[StateId::Unlocked, StateId::Locked];
+                        const NAMES: &'static [&'static str] = &["Unlocked", "Locked"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                StateId::Unlocked => 0usize,
+                                StateId::Locked => 1usize
+                            }
+                        }
+                    }
+
+                    impl From<StateId> for &'static str {
+                        #[inline]
+                        fn from(id: StateId) -> Self {
+                            id.name()
+                        }
+                    }
+
+                    impl InitialState for Unlocked {}
+                    impl InitialState for Locked {}
+                }
+
+                mod events {
+                    use super::*;
+
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Push;
+                    impl Event for Push {}
+
+                    pub const PUSH: Push = Push;
+
+                    impl From<Push> for EventId {
+                        #[inline]
+                        fn from(_: Push) -> Self {
+                            EventId::Push
+                        }
+                    }
+
+                    impl From<Push> for &'static str {
+                        #[inline]
+                        fn from(_: Push) -> Self {
+                            "Push"
+                        }
+                    }
+
+                    impl Identify for Push {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::Push;
+                    }
+
+                    impl PartialEq<Push> for Push {
+                        #[inline]
+                        fn eq(&self, _: &Push) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum EventId {
+                        Push
+                    }
+
+                    impl Id for EventId {
+                        const ALL: &'static [Self] = &[EventId::Push];
+                        const NAMES: &'static [&'static str] = &["Push"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                EventId::Push => 0usize
+                            }
+                        }
+                    }
+
+                    impl From<EventId> for &'static str {
+                        #[inline]
+                        fn from(id: EventId) -> Self {
+                            id.name()
+                        }
+                    }
+                }
+
+                mod variant {
+                    use super::*;
+
+                    #[derive(Clone, Debug)]
+                    pub enum Variant {
+                        InitialUnlocked(Machine<Unlocked, NoneEvent>),
+                        InitialLocked(Machine<Locked, NoneEvent>),
+                        LockedByPush(Machine<Locked, Push>)
+                    }
+
+                    impl AsEnum for Machine<Unlocked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Locked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialLocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Locked, Push> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::LockedByPush(self)
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug)]
+                    pub enum VariantRef<'a> {
+                        InitialUnlocked(&'a Machine<Unlocked, NoneEvent>),
+                        InitialLocked(&'a Machine<Locked, NoneEvent>),
+                        LockedByPush(&'a Machine<Locked, Push>)
+                    }
+
+                    impl Machine<Unlocked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl Machine<Locked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialLocked(self)
+                        }
+                    }
+
+                    impl Machine<Locked, Push> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::LockedByPush(self)
+                        }
+                    }
+
+                    impl Variant {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            match *self {
+                                Variant::InitialUnlocked(ref m) => VariantRef::InitialUnlocked(m),
+                                Variant::InitialLocked(ref m) => VariantRef::InitialLocked(m),
+                                Variant::LockedByPush(ref m) => VariantRef::LockedByPush(m)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn state_id(&self) -> StateId {
+                            match *self {
+                                Variant::InitialUnlocked(_) => StateId::Unlocked,
+                                Variant::InitialLocked(_) => StateId::Locked,
+                                Variant::LockedByPush(_) => StateId::Locked
+                            }
+                        }
+
+                        #[inline]
+                        pub fn same_state(&self, other: &Variant) -> bool {
+                            self.state_id() == other.state_id()
+                        }
+
+                        #[inline]
+                        pub fn trigger_id(&self) -> Option<EventId> {
+                            match *self {
+                                Variant::InitialUnlocked(_) => Option::None,
+                                Variant::InitialLocked(_) => Option::None,
+                                Variant::LockedByPush(_) => Option::Some(EventId::Push)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                            self.state_id() == T::ID
+                        }
+
+                        #[inline]
+                        pub fn is_unlocked(&self) -> bool {
+                            self.state_id() == StateId::Unlocked
+                        }
+
+                        #[inline]
+                        pub fn is_locked(&self) -> bool {
+                            self.state_id() == StateId::Locked
+                        }
+
+                        pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                            match (state, trigger) {
+                                (StateId::Unlocked, Option::None) => Option::Some(Variant::InitialUnlocked(Machine(Unlocked, Option::None))),
+                                (StateId::Locked, Option::None) => Option::Some(Variant::InitialLocked(Machine(Locked, Option::None))),
+                                (StateId::Locked, Option::Some(EventId::Push)) => Option::Some(Variant::LockedByPush(Machine(Locked, Option::Some(Push)))),
+                                _ => Option::None,
+                            }
+                        }
+                    }
+
+                    impl TryTransition<Push> for Variant {
+                        fn try_transition(self, event: Push) -> Result<Self, Self> {
+                            match self {
+                                Variant::InitialUnlocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                machine => Result::Err(machine),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<EventId> for Variant {
+                        fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                            match event {
+                                EventId::Push => self.try_transition(Push),
+                            }
+                        }
+                    }
+                }
+
+                mod transitions {
+                    use super::*;
+
+                    impl<E: Event> Transition<Push> for Machine<Unlocked, E> {
+                        type Machine = Machine<Locked, Push>;
+
+                        #[inline]
+                        fn transition(self, event: Push) -> Self::Machine {
+                            Machine(Locked, Some(event))
+                        }
+                    }
+
+                    impl Accepts<Push> for Unlocked {
+                        type Next = Locked;
+                    }
+                }
+
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    pub use super::variant::*;
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        machine.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_machine_erased_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[erase_trigger]
+            Lock {
+                InitialStates { Locked, Unlocked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[allow(dead_code, non_snake_case, unused_imports)]
+            mod Lock {
+                use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Machine as M, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+
+                pub use self::events::*;
+                pub use self::states::*;
+                pub use self::variant::*;
+
+                #[derive(Clone, Eq, PartialEq)]
+                pub struct Machine<S: State>(S, Option<EventId>);
+
+                impl<S: State> ::sm::export::fmt::Debug for Machine<S> {
+                    fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                        f.debug_struct("Lock::Machine")
+                            .field("state", &self.0)
+                            .field("trigger", &self.1)
+                            .finish()
+                    }
+                }
+
+                impl Event for EventId {}
+
+                impl<S: State> M for Machine<S> {
+                    type State = S;
+                    type Event = EventId;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        self.1
+                    }
+                }
+
+                impl<S: InitialState> Machine<S> {
+                    #[inline]
+                    pub fn new(state: S) -> Self {
+                        Machine(state, Option::None)
+                    }
+                }
+
+                impl Machine<Locked> {
+                    #[inline]
+                    pub fn locked() -> Self {
+                        Machine(Locked, Option::None)
+                    }
+                }
+
+                impl Machine<Unlocked> {
+                    #[inline]
+                    pub fn unlocked() -> Self {
+                        Machine(Unlocked, Option::None)
+                    }
+                }
+
+                impl <S: State + Identify<Id = StateId>> Machine<S> {
+                    #[inline]
+                    pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                        S::ID == T::ID
+                    }
+
+                    #[inline]
+                    pub fn is_locked(&self) -> bool {
+                        self.is::<Locked>()
+                    }
+
+                    #[inline]
+                    pub fn is_unlocked(&self) -> bool {
+                        self.is::<Unlocked>()
+                    }
+                }
+
+                impl From<Machine<Locked>> for Locked {
+                    #[inline]
+                    fn from(machine: Machine<Locked>) -> Self {
+                        machine.0
+                    }
+                }
+
+                impl From<Machine<Unlocked>> for Unlocked {
+                    #[inline]
+                    fn from(machine: Machine<Unlocked>) -> Self {
+                        machine.0
+                    }
+                }
+
+                mod states {
+                    use super::*;
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Unlocked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Unlocked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Locked;
+                    impl State for Locked {}
+
+                    pub const LOCKED: Locked = Locked;
+
+                    impl From<Locked> for StateId {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            StateId::Locked
+                        }
+                    }
+
+                    impl From<Locked> for &'static str {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            "Locked"
+                        }
+                    }
+
+                    impl Identify for Locked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Locked;
+                    }
+
+                    impl PartialEq<Locked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Locked) -> bool {
+                            true
+                        }
+                    }
+
+                    impl PartialEq<Unlocked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Unlocked) -> bool {
+                            false
+                        }
+                    }
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Locked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Locked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Unlocked;
+                    impl State for Unlocked {}
+
+                    pub const UNLOCKED: Unlocked = Unlocked;
+
+                    impl From<Unlocked> for StateId {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            StateId::Unlocked
+                        }
+                    }
+
+                    impl From<Unlocked> for &'static str {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            "Unlocked"
+                        }
+                    }
+
+                    impl Identify for Unlocked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Unlocked;
+                    }
+
+                    impl PartialEq<Locked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Locked) -> bool {
+                            false
+                        }
+                    }
+
+                    impl PartialEq<Unlocked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Unlocked) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum StateId {
+                        Locked,
+                        Unlocked
+                    }
+
+                    impl Id for StateId {
+                        const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                        const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                StateId::Locked => 0usize,
+                                StateId::Unlocked => 1usize
+                            }
+                        }
+                    }
+
+                    impl From<StateId> for &'static str {
+                        #[inline]
+                        fn from(id: StateId) -> Self {
+                            id.name()
+                        }
+                    }
+
+                    impl InitialState for Locked {}
+                    impl InitialState for Unlocked {}
+                }
+
+                mod events {
+                    use super::*;
+
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct TurnKey;
+                    impl Event for TurnKey {}
+
+                    pub const TURN_KEY: TurnKey = TurnKey;
+
+                    impl From<TurnKey> for EventId {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            EventId::TurnKey
+                        }
+                    }
+
+                    impl From<TurnKey> for &'static str {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            "TurnKey"
+                        }
+                    }
+
+                    impl Identify for TurnKey {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::TurnKey;
+                    }
+
+                    impl PartialEq<TurnKey> for TurnKey {
+                        #[inline]
+                        fn eq(&self, _: &TurnKey) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum EventId {
+                        TurnKey
+                    }
+
+                    impl Id for EventId {
+                        const ALL: &'static [Self] = &[EventId::TurnKey];
+                        const NAMES: &'static [&'static str] = &["TurnKey"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                EventId::TurnKey => 0usize
+                            }
+                        }
+                    }
+
+                    impl From<EventId> for &'static str {
+                        #[inline]
+                        fn from(id: EventId) -> Self {
+                            id.name()
+                        }
+                    }
+                }
+
+                mod variant {
+                    use super::*;
+
+                    #[derive(Clone, Debug)]
+                    pub enum Variant {
+                        Locked(Machine<Locked>),
+                        Unlocked(Machine<Unlocked>)
+                    }
+
+                    impl AsEnum for Machine<Locked> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::Locked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::Unlocked(self)
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug)]
+                    pub enum VariantRef<'a> {
+                        Locked(&'a Machine<Locked>),
+                        Unlocked(&'a Machine<Unlocked>)
+                    }
+
+                    impl Machine<Locked> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::Locked(self)
+                        }
+                    }
+
+                    impl Machine<Unlocked> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::Unlocked(self)
+                        }
+                    }
+
+                    impl Variant {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            match *self {
+                                Variant::Locked(ref m) => VariantRef::Locked(m),
+                                Variant::Unlocked(ref m) => VariantRef::Unlocked(m)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn state_id(&self) -> StateId {
+                            match *self {
+                                Variant::Locked(_) => StateId::Locked,
+                                Variant::Unlocked(_) => StateId::Unlocked
+                            }
+                        }
+
+                        #[inline]
+                        pub fn same_state(&self, other: &Variant) -> bool {
+                            self.state_id() == other.state_id()
+                        }
+
+                        #[inline]
+                        pub fn trigger_id(&self) -> Option<EventId> {
+                            match *self {
+                                Variant::Locked(ref m) => m.1,
+                                Variant::Unlocked(ref m) => m.1
+                            }
+                        }
+
+                        #[inline]
+                        pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                            self.state_id() == T::ID
+                        }
+
+                        #[inline]
+                        pub fn is_locked(&self) -> bool {
+                            self.state_id() == StateId::Locked
+                        }
+
+                        #[inline]
+                        pub fn is_unlocked(&self) -> bool {
+                            self.state_id() == StateId::Unlocked
+                        }
+
+                        pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                            match (state, trigger) {
+                                (StateId::Locked, Option::None) => Option::Some(Variant::Locked(Machine(Locked, Option::None))),
+                                (StateId::Unlocked, Option::None) => Option::Some(Variant::Unlocked(Machine(Unlocked, Option::None))),
+                                (StateId::Unlocked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::Unlocked(Machine(Unlocked, Option::Some(EventId::TurnKey)))),
+                                (StateId::Locked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::Locked(Machine(Locked, Option::Some(EventId::TurnKey)))),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<TurnKey> for Variant {
+                        fn try_transition(self, event: TurnKey) -> Result<Self, Self> {
+                            match self {
+                                Variant::Locked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::Unlocked(m) => Result::Ok(m.transition(event).as_enum()),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<EventId> for Variant {
+                        fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                            match event {
+                                EventId::TurnKey => self.try_transition(TurnKey),
+                            }
+                        }
+                    }
+                }
+
+                mod transitions {
+                    use super::*;
+
+                    impl Transition<TurnKey> for Machine<Locked> {
+                        type Machine = Machine<Unlocked>;
+
+                        #[inline]
+                        fn transition(self, _: TurnKey) -> Self::Machine {
+                            Machine(Unlocked, Some(EventId::TurnKey))
+                        }
+                    }
+
+                    impl Transition<TurnKey> for Machine<Unlocked> {
+                        type Machine = Machine<Locked>;
+
+                        #[inline]
+                        fn transition(self, _: TurnKey) -> Self::Machine {
+                            Machine(Locked, Some(EventId::TurnKey))
+                        }
+                    }
+
+                    impl Accepts<TurnKey> for Locked {
+                        type Next = Unlocked;
+                    }
+
+                    impl Accepts<TurnKey> for Unlocked {
+                        type Next = Locked;
+                    }
+                }
+
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    pub use super::variant::*;
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        machine.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_machine_zero_sized_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[zero_sized]
+            Lock {
+                InitialStates { Locked, Unlocked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[allow(dead_code, non_snake_case, unused_imports)]
+            mod Lock {
+                use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+
+                pub use self::events::*;
+                pub use self::states::*;
+                pub use self::variant::*;
+
+                #[derive(Clone, Eq, PartialEq)]
+                pub struct Machine<S: State, E: Event>(S, E);
+
+                impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                    fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                        f.debug_struct("Lock::Machine")
+                            .field("state", &self.0)
+                            .field("trigger", &self.1)
+                            .finish()
+                    }
+                }
+
+                impl M for Machine<Locked, NoneEvent> {
+                    type State = Locked;
+                    type Event = NoneEvent;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        Option::None
+                    }
+                }
+
+                impl Initializer<Locked> for Machine<Locked, NoneEvent> {
+                    type Machine = Machine<Locked, NoneEvent>;
+
+                    #[inline]
+                    fn new(state: Locked) -> Self::Machine {
+                        Machine(state, NoneEvent)
+                    }
+                }
+
+                impl M for Machine<Unlocked, NoneEvent> {
+                    type State = Unlocked;
+                    type Event = NoneEvent;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        Option::None
+                    }
+                }
+
+                impl Initializer<Unlocked> for Machine<Unlocked, NoneEvent> {
+                    type Machine = Machine<Unlocked, NoneEvent>;
+
+                    #[inline]
+                    fn new(state: Unlocked) -> Self::Machine {
+                        Machine(state, NoneEvent)
+                    }
+                }
+
+                impl M for Machine<Unlocked, TurnKey> {
+                    type State = Unlocked;
+                    type Event = TurnKey;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        Option::Some(self.1.clone())
+                    }
+                }
+
+                impl M for Machine<Locked, TurnKey> {
+                    type State = Locked;
+                    type Event = TurnKey;
+
+                    #[inline]
+                    fn state(&self) -> Self::State {
+                        self.0.clone()
+                    }
+
+                    #[inline]
+                    fn trigger(&self) -> Option<Self::Event> {
+                        Option::Some(self.1.clone())
+                    }
+                }
+
+                impl Machine<Locked, NoneEvent> {
+                    #[inline]
+                    pub fn locked() -> Self {
+                        Machine(Locked, NoneEvent)
+                    }
+                }
+
+                impl Machine<Unlocked, NoneEvent> {
+                    #[inline]
+                    pub fn unlocked() -> Self {
+                        Machine(Unlocked, NoneEvent)
+                    }
+                }
+
+                impl <S: State + Identify<Id = StateId>, E: Event> Machine<S, E> {
+                    #[inline]
+                    pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                        S::ID == T::ID
+                    }
+
+                    #[inline]
+                    pub fn is_locked(&self) -> bool {
+                        self.is::<Locked>()
+                    }
+
+                    #[inline]
+                    pub fn is_unlocked(&self) -> bool {
+                        self.is::<Unlocked>()
+                    }
+                }
+
+                impl<E: Event> From<Machine<Locked, E>> for Locked {
+                    #[inline]
+                    fn from(machine: Machine<Locked, E>) -> Self {
+                        machine.0
+                    }
+                }
+
+                impl<E: Event> From<Machine<Unlocked, E>> for Unlocked {
+                    #[inline]
+                    fn from(machine: Machine<Unlocked, E>) -> Self {
+                        machine.0
+                    }
+                }
+
+                mod states {
+                    use super::*;
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Unlocked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Unlocked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Locked;
+                    impl State for Locked {}
+
+                    pub const LOCKED: Locked = Locked;
+
+                    impl From<Locked> for StateId {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            StateId::Locked
+                        }
+                    }
+
+                    impl From<Locked> for &'static str {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            "Locked"
+                        }
+                    }
+
+                    impl Identify for Locked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Locked;
+                    }
+
+                    impl PartialEq<Locked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Locked) -> bool {
+                            true
+                        }
+                    }
+
+                    impl PartialEq<Unlocked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Unlocked) -> bool {
+                            false
+                        }
+                    }
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Locked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Locked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Unlocked;
+                    impl State for Unlocked {}
+
+                    pub const UNLOCKED: Unlocked = Unlocked;
+
+                    impl From<Unlocked> for StateId {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            StateId::Unlocked
+                        }
+                    }
+
+                    impl From<Unlocked> for &'static str {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            "Unlocked"
+                        }
+                    }
+
+                    impl Identify for Unlocked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Unlocked;
+                    }
+
+                    impl PartialEq<Locked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Locked) -> bool {
+                            false
+                        }
+                    }
+
+                    impl PartialEq<Unlocked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Unlocked) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum StateId {
+                        Locked,
+                        Unlocked
+                    }
+
+                    impl Id for StateId {
+                        const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                        const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                StateId::Locked => 0usize,
+                                StateId::Unlocked => 1usize
+                            }
+                        }
+                    }
+
+                    impl From<StateId> for &'static str {
+                        #[inline]
+                        fn from(id: StateId) -> Self {
+                            id.name()
+                        }
+                    }
+
+                    impl InitialState for Locked {}
+                    impl InitialState for Unlocked {}
+                }
+
+                mod events {
+                    use super::*;
+
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct TurnKey;
+                    impl Event for TurnKey {}
+
+                    pub const TURN_KEY: TurnKey = TurnKey;
+
+                    impl From<TurnKey> for EventId {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            EventId::TurnKey
+                        }
+                    }
+
+                    impl From<TurnKey> for &'static str {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            "TurnKey"
+                        }
+                    }
+
+                    impl Identify for TurnKey {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::TurnKey;
+                    }
+
+                    impl PartialEq<TurnKey> for TurnKey {
+                        #[inline]
+                        fn eq(&self, _: &TurnKey) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum EventId {
+                        TurnKey
+                    }
+
+                    impl Id for EventId {
+                        const ALL: &'static [Self] = &[EventId::TurnKey];
+                        const NAMES: &'static [&'static str] = &["TurnKey"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                EventId::TurnKey => 0usize
+                            }
+                        }
+                    }
+
+                    impl From<EventId> for &'static str {
+                        #[inline]
+                        fn from(id: EventId) -> Self {
+                            id.name()
+                        }
+                    }
+                }
+
+                mod variant {
+                    use super::*;
+
+                    #[derive(Clone, Debug)]
+                    pub enum Variant {
+                        InitialLocked(Machine<Locked, NoneEvent>),
+                        InitialUnlocked(Machine<Unlocked, NoneEvent>),
+                        UnlockedByTurnKey(Machine<Unlocked, TurnKey>),
+                        LockedByTurnKey(Machine<Locked, TurnKey>)
+                    }
+
+                    impl AsEnum for Machine<Locked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialLocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, TurnKey> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::UnlockedByTurnKey(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Locked, TurnKey> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::LockedByTurnKey(self)
+                        }
                     }
 
-                    fn trigger(&self) -> Option<Self::Event> {
-                        self.1.clone()
+                    #[derive(Clone, Copy, Debug)]
+                    pub enum VariantRef<'a> {
+                        InitialLocked(&'a Machine<Locked, NoneEvent>),
+                        InitialUnlocked(&'a Machine<Unlocked, NoneEvent>),
+                        UnlockedByTurnKey(&'a Machine<Unlocked, TurnKey>),
+                        LockedByTurnKey(&'a Machine<Locked, TurnKey>)
                     }
-                }
 
-                impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
-                    type Machine = Machine<S, NoneEvent>;
+                    impl Machine<Locked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialLocked(self)
+                        }
+                    }
 
-                    fn new(state: S) -> Self::Machine {
-                        Machine(state, Option::None)
+                    impl Machine<Unlocked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialUnlocked(self)
+                        }
                     }
-                }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Unlocked;
-                impl State for Unlocked {}
+                    impl Machine<Unlocked, TurnKey> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::UnlockedByTurnKey(self)
+                        }
+                    }
 
-                impl PartialEq<Unlocked> for Unlocked {
-                    fn eq(&self, _: & Unlocked) -> bool {
-                        true
+                    impl Machine<Locked, TurnKey> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::LockedByTurnKey(self)
+                        }
                     }
-                }
 
-                impl PartialEq<Locked> for Unlocked {
-                    fn eq(&self, _: & Locked) -> bool {
-                        false
+                    impl Variant {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            match *self {
+                                Variant::InitialLocked(ref m) => VariantRef::InitialLocked(m),
+                                Variant::InitialUnlocked(ref m) => VariantRef::InitialUnlocked(m),
+                                Variant::UnlockedByTurnKey(ref m) => VariantRef::UnlockedByTurnKey(m),
+                                Variant::LockedByTurnKey(ref m) => VariantRef::LockedByTurnKey(m)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn state_id(&self) -> StateId {
+                            match *self {
+                                Variant::InitialLocked(_) => StateId::Locked,
+                                Variant::InitialUnlocked(_) => StateId::Unlocked,
+                                Variant::UnlockedByTurnKey(_) => StateId::Unlocked,
+                                Variant::LockedByTurnKey(_) => StateId::Locked
+                            }
+                        }
+
+                        #[inline]
+                        pub fn same_state(&self, other: &Variant) -> bool {
+                            self.state_id() == other.state_id()
+                        }
+
+                        #[inline]
+                        pub fn trigger_id(&self) -> Option<EventId> {
+                            match *self {
+                                Variant::InitialLocked(_) => Option::None,
+                                Variant::InitialUnlocked(_) => Option::None,
+                                Variant::UnlockedByTurnKey(_) => Option::Some(EventId::TurnKey),
+                                Variant::LockedByTurnKey(_) => Option::Some(EventId::TurnKey)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                            self.state_id() == T::ID
+                        }
+
+                        #[inline]
+                        pub fn is_locked(&self) -> bool {
+                            self.state_id() == StateId::Locked
+                        }
+
+                        #[inline]
+                        pub fn is_unlocked(&self) -> bool {
+                            self.state_id() == StateId::Unlocked
+                        }
+
+                        pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                            match (state, trigger) {
+                                (StateId::Locked, Option::None) => Option::Some(Variant::InitialLocked(Machine(Locked, NoneEvent))),
+                                (StateId::Unlocked, Option::None) => Option::Some(Variant::InitialUnlocked(Machine(Unlocked, NoneEvent))),
+                                (StateId::Unlocked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::UnlockedByTurnKey(Machine(Unlocked, TurnKey))),
+                                (StateId::Locked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::LockedByTurnKey(Machine(Locked, TurnKey))),
+                            }
+                        }
                     }
-                }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Locked;
-                impl State for Locked {}
+                    impl TryTransition<TurnKey> for Variant {
+                        fn try_transition(self, event: TurnKey) -> Result<Self, Self> {
+                            match self {
+                                Variant::InitialLocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::InitialUnlocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::UnlockedByTurnKey(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::LockedByTurnKey(m) => Result::Ok(m.transition(event).as_enum()),
+                            }
+                        }
+                    }
 
-                impl PartialEq<Unlocked> for Locked {
-                    fn eq(&self, _: &Unlocked) -> bool {
-                        false
+                    impl TryTransition<EventId> for Variant {
+                        fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                            match event {
+                                EventId::TurnKey => self.try_transition(TurnKey),
+                            }
+                        }
                     }
                 }
 
-                impl PartialEq<Locked> for Locked {
-                    fn eq(&self, _: &Locked) -> bool {
-                        true
+                mod transitions {
+                    use super::*;
+
+                    impl<E: Event> Transition<TurnKey> for Machine<Locked, E> {
+                        type Machine = Machine<Unlocked, TurnKey>;
+
+                        #[inline]
+                        fn transition(self, event: TurnKey) -> Self::Machine {
+                            Machine(Unlocked, event)
+                        }
                     }
-                }
 
-                impl InitialState for Unlocked {}
-                impl InitialState for Locked {}
+                    impl<E: Event> Transition<TurnKey> for Machine<Unlocked, E> {
+                        type Machine = Machine<Locked, TurnKey>;
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Push;
-                impl Event for Push {}
+                        #[inline]
+                        fn transition(self, event: TurnKey) -> Self::Machine {
+                            Machine(Locked, event)
+                        }
+                    }
+
+                    impl Accepts<TurnKey> for Locked {
+                        type Next = Unlocked;
+                    }
 
-                impl PartialEq<Push> for Push {
-                    fn eq(&self, _: &Push) -> bool {
-                        true
+                    impl Accepts<TurnKey> for Unlocked {
+                        type Next = Locked;
                     }
                 }
 
-                #[derive(Debug)]
-                pub enum Variant {
-                    InitialUnlocked(Machine<Unlocked, NoneEvent>),
-                    InitialLocked(Machine<Locked, NoneEvent>),
-                    LockedByPush(Machine<Locked, Push>)
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    pub use super::variant::*;
                 }
+            }
+        };
 
-                impl AsEnum for Machine<Unlocked, NoneEvent> {
-                    type Enum = Variant;
+        let mut right = TokenStream::new();
+        machine.to_tokens(&mut right);
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialUnlocked(self)
-                    }
-                }
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
 
-                impl AsEnum for Machine<Locked, NoneEvent> {
-                    type Enum = Variant;
+    #[test]
+    fn test_next_state_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
+                }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialLocked(self)
-                    }
+                Break {
+                    Locked => Broken
                 }
+            }
+        })
+        .unwrap();
 
-                impl AsEnum for Machine<Locked, Push> {
-                    type Enum = Variant;
+        let left = quote! {
+            pub const fn next_state(state: StateId, event: EventId) -> Option<StateId> {
+                const NEXT: [Option<StateId>; 6usize] = [
+                    Option::Some(StateId::Unlocked),
+                    Option::Some(StateId::Broken),
+                    Option::Some(StateId::Locked),
+                    Option::None,
+                    Option::None,
+                    Option::None
+                ];
+
+                NEXT[state as usize * 2usize + event as usize]
+            }
+        };
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::LockedByPush(self)
-                    }
+        let mut right = TokenStream::new();
+        NextState { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_validate_sequence_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey {
+                    Locked => Unlocked
+                    Unlocked => Locked
                 }
 
-                impl<E: Event> Transition<Push> for Machine<Unlocked, E> {
-                    type Machine = Machine<Locked, Push>;
+                Break {
+                    Locked => Broken
+                }
+            }
+        })
+        .unwrap();
 
-                    fn transition(self, event: Push) -> Self::Machine {
-                        Machine(Locked, Some(event))
-                    }
+        let left = quote! {
+            pub fn validate_sequence(initial: StateId, events: &[EventId]) -> Result<StateId, ::sm::InvalidAt<StateId, EventId>> {
+                let mut state = initial;
+
+                for (step, &event) in events.iter().enumerate() {
+                    state = match (state, event) {
+                        (StateId::Locked, EventId::TurnKey) => StateId::Unlocked,
+                        (StateId::Unlocked, EventId::TurnKey) => StateId::Locked,
+                        (StateId::Locked, EventId::Break) => StateId::Broken,
+                        _ => return Result::Err(::sm::InvalidAt { step, state, event }),
+                    };
                 }
+
+                Result::Ok(state)
             }
         };
 
         let mut right = TokenStream::new();
-        machine.to_tokens(&mut right);
+        ValidateSequence { machine: &machine }.to_tokens(&mut right);
 
         assert_eq!(format!("{}", left), format!("{}", right))
     }
@@ -467,6 +3121,8 @@ mod tests {
         let right = Machines(vec![
             Machine {
                 name: parse_quote! { TurnStile },
+                attributes: Default::default(),
+                externals: Default::default(),
                 initial_states: InitialStates(vec![
                     InitialState {
                         name: parse_quote! { Locked },
@@ -486,6 +3142,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Unlocked },
                         },
+                        weight: None,
                     },
                     Transition {
                         event: Event {
@@ -497,11 +3154,14 @@ mod tests {
                         to: State {
                             name: parse_quote! { Locked },
                         },
+                        weight: None,
                     },
                 ]),
             },
             Machine {
                 name: parse_quote! { Lock },
+                attributes: Default::default(),
+                externals: Default::default(),
                 initial_states: InitialStates(vec![
                     InitialState {
                         name: parse_quote! { Locked },
@@ -521,6 +3181,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Unlocked },
                         },
+                        weight: None,
                     },
                     Transition {
                         event: Event {
@@ -532,6 +3193,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Locked },
                         },
+                        weight: None,
                     },
                 ]),
             },
@@ -545,6 +3207,8 @@ mod tests {
         let machines = Machines(vec![
             Machine {
                 name: parse_quote! { TurnStile },
+                attributes: Default::default(),
+                externals: Default::default(),
                 initial_states: InitialStates(vec![
                     InitialState {
                         name: parse_quote! { Locked },
@@ -564,6 +3228,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Unlocked },
                         },
+                        weight: None,
                     },
                     Transition {
                         event: Event {
@@ -575,11 +3240,14 @@ mod tests {
                         to: State {
                             name: parse_quote! { Locked },
                         },
+                        weight: None,
                     },
                 ]),
             },
             Machine {
                 name: parse_quote! { Lock },
+                attributes: Default::default(),
+                externals: Default::default(),
                 initial_states: InitialStates(vec![
                     InitialState {
                         name: parse_quote! { Locked },
@@ -599,6 +3267,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Unlocked },
                         },
+                        weight: None,
                     },
                     Transition {
                         event: Event {
@@ -610,6 +3279,7 @@ mod tests {
                         to: State {
                             name: parse_quote! { Locked },
                         },
+                        weight: None,
                     },
                 ]),
             },
@@ -618,21 +3288,36 @@ mod tests {
         let left = quote! {
             use sm::{AsEnum, Initializer, Machine as M, Transition};
 
-            #[allow(non_snake_case)]
+            #[allow(dead_code, non_snake_case, unused_imports)]
             mod TurnStile {
-                use sm::{AsEnum, Event, InitialState, Initializer, Machine as M, NoneEvent, State, Transition};
+                use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+
+                pub use self::events::*;
+                pub use self::states::*;
+                pub use self::variant::*;
 
-                #[derive(Debug, Eq, PartialEq)]
+                #[derive(Clone, Eq, PartialEq)]
                 pub struct Machine<S: State, E: Event>(S, Option<E>);
 
+                impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                    fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                        f.debug_struct("TurnStile::Machine")
+                            .field("state", &self.0)
+                            .field("trigger", &self.1)
+                            .finish()
+                    }
+                }
+
                 impl<S: State, E: Event> M for Machine<S, E> {
                     type State = S;
                     type Event = E;
 
+                    #[inline]
                     fn state(&self) -> Self::State {
                         self.0.clone()
                     }
 
+                    #[inline]
                     fn trigger(&self) -> Option<Self::Event> {
                         self.1.clone()
                     }
@@ -641,150 +3326,538 @@ mod tests {
                 impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
                     type Machine = Machine<S, NoneEvent>;
 
+                    #[inline]
                     fn new(state: S) -> Self::Machine {
                         Machine(state, Option::None)
                     }
                 }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Locked;
-                impl State for Locked {}
-
-                impl PartialEq<Locked> for Locked {
-                    fn eq(&self, _: &Locked) -> bool {
-                        true
+                impl Machine<Locked, NoneEvent> {
+                    #[inline]
+                    pub fn locked() -> Self {
+                        Machine(Locked, Option::None)
                     }
                 }
 
-                impl PartialEq<Unlocked> for Locked {
-                    fn eq(&self, _: &Unlocked) -> bool {
-                        false
+                impl Machine<Unlocked, NoneEvent> {
+                    #[inline]
+                    pub fn unlocked() -> Self {
+                        Machine(Unlocked, Option::None)
                     }
                 }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Unlocked;
-                impl State for Unlocked {}
+                impl <S: State + Identify<Id = StateId>, E: Event> Machine<S, E> {
+                    #[inline]
+                    pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                        S::ID == T::ID
+                    }
+
+                    #[inline]
+                    pub fn is_locked(&self) -> bool {
+                        self.is::<Locked>()
+                    }
 
-                impl PartialEq<Locked> for Unlocked {
-                    fn eq(&self, _: & Locked) -> bool {
-                        false
+                    #[inline]
+                    pub fn is_unlocked(&self) -> bool {
+                        self.is::<Unlocked>()
                     }
                 }
 
-                impl PartialEq<Unlocked> for Unlocked {
-                    fn eq(&self, _: & Unlocked) -> bool {
-                        true
+                impl<E: Event> From<Machine<Locked, E>> for Locked {
+                    #[inline]
+                    fn from(machine: Machine<Locked, E>) -> Self {
+                        machine.0
                     }
                 }
 
-                impl InitialState for Locked {}
-                impl InitialState for Unlocked {}
+                impl<E: Event> From<Machine<Unlocked, E>> for Unlocked {
+                    #[inline]
+                    fn from(machine: Machine<Unlocked, E>) -> Self {
+                        machine.0
+                    }
+                }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Coin;
-                impl Event for Coin {}
+                mod states {
+                    use super::*;
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `Coin` leads to `Unlocked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Unlocked` via `Push`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Locked;
+                    impl State for Locked {}
+
+                    pub const LOCKED: Locked = Locked;
+
+                    impl From<Locked> for StateId {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            StateId::Locked
+                        }
+                    }
 
-                impl PartialEq<Coin> for Coin {
-                    fn eq(&self, _: &Coin) -> bool {
-                        true
+                    impl From<Locked> for &'static str {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            "Locked"
+                        }
                     }
-                }
 
-                impl PartialEq<Push> for Coin {
-                    fn eq(&self, _: &Push) -> bool {
-                        false
+                    impl Identify for Locked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Locked;
                     }
-                }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Push;
-                impl Event for Push {}
+                    impl PartialEq<Locked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Locked) -> bool {
+                            true
+                        }
+                    }
 
-                impl PartialEq<Coin> for Push {
-                    fn eq(&self, _: &Coin) -> bool {
-                        false
+                    impl PartialEq<Unlocked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Unlocked) -> bool {
+                            false
+                        }
                     }
-                }
 
-                impl PartialEq<Push> for Push {
-                    fn eq(&self, _: &Push) -> bool {
-                        true
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `Push` leads to `Locked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Locked` via `Coin`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Unlocked;
+                    impl State for Unlocked {}
+
+                    pub const UNLOCKED: Unlocked = Unlocked;
+
+                    impl From<Unlocked> for StateId {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            StateId::Unlocked
+                        }
                     }
-                }
 
-                #[derive(Debug)]
-                pub enum Variant {
-                    InitialLocked(Machine<Locked, NoneEvent>),
-                    InitialUnlocked(Machine<Unlocked, NoneEvent>),
-                    UnlockedByCoin(Machine<Unlocked, Coin>),
-                    LockedByPush(Machine<Locked, Push>)
-                }
+                    impl From<Unlocked> for &'static str {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            "Unlocked"
+                        }
+                    }
 
-                impl AsEnum for Machine<Locked, NoneEvent> {
-                    type Enum = Variant;
+                    impl Identify for Unlocked {
+                        type Id = StateId;
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialLocked(self)
+                        const ID: StateId = StateId::Unlocked;
                     }
-                }
 
-                impl AsEnum for Machine<Unlocked, NoneEvent> {
-                    type Enum = Variant;
+                    impl PartialEq<Locked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Locked) -> bool {
+                            false
+                        }
+                    }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialUnlocked(self)
+                    impl PartialEq<Unlocked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Unlocked) -> bool {
+                            true
+                        }
                     }
-                }
 
-                impl AsEnum for Machine<Unlocked, Coin> {
-                    type Enum = Variant;
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum StateId {
+                        Locked,
+                        Unlocked
+                    }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::UnlockedByCoin(self)
+                    impl Id for StateId {
+                        const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                        const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                StateId::Locked => 0usize,
+                                StateId::Unlocked => 1usize
+                            }
+                        }
                     }
+
+                    impl From<StateId> for &'static str {
+                        #[inline]
+                        fn from(id: StateId) -> Self {
+                            id.name()
+                        }
+                    }
+
+                    impl InitialState for Locked {}
+                    impl InitialState for Unlocked {}
                 }
 
-                impl AsEnum for Machine<Locked, Push> {
-                    type Enum = Variant;
+                mod events {
+                    use super::*;
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::LockedByPush(self)
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Coin;
+                    impl Event for Coin {}
+
+                    pub const COIN: Coin = Coin;
+
+                    impl From<Coin> for EventId {
+                        #[inline]
+                        fn from(_: Coin) -> Self {
+                            EventId::Coin
+                        }
+                    }
+
+                    impl From<Coin> for &'static str {
+                        #[inline]
+                        fn from(_: Coin) -> Self {
+                            "Coin"
+                        }
+                    }
+
+                    impl Identify for Coin {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::Coin;
+                    }
+
+                    impl PartialEq<Coin> for Coin {
+                        #[inline]
+                        fn eq(&self, _: &Coin) -> bool {
+                            true
+                        }
+                    }
+
+                    impl PartialEq<Push> for Coin {
+                        #[inline]
+                        fn eq(&self, _: &Push) -> bool {
+                            false
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Push;
+                    impl Event for Push {}
+
+                    pub const PUSH: Push = Push;
+
+                    impl From<Push> for EventId {
+                        #[inline]
+                        fn from(_: Push) -> Self {
+                            EventId::Push
+                        }
+                    }
+
+                    impl From<Push> for &'static str {
+                        #[inline]
+                        fn from(_: Push) -> Self {
+                            "Push"
+                        }
+                    }
+
+                    impl Identify for Push {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::Push;
+                    }
+
+                    impl PartialEq<Coin> for Push {
+                        #[inline]
+                        fn eq(&self, _: &Coin) -> bool {
+                            false
+                        }
+                    }
+
+                    impl PartialEq<Push> for Push {
+                        #[inline]
+                        fn eq(&self, _: &Push) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum EventId {
+                        Coin,
+                        Push
+                    }
+
+                    impl Id for EventId {
+                        const ALL: &'static [Self] = &[EventId::Coin, EventId::Push];
+                        const NAMES: &'static [&'static str] = &["Coin", "Push"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                EventId::Coin => 0usize,
+                                EventId::Push => 1usize
+                            }
+                        }
+                    }
+
+                    impl From<EventId> for &'static str {
+                        #[inline]
+                        fn from(id: EventId) -> Self {
+                            id.name()
+                        }
                     }
                 }
 
-                impl<E: Event> Transition<Coin> for Machine<Locked, E> {
-                    type Machine = Machine<Unlocked, Coin>;
+                mod variant {
+                    use super::*;
+
+                    #[derive(Clone, Debug)]
+                    pub enum Variant {
+                        InitialLocked(Machine<Locked, NoneEvent>),
+                        InitialUnlocked(Machine<Unlocked, NoneEvent>),
+                        UnlockedByCoin(Machine<Unlocked, Coin>),
+                        LockedByPush(Machine<Locked, Push>)
+                    }
+
+                    impl AsEnum for Machine<Locked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialLocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, Coin> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::UnlockedByCoin(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Locked, Push> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::LockedByPush(self)
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug)]
+                    pub enum VariantRef<'a> {
+                        InitialLocked(&'a Machine<Locked, NoneEvent>),
+                        InitialUnlocked(&'a Machine<Unlocked, NoneEvent>),
+                        UnlockedByCoin(&'a Machine<Unlocked, Coin>),
+                        LockedByPush(&'a Machine<Locked, Push>)
+                    }
+
+                    impl Machine<Locked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialLocked(self)
+                        }
+                    }
+
+                    impl Machine<Unlocked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl Machine<Unlocked, Coin> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::UnlockedByCoin(self)
+                        }
+                    }
+
+                    impl Machine<Locked, Push> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::LockedByPush(self)
+                        }
+                    }
+
+                    impl Variant {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            match *self {
+                                Variant::InitialLocked(ref m) => VariantRef::InitialLocked(m),
+                                Variant::InitialUnlocked(ref m) => VariantRef::InitialUnlocked(m),
+                                Variant::UnlockedByCoin(ref m) => VariantRef::UnlockedByCoin(m),
+                                Variant::LockedByPush(ref m) => VariantRef::LockedByPush(m)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn state_id(&self) -> StateId {
+                            match *self {
+                                Variant::InitialLocked(_) => StateId::Locked,
+                                Variant::InitialUnlocked(_) => StateId::Unlocked,
+                                Variant::UnlockedByCoin(_) => StateId::Unlocked,
+                                Variant::LockedByPush(_) => StateId::Locked
+                            }
+                        }
+
+                        #[inline]
+                        pub fn same_state(&self, other: &Variant) -> bool {
+                            self.state_id() == other.state_id()
+                        }
+
+                        #[inline]
+                        pub fn trigger_id(&self) -> Option<EventId> {
+                            match *self {
+                                Variant::InitialLocked(_) => Option::None,
+                                Variant::InitialUnlocked(_) => Option::None,
+                                Variant::UnlockedByCoin(_) => Option::Some(EventId::Coin),
+                                Variant::LockedByPush(_) => Option::Some(EventId::Push)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                            self.state_id() == T::ID
+                        }
+
+                        #[inline]
+                        pub fn is_locked(&self) -> bool {
+                            self.state_id() == StateId::Locked
+                        }
+
+                        #[inline]
+                        pub fn is_unlocked(&self) -> bool {
+                            self.state_id() == StateId::Unlocked
+                        }
+
+                        pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                            match (state, trigger) {
+                                (StateId::Locked, Option::None) => Option::Some(Variant::InitialLocked(Machine(Locked, Option::None))),
+                                (StateId::Unlocked, Option::None) => Option::Some(Variant::InitialUnlocked(Machine(Unlocked, Option::None))),
+                                (StateId::Unlocked, Option::Some(EventId::Coin)) => Option::Some(Variant::UnlockedByCoin(Machine(Unlocked, Option::Some(Coin)))),
+                                (StateId::Locked, Option::Some(EventId::Push)) => Option::Some(Variant::LockedByPush(Machine(Locked, Option::Some(Push)))),
+                                _ => Option::None,
+                            }
+                        }
+                    }
+
+                    impl TryTransition<Coin> for Variant {
+                        fn try_transition(self, event: Coin) -> Result<Self, Self> {
+                            match self {
+                                Variant::InitialLocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::LockedByPush(m) => Result::Ok(m.transition(event).as_enum()),
+                                machine => Result::Err(machine),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<Push> for Variant {
+                        fn try_transition(self, event: Push) -> Result<Self, Self> {
+                            match self {
+                                Variant::InitialUnlocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::UnlockedByCoin(m) => Result::Ok(m.transition(event).as_enum()),
+                                machine => Result::Err(machine),
+                            }
+                        }
+                    }
 
-                    fn transition(self, event: Coin) -> Self::Machine {
-                        Machine(Unlocked, Some(event))
+                    impl TryTransition<EventId> for Variant {
+                        fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                            match event {
+                                EventId::Coin => self.try_transition(Coin),
+                                EventId::Push => self.try_transition(Push),
+                            }
+                        }
                     }
                 }
 
-                impl<E: Event> Transition<Push> for Machine<Unlocked, E> {
-                    type Machine = Machine<Locked, Push>;
+                mod transitions {
+                    use super::*;
+
+                    impl<E: Event> Transition<Coin> for Machine<Locked, E> {
+                        type Machine = Machine<Unlocked, Coin>;
+
+                        #[inline]
+                        fn transition(self, event: Coin) -> Self::Machine {
+                            Machine(Unlocked, Some(event))
+                        }
+                    }
+
+                    impl<E: Event> Transition<Push> for Machine<Unlocked, E> {
+                        type Machine = Machine<Locked, Push>;
+
+                        #[inline]
+                        fn transition(self, event: Push) -> Self::Machine {
+                            Machine(Locked, Some(event))
+                        }
+                    }
+
+                    impl Accepts<Coin> for Locked {
+                        type Next = Unlocked;
+                    }
 
-                    fn transition(self, event: Push) -> Self::Machine {
-                        Machine(Locked, Some(event))
+                    impl Accepts<Push> for Unlocked {
+                        type Next = Locked;
                     }
                 }
+
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    pub use super::variant::*;
+                }
             }
 
-            #[allow(non_snake_case)]
+            #[allow(dead_code, non_snake_case, unused_imports)]
             mod Lock {
-                use sm::{AsEnum, Event, InitialState, Initializer, Machine as M, NoneEvent, State, Transition};
+                use sm::{Accepts, AsEnum, Event, Id, Identify, InitialState, Initializer, Machine as M, NoneEvent, ReplayError, State, Table, Transition, TransitionError, TryTransition};
+
+                pub use self::events::*;
+                pub use self::states::*;
+                pub use self::variant::*;
 
-                #[derive(Debug, Eq, PartialEq)]
+                #[derive(Clone, Eq, PartialEq)]
                 pub struct Machine<S: State, E: Event>(S, Option<E>);
 
+                impl<S: State, E: Event> ::sm::export::fmt::Debug for Machine<S, E> {
+                    fn fmt(&self, f: &mut ::sm::export::fmt::Formatter<'_>) -> ::sm::export::fmt::Result {
+                        f.debug_struct("Lock::Machine")
+                            .field("state", &self.0)
+                            .field("trigger", &self.1)
+                            .finish()
+                    }
+                }
+
                 impl<S: State, E: Event> M for Machine<S, E> {
                     type State = S;
                     type Event = E;
 
+                    #[inline]
                     fn state(&self) -> Self::State {
                         self.0.clone()
                     }
 
+                    #[inline]
                     fn trigger(&self) -> Option<Self::Event> {
                         self.1.clone()
                     }
@@ -793,109 +3866,445 @@ mod tests {
                 impl<S: InitialState> Initializer<S> for Machine<S, NoneEvent> {
                     type Machine = Machine<S, NoneEvent>;
 
+                    #[inline]
                     fn new(state: S) -> Self::Machine {
                         Machine(state, Option::None)
                     }
                 }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Locked;
-                impl State for Locked {}
-
-                impl PartialEq<Locked> for Locked {
-                    fn eq(&self, _: &Locked) -> bool {
-                        true
+                impl Machine<Locked, NoneEvent> {
+                    #[inline]
+                    pub fn locked() -> Self {
+                        Machine(Locked, Option::None)
                     }
                 }
 
-                impl PartialEq<Unlocked> for Locked {
-                    fn eq(&self, _: &Unlocked) -> bool {
-                        false
+                impl Machine<Unlocked, NoneEvent> {
+                    #[inline]
+                    pub fn unlocked() -> Self {
+                        Machine(Unlocked, Option::None)
                     }
                 }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct Unlocked;
-                impl State for Unlocked {}
+                impl <S: State + Identify<Id = StateId>, E: Event> Machine<S, E> {
+                    #[inline]
+                    pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                        S::ID == T::ID
+                    }
+
+                    #[inline]
+                    pub fn is_locked(&self) -> bool {
+                        self.is::<Locked>()
+                    }
 
-                impl PartialEq<Locked> for Unlocked {
-                    fn eq(&self, _: & Locked) -> bool {
-                        false
+                    #[inline]
+                    pub fn is_unlocked(&self) -> bool {
+                        self.is::<Unlocked>()
                     }
                 }
 
-                impl PartialEq<Unlocked> for Unlocked {
-                    fn eq(&self, _: & Unlocked) -> bool {
-                        true
+                impl<E: Event> From<Machine<Locked, E>> for Locked {
+                    #[inline]
+                    fn from(machine: Machine<Locked, E>) -> Self {
+                        machine.0
                     }
                 }
 
-                impl InitialState for Locked {}
-                impl InitialState for Unlocked {}
+                impl<E: Event> From<Machine<Unlocked, E>> for Unlocked {
+                    #[inline]
+                    fn from(machine: Machine<Unlocked, E>) -> Self {
+                        machine.0
+                    }
+                }
 
-                #[derive(Clone, Copy, Debug, Eq)]
-                pub struct TurnKey;
-                impl Event for TurnKey {}
+                mod states {
+                    use super::*;
+
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Unlocked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Unlocked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Locked;
+                    impl State for Locked {}
+
+                    pub const LOCKED: Locked = Locked;
+
+                    impl From<Locked> for StateId {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            StateId::Locked
+                        }
+                    }
 
-                impl PartialEq<TurnKey> for TurnKey {
-                    fn eq(&self, _: &TurnKey) -> bool {
-                        true
+                    impl From<Locked> for &'static str {
+                        #[inline]
+                        fn from(_: Locked) -> Self {
+                            "Locked"
+                        }
                     }
-                }
 
-                #[derive(Debug)]
-                pub enum Variant {
-                    InitialLocked(Machine<Locked, NoneEvent>),
-                    InitialUnlocked(Machine<Unlocked, NoneEvent>),
-                    UnlockedByTurnKey(Machine<Unlocked, TurnKey>),
-                    LockedByTurnKey(Machine<Locked, TurnKey>)
-                }
+                    impl Identify for Locked {
+                        type Id = StateId;
 
-                impl AsEnum for Machine<Locked, NoneEvent> {
-                    type Enum = Variant;
+                        const ID: StateId = StateId::Locked;
+                    }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialLocked(self)
+                    impl PartialEq<Locked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Locked) -> bool {
+                            true
+                        }
                     }
-                }
 
-                impl AsEnum for Machine<Unlocked, NoneEvent> {
-                    type Enum = Variant;
+                    impl PartialEq<Unlocked> for Locked {
+                        #[inline]
+                        fn eq(&self, _: &Unlocked) -> bool {
+                            false
+                        }
+                    }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::InitialUnlocked(self)
+                    #[doc = "Transitions:"]
+                    #[doc = ""]
+                    #[doc = "- `TurnKey` leads to `Locked`"]
+                    #[doc = ""]
+                    #[doc = "Reachable from:"]
+                    #[doc = ""]
+                    #[doc = "- `Locked` via `TurnKey`"]
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct Unlocked;
+                    impl State for Unlocked {}
+
+                    pub const UNLOCKED: Unlocked = Unlocked;
+
+                    impl From<Unlocked> for StateId {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            StateId::Unlocked
+                        }
                     }
-                }
 
-                impl AsEnum for Machine<Unlocked, TurnKey> {
-                    type Enum = Variant;
+                    impl From<Unlocked> for &'static str {
+                        #[inline]
+                        fn from(_: Unlocked) -> Self {
+                            "Unlocked"
+                        }
+                    }
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::UnlockedByTurnKey(self)
+                    impl Identify for Unlocked {
+                        type Id = StateId;
+
+                        const ID: StateId = StateId::Unlocked;
+                    }
+
+                    impl PartialEq<Locked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Locked) -> bool {
+                            false
+                        }
+                    }
+
+                    impl PartialEq<Unlocked> for Unlocked {
+                        #[inline]
+                        fn eq(&self, _: & Unlocked) -> bool {
+                            true
+                        }
                     }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum StateId {
+                        Locked,
+                        Unlocked
+                    }
+
+                    impl Id for StateId {
+                        const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                        const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                StateId::Locked => 0usize,
+                                StateId::Unlocked => 1usize
+                            }
+                        }
+                    }
+
+                    impl From<StateId> for &'static str {
+                        #[inline]
+                        fn from(id: StateId) -> Self {
+                            id.name()
+                        }
+                    }
+
+                    impl InitialState for Locked {}
+                    impl InitialState for Unlocked {}
                 }
 
-                impl AsEnum for Machine<Locked, TurnKey> {
-                    type Enum = Variant;
+                mod events {
+                    use super::*;
 
-                    fn as_enum(self) -> Self::Enum {
-                        Variant::LockedByTurnKey(self)
+                    #[derive(Clone, Copy, Debug, Eq)]
+                    pub struct TurnKey;
+                    impl Event for TurnKey {}
+
+                    pub const TURN_KEY: TurnKey = TurnKey;
+
+                    impl From<TurnKey> for EventId {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            EventId::TurnKey
+                        }
+                    }
+
+                    impl From<TurnKey> for &'static str {
+                        #[inline]
+                        fn from(_: TurnKey) -> Self {
+                            "TurnKey"
+                        }
+                    }
+
+                    impl Identify for TurnKey {
+                        type Id = EventId;
+
+                        const ID: EventId = EventId::TurnKey;
+                    }
+
+                    impl PartialEq<TurnKey> for TurnKey {
+                        #[inline]
+                        fn eq(&self, _: &TurnKey) -> bool {
+                            true
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+                    pub enum EventId {
+                        TurnKey
+                    }
+
+                    impl Id for EventId {
+                        const ALL: &'static [Self] = &[EventId::TurnKey];
+                        const NAMES: &'static [&'static str] = &["TurnKey"];
+
+                        #[inline]
+                        fn index(self) -> usize {
+                            match self {
+                                EventId::TurnKey => 0usize
+                            }
+                        }
+                    }
+
+                    impl From<EventId> for &'static str {
+                        #[inline]
+                        fn from(id: EventId) -> Self {
+                            id.name()
+                        }
                     }
                 }
-                impl<E: Event> Transition<TurnKey> for Machine<Locked, E> {
-                    type Machine = Machine<Unlocked, TurnKey>;
 
-                    fn transition(self, event: TurnKey) -> Self::Machine {
-                        Machine(Unlocked, Some(event))
+                mod variant {
+                    use super::*;
+
+                    #[derive(Clone, Debug)]
+                    pub enum Variant {
+                        InitialLocked(Machine<Locked, NoneEvent>),
+                        InitialUnlocked(Machine<Unlocked, NoneEvent>),
+                        UnlockedByTurnKey(Machine<Unlocked, TurnKey>),
+                        LockedByTurnKey(Machine<Locked, TurnKey>)
+                    }
+
+                    impl AsEnum for Machine<Locked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialLocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, NoneEvent> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Unlocked, TurnKey> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::UnlockedByTurnKey(self)
+                        }
+                    }
+
+                    impl AsEnum for Machine<Locked, TurnKey> {
+                        type Enum = Variant;
+
+                        #[inline]
+                        fn as_enum(self) -> Self::Enum {
+                            Variant::LockedByTurnKey(self)
+                        }
+                    }
+
+                    #[derive(Clone, Copy, Debug)]
+                    pub enum VariantRef<'a> {
+                        InitialLocked(&'a Machine<Locked, NoneEvent>),
+                        InitialUnlocked(&'a Machine<Unlocked, NoneEvent>),
+                        UnlockedByTurnKey(&'a Machine<Unlocked, TurnKey>),
+                        LockedByTurnKey(&'a Machine<Locked, TurnKey>)
+                    }
+
+                    impl Machine<Locked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialLocked(self)
+                        }
+                    }
+
+                    impl Machine<Unlocked, NoneEvent> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::InitialUnlocked(self)
+                        }
+                    }
+
+                    impl Machine<Unlocked, TurnKey> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::UnlockedByTurnKey(self)
+                        }
+                    }
+
+                    impl Machine<Locked, TurnKey> {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            VariantRef::LockedByTurnKey(self)
+                        }
+                    }
+
+                    impl Variant {
+                        #[inline]
+                        pub fn as_enum_ref(&self) -> VariantRef<'_> {
+                            match *self {
+                                Variant::InitialLocked(ref m) => VariantRef::InitialLocked(m),
+                                Variant::InitialUnlocked(ref m) => VariantRef::InitialUnlocked(m),
+                                Variant::UnlockedByTurnKey(ref m) => VariantRef::UnlockedByTurnKey(m),
+                                Variant::LockedByTurnKey(ref m) => VariantRef::LockedByTurnKey(m)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn state_id(&self) -> StateId {
+                            match *self {
+                                Variant::InitialLocked(_) => StateId::Locked,
+                                Variant::InitialUnlocked(_) => StateId::Unlocked,
+                                Variant::UnlockedByTurnKey(_) => StateId::Unlocked,
+                                Variant::LockedByTurnKey(_) => StateId::Locked
+                            }
+                        }
+
+                        #[inline]
+                        pub fn same_state(&self, other: &Variant) -> bool {
+                            self.state_id() == other.state_id()
+                        }
+
+                        #[inline]
+                        pub fn trigger_id(&self) -> Option<EventId> {
+                            match *self {
+                                Variant::InitialLocked(_) => Option::None,
+                                Variant::InitialUnlocked(_) => Option::None,
+                                Variant::UnlockedByTurnKey(_) => Option::Some(EventId::TurnKey),
+                                Variant::LockedByTurnKey(_) => Option::Some(EventId::TurnKey)
+                            }
+                        }
+
+                        #[inline]
+                        pub fn is<T: Identify<Id = StateId>>(&self) -> bool {
+                            self.state_id() == T::ID
+                        }
+
+                        #[inline]
+                        pub fn is_locked(&self) -> bool {
+                            self.state_id() == StateId::Locked
+                        }
+
+                        #[inline]
+                        pub fn is_unlocked(&self) -> bool {
+                            self.state_id() == StateId::Unlocked
+                        }
+
+                        pub fn from_ids(state: StateId, trigger: Option<EventId>) -> Option<Self> {
+                            match (state, trigger) {
+                                (StateId::Locked, Option::None) => Option::Some(Variant::InitialLocked(Machine(Locked, Option::None))),
+                                (StateId::Unlocked, Option::None) => Option::Some(Variant::InitialUnlocked(Machine(Unlocked, Option::None))),
+                                (StateId::Unlocked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::UnlockedByTurnKey(Machine(Unlocked, Option::Some(TurnKey)))),
+                                (StateId::Locked, Option::Some(EventId::TurnKey)) => Option::Some(Variant::LockedByTurnKey(Machine(Locked, Option::Some(TurnKey)))),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<TurnKey> for Variant {
+                        fn try_transition(self, event: TurnKey) -> Result<Self, Self> {
+                            match self {
+                                Variant::InitialLocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::InitialUnlocked(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::UnlockedByTurnKey(m) => Result::Ok(m.transition(event).as_enum()),
+                                Variant::LockedByTurnKey(m) => Result::Ok(m.transition(event).as_enum()),
+                            }
+                        }
+                    }
+
+                    impl TryTransition<EventId> for Variant {
+                        fn try_transition(self, event: EventId) -> Result<Self, Self> {
+                            match event {
+                                EventId::TurnKey => self.try_transition(TurnKey),
+                            }
+                        }
                     }
                 }
 
-                impl<E: Event> Transition<TurnKey> for Machine<Unlocked, E> {
-                    type Machine = Machine<Locked, TurnKey>;
+                mod transitions {
+                    use super::*;
+
+                    impl<E: Event> Transition<TurnKey> for Machine<Locked, E> {
+                        type Machine = Machine<Unlocked, TurnKey>;
+
+                        #[inline]
+                        fn transition(self, event: TurnKey) -> Self::Machine {
+                            Machine(Unlocked, Some(event))
+                        }
+                    }
+
+                    impl<E: Event> Transition<TurnKey> for Machine<Unlocked, E> {
+                        type Machine = Machine<Locked, TurnKey>;
+
+                        #[inline]
+                        fn transition(self, event: TurnKey) -> Self::Machine {
+                            Machine(Locked, Some(event))
+                        }
+                    }
 
-                    fn transition(self, event: TurnKey) -> Self::Machine {
-                        Machine(Locked, Some(event))
+                    impl Accepts<TurnKey> for Locked {
+                        type Next = Unlocked;
                     }
+
+                    impl Accepts<TurnKey> for Unlocked {
+                        type Next = Locked;
+                    }
+                }
+
+                pub mod prelude {
+                    pub use sm::{AsEnum, Initializer, Machine as M, Transition};
+                    pub use super::events::*;
+                    pub use super::states::*;
+                    pub use super::Machine;
+                    pub use super::variant::*;
                 }
             }
         };
@@ -0,0 +1,176 @@
+use alloc::format;
+use alloc::string::ToString;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::ffi::snake_case;
+use crate::sm::machine::{Machine, Trigger};
+
+/// MatchMacro generates a declarative macro matching on the `Variant` of a
+/// machine by state, named after the machine, e.g. `lock_match!` for `Lock`:
+///
+/// ```text
+/// lock_match!(sm,
+///     Locked(m) => ...,
+///     Unlocked(m) => ...,
+/// )
+/// ```
+///
+/// Every state has to be listed exactly once, in any order, so adding a state
+/// to the machine breaks the existing matches. A state with more than one
+/// variant expands to one arm per variant, each with the same pattern and
+/// expression. The macro is only generated for machines marked
+/// `#[match_macro]`.
+///
+/// The macro is emitted next to the machine module, and refers to the
+/// `Variant` enum through the name of the machine, which has to be in scope
+/// wherever the macro is used.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct MatchMacro<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for MatchMacro<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.machine.name;
+        let erase = self.machine.trigger() == Trigger::Erased;
+
+        // Variants of the machine, along with the state they are in.
+        let mut variants: Vec<(Ident, Ident)> = Vec::new();
+
+        for s in &self.machine.initial_states.0 {
+            let state = s.name.clone();
+            let variant = if erase {
+                state.clone()
+            } else {
+                Ident::new(&format!("Initial{}", state), Span::call_site())
+            };
+
+            variants.push((state, variant));
+        }
+
+        for t in &self.machine.transitions.0 {
+            let state = t.to.name.clone();
+            let variant = if erase {
+                state.clone()
+            } else {
+                Ident::new(&format!("{}By{}", state, t.event.name), Span::call_site())
+            };
+
+            if !variants.iter().any(|(_, v)| *v == variant) {
+                variants.push((state, variant));
+            }
+        }
+
+        let macro_name = Ident::new(
+            &format!("{}_match", snake_case(&name.to_string())),
+            Span::call_site(),
+        );
+
+        // Every arm is munched on its own, moving the match arms of its
+        // state over to the ones already done, so arms can come in any order.
+        let mut rules = Vec::new();
+
+        for state in self.machine.states().0 {
+            let state = state.name;
+            let arms = variants
+                .iter()
+                .filter(|(s, _)| *s == state)
+                .map(|(_, variant)| quote! { #name::Variant::#variant($pat) => $expr, });
+            let arms = quote! { #(#arms)* };
+
+            rules.push(quote! {
+                (@munch $machine:expr, [$($done:tt)*] #state($pat:pat) => $expr:expr, $($rest:tt)*) => {
+                    #macro_name!(@munch $machine, [$($done)* #arms] $($rest)*)
+                };
+                (@munch $machine:expr, [$($done:tt)*] #state($pat:pat) => $expr:expr) => {
+                    #macro_name!(@munch $machine, [$($done)* #arms])
+                };
+            });
+        }
+
+        tokens.extend(quote! {
+            #[allow(unused_macros)]
+            macro_rules! #macro_name {
+                #(#rules)*
+                (@munch $machine:expr, [$($done:tt)*]) => {{
+                    #[deny(unreachable_patterns)]
+                    let result = match $machine.as_enum() {
+                        $($done)*
+                    };
+                    result
+                }};
+                ($machine:expr, $($arms:tt)*) => {
+                    #macro_name!(@munch $machine, [] $($arms)*)
+                };
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+
+    #[test]
+    fn test_match_macro_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[match_macro]
+            TurnStile {
+                InitialStates { Locked }
+
+                Coin { Locked => Unlocked }
+                Push { Unlocked => Locked }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[allow(unused_macros)]
+            macro_rules! turn_stile_match {
+                (@munch $machine:expr, [$($done:tt)*] Locked($pat:pat) => $expr:expr, $($rest:tt)*) => {
+                    turn_stile_match!(@munch $machine, [$($done)*
+                        TurnStile::Variant::InitialLocked($pat) => $expr,
+                        TurnStile::Variant::LockedByPush($pat) => $expr,
+                    ] $($rest)*)
+                };
+                (@munch $machine:expr, [$($done:tt)*] Locked($pat:pat) => $expr:expr) => {
+                    turn_stile_match!(@munch $machine, [$($done)*
+                        TurnStile::Variant::InitialLocked($pat) => $expr,
+                        TurnStile::Variant::LockedByPush($pat) => $expr,
+                    ])
+                };
+                (@munch $machine:expr, [$($done:tt)*] Unlocked($pat:pat) => $expr:expr, $($rest:tt)*) => {
+                    turn_stile_match!(@munch $machine, [$($done)*
+                        TurnStile::Variant::UnlockedByCoin($pat) => $expr,
+                    ] $($rest)*)
+                };
+                (@munch $machine:expr, [$($done:tt)*] Unlocked($pat:pat) => $expr:expr) => {
+                    turn_stile_match!(@munch $machine, [$($done)*
+                        TurnStile::Variant::UnlockedByCoin($pat) => $expr,
+                    ])
+                };
+                (@munch $machine:expr, [$($done:tt)*]) => {{
+                    #[deny(unreachable_patterns)]
+                    let result = match $machine.as_enum() {
+                        $($done)*
+                    };
+                    result
+                }};
+                ($machine:expr, $($arms:tt)*) => {
+                    turn_stile_match!(@munch $machine, [] $($arms)*)
+                };
+            }
+        };
+
+        let mut right = TokenStream::new();
+        MatchMacro { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
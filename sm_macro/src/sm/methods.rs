@@ -0,0 +1,359 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{
+    parenthesized, Attribute, Error, FnArg, Ident, ImplItem, ImplItemMethod, ItemImpl, Pat,
+    ReturnType, Token, Type,
+};
+
+use crate::sm::event::Event;
+use crate::sm::initial_state::{InitialState, InitialStates};
+use crate::sm::machine::Machine;
+use crate::sm::state::State;
+use crate::sm::transition::{Transition, Transitions};
+
+/// Methods is the input of the `#[sm::machine]` attribute: an impl block whose
+/// methods are annotated with `#[transition(Locked => Unlocked)]` attributes.
+/// Every annotated method becomes an event named after the method, in camel
+/// case, and the machine is generated in a module named after the type,
+/// suffixed with `Machine`.
+///
+/// The impl block is emitted unchanged, except for the `#[transition]`
+/// attributes. For every transition, a method of the same name is added to
+/// the machine in the `from` state, which calls the annotated method and
+/// transitions the machine to the `to` state.
+pub(crate) struct Methods {
+    pub item: ItemImpl,
+    pub machine: Machine,
+    pub methods: Vec<(ImplItemMethod, Vec<Transition>)>,
+}
+
+impl Methods {
+    /// parse builds the machine from the attribute arguments, which optionally
+    /// list the initial states as `InitialStates { ... }`, and the annotated
+    /// impl block. Without arguments, the `from` state of the first transition
+    /// is the initial state.
+    pub(crate) fn parse(args: TokenStream, item: TokenStream) -> Result<Self> {
+        let mut item: ItemImpl = syn::parse2(item)?;
+
+        let name = match *item.self_ty {
+            Type::Path(ref ty) if ty.qself.is_none() && item.generics.params.is_empty() => {
+                ty.path.segments.last().unwrap().value().ident.clone()
+            }
+            _ => {
+                return Err(Error::new(
+                    item.impl_token.span,
+                    "`#[sm::machine]` only supports impl blocks of non-generic types",
+                ))
+            }
+        };
+
+        if let Some((_, ref path, _)) = item.trait_ {
+            return Err(Error::new(
+                path.segments.first().unwrap().value().ident.span(),
+                "`#[sm::machine]` does not support trait impl blocks",
+            ));
+        }
+
+        let mut transitions = Vec::new();
+        let mut methods = Vec::new();
+
+        for impl_item in &mut item.items {
+            let method = match *impl_item {
+                ImplItem::Method(ref mut method) => method,
+                _ => continue,
+            };
+
+            let event = Event {
+                name: Ident::new(&camel_case(&method.sig.ident.to_string()), method.sig.ident.span()),
+            };
+
+            let mut method_transitions = Vec::new();
+            let mut attrs = Vec::new();
+
+            for attr in method.attrs.drain(..) {
+                if !is(&attr, "transition") {
+                    attrs.push(attr);
+                    continue;
+                }
+
+                let attr: TransitionAttr = syn::parse2(attr.tts)?;
+                for from in attr.from {
+                    method_transitions.push(Transition {
+                        event: event.clone(),
+                        from: State { name: from },
+                        to: State {
+                            name: attr.to.clone(),
+                        },
+                        weight: None,
+                    });
+                }
+            }
+
+            method.attrs = attrs;
+
+            if !method_transitions.is_empty() {
+                transitions.extend(method_transitions.clone());
+                methods.push((method.clone(), method_transitions));
+            }
+        }
+
+        let initial_states = if args.is_empty() {
+            match transitions.first() {
+                Some(transition) => InitialStates(vec![InitialState {
+                    name: transition.from.name.clone(),
+                }]),
+                None => {
+                    return Err(Error::new(
+                        name.span(),
+                        "at least one method has to be marked `#[transition(From => To)]`",
+                    ))
+                }
+            }
+        } else {
+            syn::parse2(args)?
+        };
+
+        Ok(Methods {
+            machine: Machine {
+                name: Ident::new(&format!("{}Machine", name), Span::call_site()),
+                attributes: Default::default(),
+                externals: Default::default(),
+                initial_states,
+                transitions: Transitions(transitions),
+            },
+            item,
+            methods,
+        })
+    }
+}
+
+impl ToTokens for Methods {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.item.self_ty;
+        let machine = &self.machine.name;
+
+        self.item.to_tokens(tokens);
+        self.machine.to_tokens(tokens);
+
+        for (method, transitions) in &self.methods {
+            let vis = &method.vis;
+            let sig = &method.sig;
+            let ident = &sig.ident;
+            let generics = &sig.decl.generics;
+            let where_clause = &sig.decl.generics.where_clause;
+
+            let mut params = Vec::new();
+            let mut args = Vec::new();
+            let mut receiver = None;
+
+            for (i, input) in sig.decl.inputs.iter().enumerate() {
+                match *input {
+                    FnArg::SelfRef(ref arg) => {
+                        let mutability = &arg.mutability;
+                        params.push(quote! { this: &#mutability #ty });
+                        receiver = Some(quote! { this, });
+                    }
+                    FnArg::SelfValue(_) => {
+                        params.push(quote! { this: #ty });
+                        receiver = Some(quote! { this, });
+                    }
+                    FnArg::Captured(ref arg) => {
+                        let name = match arg.pat {
+                            Pat::Ident(ref pat) if pat.subpat.is_none() => pat.ident.clone(),
+                            _ => Ident::new(&format!("arg{}", i), Span::call_site()),
+                        };
+                        let arg_ty = &arg.ty;
+
+                        params.push(quote! { #name: #arg_ty });
+                        args.push(name);
+                    }
+                    FnArg::Inferred(_) | FnArg::Ignored(_) => {}
+                }
+            }
+
+            let call = quote! { <#ty>::#ident(#receiver #(#args),*) };
+
+            for transition in transitions {
+                let event = &transition.event.name;
+                let from = &transition.from.name;
+                let to = &transition.to.name;
+                let params = &params;
+
+                let (output, body) = match sig.decl.output {
+                    ReturnType::Default => (
+                        quote! { #machine::Machine<#machine::#to, #machine::#event> },
+                        quote! {
+                            #call;
+                            ::sm::Transition::transition(self, #machine::#event)
+                        },
+                    ),
+                    ReturnType::Type(_, ref output) => (
+                        quote! { (#machine::Machine<#machine::#to, #machine::#event>, #output) },
+                        quote! {
+                            let output = #call;
+                            (::sm::Transition::transition(self, #machine::#event), output)
+                        },
+                    ),
+                };
+
+                tokens.extend(quote! {
+                    impl<E: ::sm::Event> #machine::Machine<#machine::#from, E> {
+                        #vis fn #ident #generics(self, #(#params),*) -> #output #where_clause {
+                            #body
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn is(attr: &Attribute, name: &str) -> bool {
+    attr.path.segments.len() == 1 && attr.path.segments[0].ident == name
+}
+
+/// camel_case converts the snake case name of a method into the camel case
+/// name of an event, `turn_key` becoming `TurnKey`.
+fn camel_case(name: &str) -> String {
+    let mut camel = String::with_capacity(name.len());
+    let mut upper = true;
+
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+
+    camel
+}
+
+/// TransitionAttr is the content of a `#[transition(Locked, Unlocked => To)]`
+/// attribute.
+struct TransitionAttr {
+    from: Vec<Ident>,
+    to: Ident,
+}
+
+impl Parse for TransitionAttr {
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        let content;
+        parenthesized!(content in input);
+
+        let mut from = Vec::new();
+        while !content.peek(Token![=>]) {
+            from.push(content.parse()?);
+
+            if !content.peek(Token![=>]) {
+                let _: Token![,] = content.parse()?;
+            }
+        }
+
+        let _: Token![=>] = content.parse()?;
+        let to = content.parse()?;
+
+        if from.is_empty() {
+            return Err(content.error("expected `From => To`"));
+        }
+
+        Ok(TransitionAttr { from, to })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_methods_parse() {
+        let methods = Methods::parse(
+            TokenStream::new(),
+            quote! {
+                impl Lock {
+                    #[transition(Locked => Unlocked)]
+                    fn turn_key(&mut self, key: Key) {}
+
+                    fn reset(&mut self) {}
+                }
+            },
+        )
+        .unwrap();
+
+        let machine = Machine {
+            name: parse_quote! { LockMachine },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        assert_eq!(methods.machine, machine);
+        assert_eq!(methods.methods.len(), 1);
+        assert!(methods.methods[0].0.attrs.is_empty());
+    }
+
+    #[test]
+    fn test_methods_parse_initial_states() {
+        let methods = Methods::parse(
+            quote! { InitialStates { Unlocked } },
+            quote! {
+                impl Lock {
+                    #[transition(Locked, Broken => Unlocked)]
+                    fn turn_key(&mut self) {}
+                }
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            methods.machine.initial_states,
+            InitialStates(vec![InitialState {
+                name: parse_quote! { Unlocked },
+            }])
+        );
+        assert_eq!(methods.machine.transitions.0.len(), 2);
+    }
+
+    #[test]
+    fn test_methods_parse_without_transitions() {
+        let methods = Methods::parse(
+            TokenStream::new(),
+            quote! {
+                impl Lock {
+                    fn turn_key(&mut self) {}
+                }
+            },
+        );
+
+        assert!(methods.is_err());
+    }
+
+    #[test]
+    fn test_camel_case() {
+        assert_eq!(camel_case("turn_key"), "TurnKey");
+        assert_eq!(camel_case("push"), "Push");
+    }
+}
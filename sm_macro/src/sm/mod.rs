@@ -1,5 +1,33 @@
+pub mod actix;
+pub mod attributes;
+pub mod bevy;
+pub mod channel;
+pub mod defmt;
+pub mod derive;
+pub mod dual;
+pub mod dynamic;
 pub mod event;
+pub mod extend;
+pub mod external;
+pub mod ffi;
+pub mod footprint;
+pub mod handler;
+pub mod history;
+pub mod include;
 pub mod initial_state;
+pub mod instrument;
 pub mod machine;
+pub mod match_macro;
+pub mod no_panic;
+pub mod methods;
+pub mod persist;
+pub mod product;
+pub mod pushdown;
+pub mod python;
+pub mod reverse;
+pub mod serde;
 pub mod state;
+pub mod subset;
 pub mod transition;
+pub mod ufmt;
+pub mod wasm;
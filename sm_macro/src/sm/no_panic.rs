@@ -0,0 +1,299 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// NoPanic generates the `panic_free` module of a machine, with a shim marked
+/// [`#[no_panic]`][no-panic] around each of the runtime functions generated
+/// for its `ty` type, of its `History` type if it is marked `#[undo(n)]`, and
+/// of its `Pushdown` type if it is marked `#[pushdown(n)]`. Functions taking
+/// an iterator of events take a slice instead, so the shims aren't generic,
+/// and are compiled, and checked, along with the crate using the macro. A
+/// build with optimizations fails to link if any of them can panic.
+///
+/// [no-panic]: https://docs.rs/no-panic
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct NoPanic<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for NoPanic<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+
+        // Dynamic machines are created from an initial state, while the
+        // `Variant` enum is created from ids, or by replaying events. No
+        // argument is named `trigger`, which `#[no_panic]` uses for a function
+        // of its own.
+        let constructors = if self.machine.attributes.dynamic {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn new(state: StateId) -> Option<#ty> {
+                    #ty::new(state)
+                }
+            }
+        } else {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn from_ids(state: StateId, event: Option<EventId>) -> Option<#ty> {
+                    #ty::from_ids(state, event)
+                }
+            }
+        };
+
+        let replay = if self.machine.attributes.replay {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn replay(initial: StateId, events: &[EventId]) -> Result<#ty, ReplayError<StateId, EventId>> {
+                    #ty::replay(initial, events.iter().cloned())
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let fold_events = if self.machine.attributes.fold_events {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn fold_events(machine: #ty, events: &[EventId]) -> Result<#ty, (#ty, TransitionError<StateId, EventId>)> {
+                    machine.fold_events(events.iter().cloned())
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let next = if self.machine.table() {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn next(state: StateId, event: EventId) -> Option<StateId> {
+                    <#ty as Table>::next(state, event)
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let apply = if self.machine.apply() {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply(machine: &mut #ty, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply_named(machine: &mut #ty, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply_named(name)
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let history = if self.machine.attributes.undo.is_some() {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn history_apply(history: &mut History, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    history.apply(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn history_undo(history: &mut History) -> Option<EventId> {
+                    history.undo()
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        let pushdown = if self.machine.attributes.pushdown.is_some() {
+            quote! {
+                #[::sm::export::no_panic::no_panic]
+                pub fn pushdown_push(pushdown: &mut Pushdown, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    pushdown.push(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn pushdown_pop(pushdown: &mut Pushdown) -> Option<StateId> {
+                    pushdown.pop()
+                }
+            }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote! {
+            pub mod panic_free {
+                use super::*;
+
+                #constructors
+                #replay
+                #fold_events
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn state_id(machine: &#ty) -> StateId {
+                    machine.state_id()
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn trigger_id(machine: &#ty) -> Option<EventId> {
+                    machine.trigger_id()
+                }
+
+                #next
+                #apply
+                #history
+                #pushdown
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_no_panic_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[dynamic]
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey { Locked => Unlocked }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            pub mod panic_free {
+                use super::*;
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn new(state: StateId) -> Option<Machine> {
+                    Machine::new(state)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn state_id(machine: &Machine) -> StateId {
+                    machine.state_id()
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn trigger_id(machine: &Machine) -> Option<EventId> {
+                    machine.trigger_id()
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn next(state: StateId, event: EventId) -> Option<StateId> {
+                    <Machine as Table>::next(state, event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply(machine: &mut Machine, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply_named(machine: &mut Machine, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply_named(name)
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        NoPanic {
+            machine: &machine,
+            ty: parse_quote! { Machine },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_no_panic_undo_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[undo(4)]
+            #[replay]
+            #[fold_events]
+            Lock {
+                InitialStates { Locked }
+
+                TurnKey { Locked => Unlocked }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            pub mod panic_free {
+                use super::*;
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn from_ids(state: StateId, event: Option<EventId>) -> Option<Variant> {
+                    Variant::from_ids(state, event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn replay(initial: StateId, events: &[EventId]) -> Result<Variant, ReplayError<StateId, EventId>> {
+                    Variant::replay(initial, events.iter().cloned())
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn fold_events(machine: Variant, events: &[EventId]) -> Result<Variant, (Variant, TransitionError<StateId, EventId>)> {
+                    machine.fold_events(events.iter().cloned())
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn state_id(machine: &Variant) -> StateId {
+                    machine.state_id()
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn trigger_id(machine: &Variant) -> Option<EventId> {
+                    machine.trigger_id()
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn next(state: StateId, event: EventId) -> Option<StateId> {
+                    <Variant as Table>::next(state, event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply(machine: &mut Variant, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn apply_named(machine: &mut Variant, name: &str) -> Result<(), TransitionError<StateId, EventId>> {
+                    machine.apply_named(name)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn history_apply(history: &mut History, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    history.apply(event)
+                }
+
+                #[::sm::export::no_panic::no_panic]
+                pub fn history_undo(history: &mut History) -> Option<EventId> {
+                    history.undo()
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        NoPanic {
+            machine: &machine,
+            ty: parse_quote! { Variant },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,107 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+/// Persist generates the `save_to` and `load_from` methods of the `Variant`
+/// type of a machine, using the `sm::persist::Store` trait, and implements
+/// `sm::persist::Restore` for it.
+#[derive(Debug)]
+pub(crate) struct Persist;
+
+impl ToTokens for Persist {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        tokens.extend(quote! {
+            impl Variant {
+                pub fn save_to<St>(&self, store: &mut St, key: &str) -> Result<(), St::Error>
+                where
+                    St: ::sm::persist::Store<StateId, EventId>,
+                {
+                    store.save(key, ::sm::persist::Snapshot {
+                        state: self.state_id(),
+                        trigger: self.trigger_id(),
+                    })
+                }
+
+                pub fn load_from<St>(store: &St, key: &str) -> Result<Option<Self>, ::sm::persist::Error<St::Error>>
+                where
+                    St: ::sm::persist::Store<StateId, EventId>,
+                {
+                    match store.load(key) {
+                        Result::Ok(Option::Some(snapshot)) => Variant::from_ids(snapshot.state, snapshot.trigger)
+                            .map(Option::Some)
+                            .ok_or(::sm::persist::Error::Invalid),
+                        Result::Ok(Option::None) => Result::Ok(Option::None),
+                        Result::Err(err) => Result::Err(::sm::persist::Error::Store(err)),
+                    }
+                }
+            }
+
+            impl ::sm::persist::Restore for Variant {
+                fn snapshot(&self) -> ::sm::persist::Snapshot<StateId, EventId> {
+                    ::sm::persist::Snapshot {
+                        state: self.state_id(),
+                        trigger: self.trigger_id(),
+                    }
+                }
+
+                fn restore(snapshot: ::sm::persist::Snapshot<StateId, EventId>) -> Option<Self> {
+                    Variant::from_ids(snapshot.state, snapshot.trigger)
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use proc_macro2::TokenStream;
+
+    #[test]
+    fn test_persist_to_tokens() {
+        let left = quote! {
+            impl Variant {
+                pub fn save_to<St>(&self, store: &mut St, key: &str) -> Result<(), St::Error>
+                where
+                    St: ::sm::persist::Store<StateId, EventId>,
+                {
+                    store.save(key, ::sm::persist::Snapshot {
+                        state: self.state_id(),
+                        trigger: self.trigger_id(),
+                    })
+                }
+
+                pub fn load_from<St>(store: &St, key: &str) -> Result<Option<Self>, ::sm::persist::Error<St::Error>>
+                where
+                    St: ::sm::persist::Store<StateId, EventId>,
+                {
+                    match store.load(key) {
+                        Result::Ok(Option::Some(snapshot)) => Variant::from_ids(snapshot.state, snapshot.trigger)
+                            .map(Option::Some)
+                            .ok_or(::sm::persist::Error::Invalid),
+                        Result::Ok(Option::None) => Result::Ok(Option::None),
+                        Result::Err(err) => Result::Err(::sm::persist::Error::Store(err)),
+                    }
+                }
+            }
+
+            impl ::sm::persist::Restore for Variant {
+                fn snapshot(&self) -> ::sm::persist::Snapshot<StateId, EventId> {
+                    ::sm::persist::Snapshot {
+                        state: self.state_id(),
+                        trigger: self.trigger_id(),
+                    }
+                }
+
+                fn restore(snapshot: ::sm::persist::Snapshot<StateId, EventId>) -> Option<Self> {
+                    Variant::from_ids(snapshot.state, snapshot.trigger)
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Persist.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
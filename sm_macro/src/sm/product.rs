@@ -0,0 +1,241 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{braced, Error, Ident};
+
+use crate::sm::event::Event;
+use crate::sm::initial_state::{InitialState, InitialStates};
+use crate::sm::machine::{Machine, Machines};
+use crate::sm::state::State;
+use crate::sm::transition::{Transition, Transitions};
+
+/// Product is the input of the `product!` macro: two machines, composed into
+/// their synchronous product. Events known to both machines are shared, and
+/// only transition the product when both machines transition on them. Any
+/// other event transitions the machine it belongs to, leaving the other one
+/// unchanged.
+///
+/// A state of the product pairs a state of each machine, and is named after
+/// both, `Idle` and `Listening` becoming `IdleListening`. Only the states
+/// reachable from the initial states are generated.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Product {
+    pub machine: Machine,
+}
+
+impl Parse for Product {
+    /// example product tokens:
+    ///
+    /// ```text
+    /// Session {
+    ///     Client { ... }
+    ///     Server { ... }
+    /// }
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `Session { ... }`
+        //  ^^^^^^^
+        let name: Ident = input.parse()?;
+
+        // `Session { ... }`
+        //            ^^^
+        let block_product;
+        braced!(block_product in input);
+
+        // `Client { ... }`
+        //  ^^^^^^^^^^^^^^
+        let left = Machine::parse(&block_product)?;
+
+        // `Server { ... }`
+        //  ^^^^^^^^^^^^^^
+        let right = Machine::parse(&block_product)?;
+
+        if !block_product.is_empty() {
+            return Err(block_product.error("expected exactly two machines"));
+        }
+
+        let machine = compose(name, &left, &right)?;
+
+        Ok(Product { machine })
+    }
+}
+
+impl ToTokens for Product {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Machines(vec![self.machine.clone()]).to_tokens(tokens);
+    }
+}
+
+/// compose builds the synchronous product of two machines, exploring the
+/// pairs of states reachable from their initial states.
+fn compose(name: Ident, left: &Machine, right: &Machine) -> Result<Machine> {
+    let left_events = left.events().0;
+    let right_events = right.events().0;
+
+    let mut events: Vec<Event> = left_events.clone();
+    for event in &right_events {
+        if !events.iter().any(|e| e.name == event.name) {
+            events.push(event.clone());
+        }
+    }
+
+    let mut initial_states = Vec::new();
+    let mut pending: Vec<(Ident, Ident)> = Vec::new();
+
+    for l in &left.initial_states.0 {
+        for r in &right.initial_states.0 {
+            pending.push((l.name.clone(), r.name.clone()));
+            initial_states.push(InitialState {
+                name: pair(&l.name, &r.name),
+            });
+        }
+    }
+
+    let mut seen: Vec<(Ident, Ident)> = pending.clone();
+    let mut transitions = Vec::new();
+
+    while let Some((l, r)) = pending.pop() {
+        for event in &events {
+            let in_left = left_events.iter().any(|e| e.name == event.name);
+            let in_right = right_events.iter().any(|e| e.name == event.name);
+
+            let next_left = if in_left { target(left, &l, &event.name) } else { Some(l.clone()) };
+            let next_right = if in_right { target(right, &r, &event.name) } else { Some(r.clone()) };
+
+            let (to_left, to_right) = match (next_left, next_right) {
+                (Some(to_left), Some(to_right)) => (to_left, to_right),
+                _ => continue,
+            };
+
+            transitions.push(Transition {
+                event: event.clone(),
+                from: State { name: pair(&l, &r) },
+                to: State {
+                    name: pair(&to_left, &to_right),
+                },
+                weight: None,
+            });
+
+            if !seen.iter().any(|(sl, sr)| *sl == to_left && *sr == to_right) {
+                seen.push((to_left.clone(), to_right.clone()));
+                pending.push((to_left, to_right));
+            }
+        }
+    }
+
+    // Pairs are named by concatenating their states, which could make two
+    // different pairs collide, such as `AB` and `C`, and `A` and `BC`.
+    for (i, (l, r)) in seen.iter().enumerate() {
+        let name = pair(l, r);
+        if let Some((ol, or)) = seen[..i].iter().find(|(ol, or)| pair(ol, or) == name) {
+            return Err(Error::new(
+                name.span(),
+                format!(
+                    "states `{}` and `{}`, and `{}` and `{}` both compose into `{}`",
+                    ol, or, l, r, name
+                ),
+            ));
+        }
+    }
+
+    transitions.sort_by_key(|t: &Transition| (t.from.name.to_string(), t.event.name.to_string()));
+
+    Ok(Machine {
+        name,
+        attributes: Default::default(),
+        externals: Default::default(),
+        initial_states: InitialStates(initial_states),
+        transitions: Transitions(transitions),
+    })
+}
+
+/// target returns the state a machine transitions to, from the given state,
+/// on the given event.
+fn target(machine: &Machine, from: &Ident, event: &Ident) -> Option<Ident> {
+    machine
+        .transitions
+        .0
+        .iter()
+        .find(|t| t.from.name == *from && t.event.name == *event)
+        .map(|t| t.to.name.clone())
+}
+
+fn pair(left: &Ident, right: &Ident) -> Ident {
+    let name: String = format!("{}{}", left, right);
+    Ident::new(&name, left.span())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_product_parse() {
+        let left: Product = syn::parse2(quote! {
+            Session {
+                Client {
+                    InitialStates { Idle }
+
+                    Connect { Idle => Waiting }
+                    Accept { Waiting => Connected }
+                }
+
+                Server {
+                    InitialStates { Listening }
+
+                    Accept { Listening => Serving }
+                    Tick { Listening, Serving => Listening }
+                }
+            }
+        })
+        .unwrap();
+
+        let right: Machine = syn::parse2(quote! {
+            Session {
+                InitialStates { IdleListening }
+
+                Connect { IdleListening => WaitingListening }
+                Tick { IdleListening => IdleListening }
+                Accept { WaitingListening => ConnectedServing }
+                Tick { WaitingListening => WaitingListening }
+                Tick { ConnectedServing => ConnectedListening }
+                Tick { ConnectedListening => ConnectedListening }
+            }
+        })
+        .unwrap();
+
+        let mut expected = right.transitions.0.clone();
+        expected.sort_by_key(|t| (t.from.name.to_string(), t.event.name.to_string()));
+
+        assert_eq!(left.machine.name, right.name);
+        assert_eq!(left.machine.initial_states, right.initial_states);
+        assert_eq!(left.machine.transitions.0, expected);
+    }
+
+    #[test]
+    fn test_product_parse_collision() {
+        let product: Result<Product> = syn::parse2(quote! {
+            Pair {
+                Left {
+                    InitialStates { A, AB }
+
+                    Go { A => A }
+                }
+
+                Right {
+                    InitialStates { BC, C }
+
+                    Go { BC => BC  C => C }
+                }
+            }
+        });
+
+        assert!(product.is_err());
+    }
+}
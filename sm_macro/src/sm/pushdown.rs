@@ -0,0 +1,193 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// Pushdown generates the `Pushdown` type of a machine marked
+/// `#[pushdown(n)]`: a wrapper around the `ty` type of the machine, with a
+/// stack of up to `n` machines. Its `push` method applies an event, and pushes
+/// the machine it transitioned from on the stack, while its `pop` method
+/// returns to the machine on top of the stack, whichever state it was in.
+/// Events applied through `apply` leave the stack untouched.
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Pushdown<'a> {
+    pub machine: &'a Machine,
+    pub ty: Ident,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Pushdown<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let ty = &self.ty;
+        let size = match self.machine.attributes.pushdown {
+            Some(size) => size,
+            None => return,
+        };
+
+        // Dynamic machines are rebuilt from their ids directly, as they're
+        // defined in the same module, while the `Variant` enum knows which of
+        // its variants the ids belong to.
+        let restore = if self.machine.attributes.dynamic {
+            quote! { #ty { state, trigger } }
+        } else {
+            quote! { #ty::from_ids(state, trigger)? }
+        };
+
+        tokens.extend(quote! {
+            #[derive(Clone, Debug)]
+            pub struct Pushdown {
+                machine: #ty,
+                stack: [Option<(StateId, Option<EventId>)>; #size],
+                len: usize,
+            }
+
+            impl Pushdown {
+                #[inline]
+                pub fn new(machine: #ty) -> Self {
+                    Pushdown {
+                        machine,
+                        stack: [Option::None; #size],
+                        len: 0,
+                    }
+                }
+
+                #[inline]
+                pub fn machine(&self) -> &#ty {
+                    &self.machine
+                }
+
+                #[inline]
+                pub fn into_machine(self) -> #ty {
+                    self.machine
+                }
+
+                #[inline]
+                pub fn depth(&self) -> usize {
+                    self.len
+                }
+
+                pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    self.machine.apply(event)
+                }
+
+                pub fn push(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    let below = (self.machine.state_id(), self.machine.trigger_id());
+                    let slot = match self.stack.get_mut(self.len) {
+                        Option::Some(slot) => slot,
+                        Option::None => return Result::Err(TransitionError::StackFull(event)),
+                    };
+
+                    self.machine.apply(event)?;
+                    *slot = Option::Some(below);
+                    self.len += 1;
+
+                    Result::Ok(())
+                }
+
+                pub fn pop(&mut self) -> Option<StateId> {
+                    let top = self.len.checked_sub(1)?;
+                    let (state, trigger) = self.stack.get_mut(top)?.take()?;
+
+                    self.machine = #restore;
+                    self.len = top;
+
+                    Option::Some(state)
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_pushdown_to_tokens() {
+        let machine: Machine = syn::parse2(quote! {
+            #[pushdown(4)]
+            Game {
+                InitialStates { Playing }
+
+                Pause { Playing => Paused }
+            }
+        })
+        .unwrap();
+
+        let left = quote! {
+            #[derive(Clone, Debug)]
+            pub struct Pushdown {
+                machine: Variant,
+                stack: [Option<(StateId, Option<EventId>)>; 4usize],
+                len: usize,
+            }
+
+            impl Pushdown {
+                #[inline]
+                pub fn new(machine: Variant) -> Self {
+                    Pushdown {
+                        machine,
+                        stack: [Option::None; 4usize],
+                        len: 0,
+                    }
+                }
+
+                #[inline]
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+
+                #[inline]
+                pub fn into_machine(self) -> Variant {
+                    self.machine
+                }
+
+                #[inline]
+                pub fn depth(&self) -> usize {
+                    self.len
+                }
+
+                pub fn apply(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    self.machine.apply(event)
+                }
+
+                pub fn push(&mut self, event: EventId) -> Result<(), TransitionError<StateId, EventId>> {
+                    let below = (self.machine.state_id(), self.machine.trigger_id());
+                    let slot = match self.stack.get_mut(self.len) {
+                        Option::Some(slot) => slot,
+                        Option::None => return Result::Err(TransitionError::StackFull(event)),
+                    };
+
+                    self.machine.apply(event)?;
+                    *slot = Option::Some(below);
+                    self.len += 1;
+
+                    Result::Ok(())
+                }
+
+                pub fn pop(&mut self) -> Option<StateId> {
+                    let top = self.len.checked_sub(1)?;
+                    let (state, trigger) = self.stack.get_mut(top)?.take()?;
+
+                    self.machine = Variant::from_ids(state, trigger)?;
+                    self.len = top;
+
+                    Option::Some(state)
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Pushdown {
+            machine: &machine,
+            ty: parse_quote! { Variant },
+        }
+        .to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,176 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::sm::machine::Machine;
+
+/// Python generates a [PyO3] class wrapping the `Variant` of a machine marked
+/// `#[python]`, exposed to Python under the name of the machine. States and
+/// events are identified by their names. The code generated by PyO3 refers to
+/// `::core`, so crates on the 2015 edition have to declare `extern crate core`.
+///
+/// [PyO3]: https://docs.rs/pyo3
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Python<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Python<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name: String = self.machine.name.to_string();
+        let not_initial: String = format!("not an initial state of {}", name);
+        let not_event: String = format!("not an event of {}", name);
+
+        tokens.extend(quote! {
+            #[::sm::export::pyo3::pyclass(name = #name, crate = "::sm::export::pyo3")]
+            #[derive(Debug)]
+            pub struct PyMachine {
+                machine: Variant,
+            }
+
+            #[::sm::export::pyo3::pymethods(crate = "::sm::export::pyo3")]
+            impl PyMachine {
+                #[new]
+                pub fn new(initial: &str) -> ::sm::export::pyo3::PyResult<Self> {
+                    StateId::from_name(initial)
+                        .and_then(|state| Variant::from_ids(state, Option::None))
+                        .map(|machine| PyMachine { machine })
+                        .ok_or_else(|| ::sm::export::pyo3::exceptions::PyValueError::new_err(#not_initial))
+                }
+
+                pub fn apply(&mut self, event: &str) -> ::sm::export::pyo3::PyResult<()> {
+                    let event = EventId::from_name(event)
+                        .ok_or_else(|| ::sm::export::pyo3::exceptions::PyValueError::new_err(#not_event))?;
+
+                    match self.machine.clone().try_transition(event) {
+                        Result::Ok(machine) => {
+                            self.machine = machine;
+                            Result::Ok(())
+                        }
+                        Result::Err(_) => Result::Err(::sm::export::pyo3::exceptions::PyValueError::new_err(
+                            "event not permitted in the current state",
+                        )),
+                    }
+                }
+
+                pub fn state(&self) -> &'static str {
+                    self.machine.state_id().name()
+                }
+
+                pub fn permitted_events(&self) -> Vec<&'static str> {
+                    let state = self.machine.state_id();
+
+                    EventId::ALL
+                        .iter()
+                        .filter(|event| Variant::next(state, **event).is_some())
+                        .map(|event| event.name())
+                        .collect()
+                }
+            }
+
+            impl PyMachine {
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_python_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            #[::sm::export::pyo3::pyclass(name = "Lock", crate = "::sm::export::pyo3")]
+            #[derive(Debug)]
+            pub struct PyMachine {
+                machine: Variant,
+            }
+
+            #[::sm::export::pyo3::pymethods(crate = "::sm::export::pyo3")]
+            impl PyMachine {
+                #[new]
+                pub fn new(initial: &str) -> ::sm::export::pyo3::PyResult<Self> {
+                    StateId::from_name(initial)
+                        .and_then(|state| Variant::from_ids(state, Option::None))
+                        .map(|machine| PyMachine { machine })
+                        .ok_or_else(|| ::sm::export::pyo3::exceptions::PyValueError::new_err("not an initial state of Lock"))
+                }
+
+                pub fn apply(&mut self, event: &str) -> ::sm::export::pyo3::PyResult<()> {
+                    let event = EventId::from_name(event)
+                        .ok_or_else(|| ::sm::export::pyo3::exceptions::PyValueError::new_err("not an event of Lock"))?;
+
+                    match self.machine.clone().try_transition(event) {
+                        Result::Ok(machine) => {
+                            self.machine = machine;
+                            Result::Ok(())
+                        }
+                        Result::Err(_) => Result::Err(::sm::export::pyo3::exceptions::PyValueError::new_err(
+                            "event not permitted in the current state",
+                        )),
+                    }
+                }
+
+                pub fn state(&self) -> &'static str {
+                    self.machine.state_id().name()
+                }
+
+                pub fn permitted_events(&self) -> Vec<&'static str> {
+                    let state = self.machine.state_id();
+
+                    EventId::ALL
+                        .iter()
+                        .filter(|event| Variant::next(state, **event).is_some())
+                        .map(|event| event.name())
+                        .collect()
+                }
+            }
+
+            impl PyMachine {
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Python { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,138 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::{Error, Ident};
+
+use crate::sm::initial_state::{InitialState, InitialStates};
+use crate::sm::machine::{Machine, Machines};
+use crate::sm::transition::{Transition, Transitions};
+
+/// Reverse is the input of the `reverse!` macro: a machine, generated along
+/// with a companion machine whose transitions are reversed. The companion is
+/// named after the machine, suffixed with `Reversed`, and every state is one
+/// of its initial states, so it can walk back from any state.
+///
+/// A machine can only be reversed if no state is reached on the same event
+/// from two different states, as the reversed transition would be ambiguous.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Reverse {
+    pub machine: Machine,
+    pub reversed: Machine,
+}
+
+impl Parse for Reverse {
+    /// example reverse tokens:
+    ///
+    /// ```text
+    /// Lock {
+    ///     InitialStates { ... }
+    ///
+    ///     TurnKey { ... }
+    /// }
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `Lock { ... }`
+        //  ^^^^^^^^^^^^
+        let machine = Machine::parse(input)?;
+
+        let mut transitions: Vec<Transition> = Vec::new();
+        for transition in &machine.transitions.0 {
+            let conflict = transitions.iter().find(|t| {
+                t.event.name == transition.event.name && t.from.name == transition.to.name
+            });
+
+            if let Some(conflict) = conflict {
+                return Err(Error::new(
+                    transition.event.name.span(),
+                    format!(
+                        "`{}` is reached on `{}` from both `{}` and `{}`",
+                        transition.to.name,
+                        transition.event.name,
+                        conflict.to.name,
+                        transition.from.name
+                    ),
+                ));
+            }
+
+            transitions.push(Transition {
+                event: transition.event.clone(),
+                from: transition.to.clone(),
+                to: transition.from.clone(),
+                weight: transition.weight,
+            });
+        }
+
+        let initial_states = machine
+            .states()
+            .0
+            .into_iter()
+            .map(|state| InitialState { name: state.name })
+            .collect();
+
+        let reversed = Machine {
+            name: Ident::new(&format!("{}Reversed", machine.name), Span::call_site()),
+            attributes: machine.attributes.clone(),
+            externals: Default::default(),
+            initial_states: InitialStates(initial_states),
+            transitions: Transitions(transitions),
+        };
+
+        Ok(Reverse { machine, reversed })
+    }
+}
+
+impl ToTokens for Reverse {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Machines(vec![self.machine.clone(), self.reversed.clone()]).to_tokens(tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    #[test]
+    fn test_reverse_parse() {
+        let left: Reverse = syn::parse2(quote! {
+            Door {
+                InitialStates { Closed }
+
+                Open { Closed => Opened }
+                Close { Opened => Closed }
+                Lock { Closed => Locked }
+            }
+        })
+        .unwrap();
+
+        let right: Machine = syn::parse2(quote! {
+            DoorReversed {
+                InitialStates { Closed, Opened, Locked }
+
+                Open { Opened => Closed }
+                Close { Closed => Opened }
+                Lock { Locked => Closed }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(left.reversed, right);
+    }
+
+    #[test]
+    fn test_reverse_parse_ambiguous() {
+        let reverse: Result<Reverse> = syn::parse2(quote! {
+            Door {
+                InitialStates { Closed }
+
+                Slam { Opened, Ajar => Closed }
+            }
+        });
+
+        assert!(reverse.is_err());
+    }
+}
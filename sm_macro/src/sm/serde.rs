@@ -0,0 +1,107 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+/// Serde generates [serde] implementations for the `StateId`, `EventId` and
+/// `Variant` types of a machine, using the helpers of the `sm::serde` module.
+///
+/// [serde]: https://serde.rs
+#[derive(Debug)]
+pub(crate) struct Serde;
+
+impl ToTokens for Serde {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        for name in &["StateId", "EventId"] {
+            let id = Ident::new(name, Span::call_site());
+
+            tokens.extend(quote! {
+                impl ::sm::serde::Serialize for #id {
+                    fn serialize<S: ::sm::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                        ::sm::serde::serialize_id(*self, #name, serializer)
+                    }
+                }
+
+                impl<'de> ::sm::serde::Deserialize<'de> for #id {
+                    fn deserialize<D: ::sm::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                        ::sm::serde::deserialize_id(#name, deserializer)
+                    }
+                }
+            });
+        }
+
+        tokens.extend(quote! {
+            impl ::sm::serde::Serialize for Variant {
+                fn serialize<S: ::sm::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::sm::serde::Serialize::serialize(&(self.state_id(), self.trigger_id()), serializer)
+                }
+            }
+
+            impl<'de> ::sm::serde::Deserialize<'de> for Variant {
+                fn deserialize<D: ::sm::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let (state, trigger): (StateId, Option<EventId>) =
+                        ::sm::serde::Deserialize::deserialize(deserializer)?;
+
+                    Variant::from_ids(state, trigger)
+                        .ok_or_else(|| ::sm::serde::invalid_machine(state, trigger))
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::format;
+    use proc_macro2::TokenStream;
+
+    #[test]
+    fn test_serde_to_tokens() {
+        let left = quote! {
+            impl ::sm::serde::Serialize for StateId {
+                fn serialize<S: ::sm::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::sm::serde::serialize_id(*self, "StateId", serializer)
+                }
+            }
+
+            impl<'de> ::sm::serde::Deserialize<'de> for StateId {
+                fn deserialize<D: ::sm::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    ::sm::serde::deserialize_id("StateId", deserializer)
+                }
+            }
+
+            impl ::sm::serde::Serialize for EventId {
+                fn serialize<S: ::sm::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::sm::serde::serialize_id(*self, "EventId", serializer)
+                }
+            }
+
+            impl<'de> ::sm::serde::Deserialize<'de> for EventId {
+                fn deserialize<D: ::sm::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    ::sm::serde::deserialize_id("EventId", deserializer)
+                }
+            }
+
+            impl ::sm::serde::Serialize for Variant {
+                fn serialize<S: ::sm::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::sm::serde::Serialize::serialize(&(self.state_id(), self.trigger_id()), serializer)
+                }
+            }
+
+            impl<'de> ::sm::serde::Deserialize<'de> for Variant {
+                fn deserialize<D: ::sm::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let (state, trigger): (StateId, Option<EventId>) =
+                        ::sm::serde::Deserialize::deserialize(deserializer)?;
+
+                    Variant::from_ids(state, trigger)
+                        .ok_or_else(|| ::sm::serde::invalid_machine(state, trigger))
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Serde.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -1,25 +1,45 @@
+use alloc::string::{String, ToString};
 use alloc::vec::{IntoIter, Vec};
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
 use quote::ToTokens;
 use syn::parse::{Parse, ParseStream, Result};
 use syn::Ident;
 
+use crate::sm::ffi::snake_case;
+use crate::sm::transition::Transitions;
+
 #[derive(Debug, PartialEq)]
 pub(crate) struct States(pub Vec<State>);
 
 impl ToTokens for States {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.eq_tokens(tokens, true, None);
+    }
+}
+
+impl States {
+    /// eq_tokens generates the states, the `PartialEq` implementations comparing
+    /// them, and the `StateId` enum. Without `cross_eq`, each of the states
+    /// can only be compared to itself. With `transitions`, the states are
+    /// documented with the transitions out of and into them.
+    pub(crate) fn eq_tokens(&self, tokens: &mut TokenStream, cross_eq: bool, transitions: Option<&Transitions>) {
         for state in &self.0 {
-            state.to_tokens(tokens);
+            let doc = transitions.map_or_else(Vec::new, |t| t.doc(&state.name));
+            state.doc_tokens(tokens, &doc);
 
             let name = &state.name;
             for other in &self.0 {
                 let other = &other.name;
                 let eq = name == other;
 
+                if !cross_eq && !eq {
+                    continue;
+                }
+
                 tokens.extend(quote! {
                     impl PartialEq<#other> for #name {
+                        #[inline]
                         fn eq(&self, _: & #other) -> bool {
                             #eq
                         }
@@ -27,11 +47,55 @@ impl ToTokens for States {
                 });
             }
         }
+
+        self.id_tokens(tokens);
+    }
+
+    /// id_tokens generates the `StateId` enum, without the types of the states
+    /// themselves.
+    pub(crate) fn id_tokens(&self, tokens: &mut TokenStream) {
+        let names: Vec<&Ident> = self.0.iter().map(|s| &s.name).collect();
+        let strings: Vec<String> = names.iter().map(|n| n.to_string()).collect();
+        let indices: Vec<usize> = (0..names.len()).collect();
+        let names = &names;
+
+        // An enum without variants can't have a `repr`.
+        let repr = if cfg!(feature = "ffi") && !names.is_empty() {
+            quote! { #[repr(C)] }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote! {
+            #repr
+            #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+            pub enum StateId {
+                #(#names),*
+            }
+
+            impl Id for StateId {
+                const ALL: &'static [Self] = &[#(StateId::#names),*];
+                const NAMES: &'static [&'static str] = &[#(#strings),*];
+
+                #[inline]
+                fn index(self) -> usize {
+                    match self {
+                        #(StateId::#names => #indices),*
+                    }
+                }
+            }
+
+            impl From<StateId> for &'static str {
+                #[inline]
+                fn from(id: StateId) -> Self {
+                    id.name()
+                }
+            }
+        });
     }
 }
 
-#[allow(single_use_lifetimes)] // TODO: how to fix this?
-impl<'a> IntoIterator for &'a States {
+impl IntoIterator for &States {
     type Item = State;
     type IntoIter = IntoIter<State>;
 
@@ -61,12 +125,45 @@ impl Parse for State {
 
 impl ToTokens for State {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.doc_tokens(tokens, &[]);
+    }
+}
+
+impl State {
+    /// doc_tokens generates the state, with the provided lines of
+    /// documentation.
+    pub(crate) fn doc_tokens(&self, tokens: &mut TokenStream, doc: &[String]) {
         let name = &self.name;
+        let string = name.to_string();
+        let constant = Ident::new(&snake_case(&string).to_uppercase(), Span::call_site());
 
         tokens.extend(quote! {
+            #(#[doc = #doc])*
             #[derive(Clone, Copy, Debug, Eq)]
             pub struct #name;
             impl State for #name {}
+
+            pub const #constant: #name = #name;
+
+            impl From<#name> for StateId {
+                #[inline]
+                fn from(_: #name) -> Self {
+                    StateId::#name
+                }
+            }
+
+            impl From<#name> for &'static str {
+                #[inline]
+                fn from(_: #name) -> Self {
+                    #string
+                }
+            }
+
+            impl Identify for #name {
+                type Id = StateId;
+
+                const ID: StateId = StateId::#name;
+            }
         });
     }
 }
@@ -99,6 +196,28 @@ mod tests {
             #[derive(Clone, Copy, Debug, Eq)]
             pub struct Unlocked;
             impl State for Unlocked {}
+
+            pub const UNLOCKED: Unlocked = Unlocked;
+
+            impl From<Unlocked> for StateId {
+                #[inline]
+                fn from(_: Unlocked) -> Self {
+                    StateId::Unlocked
+                }
+            }
+
+            impl From<Unlocked> for &'static str {
+                #[inline]
+                fn from(_: Unlocked) -> Self {
+                    "Unlocked"
+                }
+            }
+
+            impl Identify for Unlocked {
+                type Id = StateId;
+
+                const ID: StateId = StateId::Unlocked;
+            }
         };
 
         let mut right = TokenStream::new();
@@ -123,13 +242,37 @@ mod tests {
             pub struct Locked;
             impl State for Locked {}
 
+            pub const LOCKED: Locked = Locked;
+
+            impl From<Locked> for StateId {
+                #[inline]
+                fn from(_: Locked) -> Self {
+                    StateId::Locked
+                }
+            }
+
+            impl From<Locked> for &'static str {
+                #[inline]
+                fn from(_: Locked) -> Self {
+                    "Locked"
+                }
+            }
+
+            impl Identify for Locked {
+                type Id = StateId;
+
+                const ID: StateId = StateId::Locked;
+            }
+
             impl PartialEq<Locked> for Locked {
+                #[inline]
                 fn eq(&self, _: &Locked) -> bool {
                     true
                 }
             }
 
             impl PartialEq<Unlocked> for Locked {
+                #[inline]
                 fn eq(&self, _: &Unlocked) -> bool {
                     false
                 }
@@ -139,17 +282,67 @@ mod tests {
             pub struct Unlocked;
             impl State for Unlocked {}
 
+            pub const UNLOCKED: Unlocked = Unlocked;
+
+            impl From<Unlocked> for StateId {
+                #[inline]
+                fn from(_: Unlocked) -> Self {
+                    StateId::Unlocked
+                }
+            }
+
+            impl From<Unlocked> for &'static str {
+                #[inline]
+                fn from(_: Unlocked) -> Self {
+                    "Unlocked"
+                }
+            }
+
+            impl Identify for Unlocked {
+                type Id = StateId;
+
+                const ID: StateId = StateId::Unlocked;
+            }
+
             impl PartialEq<Locked> for Unlocked {
+                #[inline]
                 fn eq(&self, _: & Locked) -> bool {
                     false
                 }
             }
 
             impl PartialEq<Unlocked> for Unlocked {
+                #[inline]
                 fn eq(&self, _: & Unlocked) -> bool {
                     true
                 }
             }
+
+            #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+            pub enum StateId {
+                Locked,
+                Unlocked
+            }
+
+            impl Id for StateId {
+                const ALL: &'static [Self] = &[StateId::Locked, StateId::Unlocked];
+                const NAMES: &'static [&'static str] = &["Locked", "Unlocked"];
+
+                #[inline]
+                fn index(self) -> usize {
+                    match self {
+                        StateId::Locked => 0usize,
+                        StateId::Unlocked => 1usize
+                    }
+                }
+            }
+
+            impl From<StateId> for &'static str {
+                #[inline]
+                fn from(id: StateId) -> Self {
+                    id.name()
+                }
+            }
         };
 
         let mut right = TokenStream::new();
@@ -0,0 +1,240 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use proc_macro2::TokenStream;
+use quote::ToTokens;
+use syn::parse::{Parse, ParseStream, Result};
+use syn::punctuated::Punctuated;
+use syn::{braced, Error, Ident, Token};
+
+use crate::sm::initial_state::{InitialState, InitialStates};
+use crate::sm::machine::{Machine, Machines};
+use crate::sm::transition::Transitions;
+
+/// Subset is the input of the `subset!` macro: the definition of a machine,
+/// followed by a restriction to some of its states and events. The restricted
+/// machine is generated under the name of the restriction.
+///
+/// The restriction keeps the transitions between the listed states, on the
+/// listed events. Omitting the states or the events keeps all of them. The
+/// initial states of the machine that are kept remain initial states, and
+/// every state of the restricted machine has to be reachable from them.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Subset {
+    pub machine: Machine,
+}
+
+impl Parse for Subset {
+    /// example subset tokens:
+    ///
+    /// ```text
+    /// Workflow {
+    ///     InitialStates { ... }
+    ///
+    ///     Submit { ... }
+    ///     View { ... }
+    /// }
+    ///
+    /// ReadOnlyWorkflow {
+    ///     States { ... }
+    ///     Events { ... }
+    /// }
+    /// ```
+    ///
+    fn parse(input: ParseStream<'_>) -> Result<Self> {
+        // `Workflow { ... }`
+        //  ^^^^^^^^^^^^^^^^
+        let machine = Machine::parse(input)?;
+
+        // `ReadOnlyWorkflow { ... }`
+        //  ^^^^^^^^^^^^^^^^
+        let name: Ident = input.parse()?;
+
+        // `ReadOnlyWorkflow { ... }`
+        //                     ^^^
+        let block_subset;
+        braced!(block_subset in input);
+
+        let mut states: Option<Vec<Ident>> = None;
+        let mut events: Option<Vec<Ident>> = None;
+
+        while !block_subset.is_empty() {
+            // `States { Draft, Published }`
+            //  ^^^^^^
+            let key: Ident = block_subset.parse()?;
+
+            // `States { Draft, Published }`
+            //          ^^^^^^^^^^^^^^^^^^^
+            let block_list;
+            braced!(block_list in block_subset);
+            let list: Punctuated<Ident, Token![,]> = block_list.parse_terminated(Ident::parse)?;
+            let list: Vec<Ident> = list.into_iter().collect();
+
+            let (slot, known): (_, Vec<Ident>) = if key == "States" {
+                (&mut states, machine.states().0.into_iter().map(|s| s.name).collect())
+            } else if key == "Events" {
+                (&mut events, machine.events().0.into_iter().map(|e| e.name).collect())
+            } else {
+                return Err(Error::new(
+                    key.span(),
+                    "expected `States { ... }` or `Events { ... }`",
+                ));
+            };
+
+            if slot.is_some() {
+                return Err(Error::new(key.span(), format!("duplicate `{}` block", key)));
+            }
+
+            for item in &list {
+                if !known.contains(item) {
+                    return Err(Error::new(
+                        item.span(),
+                        format!("`{}` is not part of `{}`", item, machine.name),
+                    ));
+                }
+            }
+
+            *slot = Some(list);
+        }
+
+        let keep_state = |state: &Ident| states.as_ref().is_none_or(|s| s.contains(state));
+        let keep_event = |event: &Ident| events.as_ref().is_none_or(|e| e.contains(event));
+
+        let initial_states: Vec<InitialState> = machine
+            .initial_states
+            .0
+            .iter()
+            .filter(|i| keep_state(&i.name))
+            .cloned()
+            .collect();
+
+        if initial_states.is_empty() {
+            return Err(Error::new(name.span(), "none of the initial states are kept"));
+        }
+
+        let transitions: Vec<_> = machine
+            .transitions
+            .0
+            .iter()
+            .filter(|t| keep_event(&t.event.name))
+            .filter(|t| keep_state(&t.from.name) && keep_state(&t.to.name))
+            .cloned()
+            .collect();
+
+        let subset = Machine {
+            name,
+            attributes: machine.attributes.clone(),
+            externals: Default::default(),
+            initial_states: InitialStates(initial_states),
+            transitions: Transitions(transitions),
+        };
+
+        // Every listed event has to keep a transition, and every state has to
+        // be reachable from the initial states.
+        for event in events.iter().flatten() {
+            if !subset.transitions.0.iter().any(|t| t.event.name == *event) {
+                return Err(Error::new(
+                    event.span(),
+                    format!("`{}` has no transitions in `{}`", event, subset.name),
+                ));
+            }
+        }
+
+        let mut reachable: Vec<Ident> =
+            subset.initial_states.0.iter().map(|i| i.name.clone()).collect();
+        let mut i = 0;
+        while i < reachable.len() {
+            for t in &subset.transitions.0 {
+                if t.from.name == reachable[i] && !reachable.contains(&t.to.name) {
+                    reachable.push(t.to.name.clone());
+                }
+            }
+
+            i += 1;
+        }
+
+        let mut all: Vec<Ident> = subset.states().0.into_iter().map(|s| s.name).collect();
+        all.extend(states.into_iter().flatten());
+
+        for state in all {
+            if !reachable.contains(&state) {
+                return Err(Error::new(
+                    state.span(),
+                    format!("`{}` is unreachable in `{}`", state, subset.name),
+                ));
+            }
+        }
+
+        Ok(Subset { machine: subset })
+    }
+}
+
+impl ToTokens for Subset {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        Machines(vec![self.machine.clone()]).to_tokens(tokens);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quote::quote;
+
+    fn workflow(restriction: TokenStream) -> Result<Subset> {
+        syn::parse2(quote! {
+            Workflow {
+                InitialStates { Draft, Published }
+
+                Submit { Draft => Review }
+                Approve { Review => Published }
+                View { Draft, Review, Published => Published }
+            }
+
+            #restriction
+        })
+    }
+
+    #[test]
+    fn test_subset_parse() {
+        let left = workflow(quote! {
+            ReadOnlyWorkflow {
+                States { Draft, Published }
+                Events { View }
+            }
+        })
+        .unwrap();
+
+        let right: Machine = syn::parse2(quote! {
+            ReadOnlyWorkflow {
+                InitialStates { Draft, Published }
+
+                View { Draft, Published => Published }
+            }
+        })
+        .unwrap();
+
+        assert_eq!(left.machine, right);
+    }
+
+    #[test]
+    fn test_subset_parse_unknown() {
+        let subset = workflow(quote! {
+            ReadOnlyWorkflow {
+                Events { Delete }
+            }
+        });
+
+        assert!(subset.is_err());
+    }
+
+    #[test]
+    fn test_subset_parse_unreachable() {
+        let subset = workflow(quote! {
+            ReadOnlyWorkflow {
+                Events { View }
+            }
+        });
+
+        assert!(subset.is_err());
+    }
+}
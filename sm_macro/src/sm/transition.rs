@@ -1,14 +1,17 @@
+use alloc::format;
+use alloc::string::String;
 use alloc::vec::Vec;
 use proc_macro2::TokenStream;
 use quote::{quote, ToTokens};
 use syn::parse::{Parse, ParseStream, Result};
 use syn::token::Comma;
-use syn::{braced, Token};
+use syn::{braced, Error, Ident, LitInt, Token};
 
 use crate::sm::event::Event;
+use crate::sm::instrument;
 use crate::sm::state::State;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Transitions(pub Vec<Transition>);
 
 impl Parse for Transitions {
@@ -19,9 +22,23 @@ impl Parse for Transitions {
     /// Coin { ... }
     /// ```
     ///
+    /// or, grouped by state:
+    ///
+    /// ```text
+    /// state Locked { ... }
+    /// state Unlocked { ... }
+    /// ```
+    ///
     fn parse(input: ParseStream<'_>) -> Result<Self> {
         let mut transitions: Vec<Transition> = Vec::new();
         while !input.is_empty() {
+            // `state Locked { on Coin => Unlocked; }`
+            //  ^^^^^^^^^^^^
+            if input.peek(Ident) && input.peek2(Ident) {
+                transitions.extend(parse_state_block(input)?);
+                continue;
+            }
+
             // `Coin { Locked, Unlocked => Unlocked }`
             //  ^^^^
             let event = Event::parse(input)?;
@@ -57,11 +74,20 @@ impl Parse for Transitions {
                 //                             ^^^^^^^^
                 let to = State::parse(&block_transition)?;
 
+                // `Coin { Locked, Unlocked => Unlocked @ 3 }`
+                //                                      ^^^
+                let weight = parse_weight(&block_transition)?;
+
                 for from in from_states {
                     let event = event.clone();
                     let to = to.clone();
 
-                    transitions.push(Transition { event, from, to })
+                    transitions.push(Transition {
+                        event,
+                        from,
+                        to,
+                        weight,
+                    })
                 }
             }
         }
@@ -70,6 +96,89 @@ impl Parse for Transitions {
     }
 }
 
+/// parse_state_block parses the transitions out of a single state, grouped in
+/// a block of `on Event => State;` statements.
+fn parse_state_block(input: ParseStream<'_>) -> Result<Vec<Transition>> {
+    let mut transitions: Vec<Transition> = Vec::new();
+
+    // `state Locked { on Coin => Unlocked; }`
+    //  ^^^^^
+    let keyword: Ident = input.parse()?;
+    if keyword != "state" {
+        return Err(Error::new(keyword.span(), "expected `state` or an event"));
+    }
+
+    // `state Locked { on Coin => Unlocked; }`
+    //        ^^^^^^
+    let from = State::parse(input)?;
+
+    // `state Locked { on Coin => Unlocked; }`
+    //                 ^^^^^^^^^^^^^^^^^^^^
+    let block_state;
+    braced!(block_state in input);
+
+    while !block_state.is_empty() {
+        // `state Locked { on Coin => Unlocked; }`
+        //                 ^^
+        let keyword: Ident = block_state.parse()?;
+        if keyword != "on" {
+            return Err(Error::new(
+                keyword.span(),
+                "expected `on Event => State;`, entry and exit actions are not supported",
+            ));
+        }
+
+        // `state Locked { on Coin => Unlocked; }`
+        //                    ^^^^
+        let event = Event::parse(&block_state)?;
+
+        // `state Locked { on Coin => Unlocked; }`
+        //                         ^^
+        let _: Token![=>] = block_state.parse()?;
+
+        // `state Locked { on Coin => Unlocked; }`
+        //                            ^^^^^^^^
+        let to = State::parse(&block_state)?;
+
+        // `state Locked { on Coin => Unlocked @ 3; }`
+        //                                     ^^^
+        let weight = parse_weight(&block_state)?;
+
+        // `state Locked { on Coin => Unlocked; }`
+        //                                    ^
+        if !block_state.is_empty() {
+            let _: Token![;] = block_state.parse()?;
+        }
+
+        transitions.push(Transition {
+            event,
+            from: from.clone(),
+            to,
+            weight,
+        });
+    }
+
+    Ok(transitions)
+}
+
+/// parse_weight parses the optional weight following the target state of a
+/// transition, such as `@ 3`, used to pick transitions when simulating the
+/// machine.
+fn parse_weight(input: ParseStream<'_>) -> Result<Option<u32>> {
+    if !input.peek(Token![@]) {
+        return Ok(None);
+    }
+
+    let _: Token![@] = input.parse()?;
+    let weight: LitInt = input.parse()?;
+
+    if weight.value() > u64::from(u32::MAX) {
+        return Err(Error::new(weight.span(), "weights have to fit in a `u32`"));
+    }
+
+    Ok(Some(weight.value() as u32))
+}
+
 impl ToTokens for Transitions {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         for transition in &self.0 {
@@ -78,25 +187,129 @@ impl ToTokens for Transitions {
     }
 }
 
-#[derive(Debug, PartialEq)]
+impl Transitions {
+    /// erased_tokens generates the transitions of a machine marked
+    /// `#[erase_trigger]`, which records the trigger as an `EventId`.
+    pub(crate) fn erased_tokens(&self, tokens: &mut TokenStream) {
+        for transition in &self.0 {
+            transition.erased_tokens(tokens);
+        }
+    }
+
+    /// accepts_tokens generates the `Accepts` implementations of the states,
+    /// for every event they have a transition on.
+    pub(crate) fn accepts_tokens(&self, tokens: &mut TokenStream) {
+        for transition in &self.0 {
+            let event = &transition.event.name;
+            let from = &transition.from.name;
+            let to = &transition.to.name;
+
+            tokens.extend(quote! {
+                impl Accepts<#event> for #from {
+                    type Next = #to;
+                }
+            });
+        }
+    }
+
+    /// doc returns the lines of the documentation of a state, listing the
+    /// transitions out of it, and the transitions leading to it.
+    pub(crate) fn doc(&self, state: &Ident) -> Vec<String> {
+        let mut lines = Vec::new();
+
+        let outgoing: Vec<&Transition> = self.0.iter().filter(|t| t.from.name == *state).collect();
+        if outgoing.is_empty() {
+            lines.push(String::from("Terminal: no event transitions out of this state."));
+        } else {
+            lines.push(String::from("Transitions:"));
+            lines.push(String::new());
+            for t in outgoing {
+                lines.push(format!("- `{}` leads to `{}`", t.event.name, t.to.name));
+            }
+        }
+
+        let incoming: Vec<&Transition> = self.0.iter().filter(|t| t.to.name == *state).collect();
+        if !incoming.is_empty() {
+            lines.push(String::new());
+            lines.push(String::from("Reachable from:"));
+            lines.push(String::new());
+            for t in incoming {
+                lines.push(format!("- `{}` via `{}`", t.from.name, t.event.name));
+            }
+        }
+
+        lines
+    }
+
+    /// zero_sized_tokens generates the transitions of a machine marked
+    /// `#[zero_sized]`, which holds the trigger itself.
+    pub(crate) fn zero_sized_tokens(&self, tokens: &mut TokenStream) {
+        for transition in &self.0 {
+            transition.typed_tokens(tokens, quote! { event });
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub(crate) struct Transition {
     pub event: Event,
     pub from: State,
     pub to: State,
+    pub weight: Option<u32>,
 }
 
 impl ToTokens for Transition {
     fn to_tokens(&self, tokens: &mut TokenStream) {
+        self.typed_tokens(tokens, quote! { Some(event) });
+    }
+}
+
+impl Transition {
+    /// typed_tokens generates a transition for a machine generic over its
+    /// trigger, storing `trigger` as the trigger of the new machine.
+    fn typed_tokens(&self, tokens: &mut TokenStream, trigger: TokenStream) {
         let event = &self.event.name;
         let from = &self.from.name;
         let to = &self.to.name;
 
+        let record = if instrument::ENABLED {
+            quote! { record_transition(StateId::#from, EventId::#event, StateId::#to); }
+        } else {
+            TokenStream::new()
+        };
+
         tokens.extend(quote! {
             impl<E: Event> Transition<#event> for Machine<#from, E> {
                 type Machine = Machine<#to, #event>;
 
+                #[inline]
                 fn transition(self, event: #event) -> Self::Machine {
-                    Machine(#to, Some(event))
+                    #record
+                    Machine(#to, #trigger)
+                }
+            }
+        });
+    }
+
+    fn erased_tokens(&self, tokens: &mut TokenStream) {
+        let event = &self.event.name;
+        let from = &self.from.name;
+        let to = &self.to.name;
+
+        let record = if instrument::ENABLED {
+            quote! { record_transition(StateId::#from, EventId::#event, StateId::#to); }
+        } else {
+            TokenStream::new()
+        };
+
+        tokens.extend(quote! {
+            impl Transition<#event> for Machine<#from> {
+                type Machine = Machine<#to>;
+
+                #[inline]
+                fn transition(self, _: #event) -> Self::Machine {
+                    #record
+                    Machine(#to, Some(EventId::#event))
                 }
             }
         });
@@ -123,12 +336,14 @@ mod tests {
             to: State {
                 name: parse_quote! { Unlocked },
             },
+            weight: None,
         };
 
         let left = quote! {
             impl<E: Event> Transition<Push> for Machine<Locked, E> {
                 type Machine = Machine<Unlocked, Push>;
 
+                #[inline]
                 fn transition(self, event: Push) -> Self::Machine {
                     Machine(Unlocked, Some(event))
                 }
@@ -141,6 +356,70 @@ mod tests {
         assert_eq!(format!("{}", left), format!("{}", right))
     }
 
+    #[test]
+    fn test_transition_erased_tokens() {
+        let transition = Transition {
+            event: Event {
+                name: parse_quote! { Push },
+            },
+            from: State {
+                name: parse_quote! { Locked },
+            },
+            to: State {
+                name: parse_quote! { Unlocked },
+            },
+            weight: None,
+        };
+
+        let left = quote! {
+            impl Transition<Push> for Machine<Locked> {
+                type Machine = Machine<Unlocked>;
+
+                #[inline]
+                fn transition(self, _: Push) -> Self::Machine {
+                    Machine(Unlocked, Some(EventId::Push))
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        transition.erased_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
+    #[test]
+    fn test_transition_zero_sized_tokens() {
+        let transitions = Transitions(vec![Transition {
+            event: Event {
+                name: parse_quote! { Push },
+            },
+            from: State {
+                name: parse_quote! { Locked },
+            },
+            to: State {
+                name: parse_quote! { Unlocked },
+            },
+            weight: None,
+        }]);
+
+        let left = quote! {
+            impl<E: Event> Transition<Push> for Machine<Locked, E> {
+                type Machine = Machine<Unlocked, Push>;
+
+                #[inline]
+                fn transition(self, event: Push) -> Self::Machine {
+                    Machine(Unlocked, event)
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        transitions.zero_sized_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+
     #[test]
     fn test_transitions_parse() {
         let left: Transitions = syn::parse2(quote! {
@@ -159,6 +438,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Locked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -170,6 +450,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Locked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -181,6 +462,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Unlocked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -192,12 +474,94 @@ mod tests {
                 to: State {
                     name: parse_quote! { Unlocked },
                 },
+                weight: None,
             },
         ]);
 
         assert_eq!(left, right);
     }
 
+    #[test]
+    fn test_transitions_parse_state_blocks() {
+        let left: Transitions = syn::parse2(quote! {
+            state Locked {
+                on Coin => Unlocked;
+                on Push => Locked;
+            }
+
+            Push { Unlocked => Locked }
+        }).unwrap();
+
+        let right: Transitions = syn::parse2(quote! {
+            Coin { Locked => Unlocked }
+            Push { Locked => Locked }
+            Push { Unlocked => Locked }
+        }).unwrap();
+
+        assert_eq!(left, right);
+    }
+
+    #[test]
+    fn test_transitions_parse_state_entry() {
+        let transitions: Result<Transitions> = syn::parse2(quote! {
+            state Locked {
+                entry open();
+            }
+        });
+
+        assert!(transitions.is_err());
+    }
+
+    #[test]
+    fn test_transitions_parse_weights() {
+        let left: Transitions = syn::parse2(quote! {
+            Push { Locked, Unlocked => Locked @ 3 }
+
+            state Locked {
+                on Coin => Unlocked @ 0;
+            }
+        }).unwrap();
+
+        let weights: Vec<Option<u32>> = left.0.iter().map(|t| t.weight).collect();
+        assert_eq!(weights, vec![Some(3), Some(3), Some(0)]);
+
+        let transitions: Result<Transitions> = syn::parse2(quote! {
+            Push { Locked => Locked @ heavy }
+        });
+
+        assert!(transitions.is_err());
+    }
+
+    #[test]
+    fn test_transitions_doc() {
+        let transitions: Transitions = syn::parse2(quote! {
+            TurnKey { Locked => Unlocked }
+            Break { Locked, Unlocked => Broken }
+        }).unwrap();
+
+        assert_eq!(
+            transitions.doc(&parse_quote! { Locked }),
+            vec![
+                "Transitions:",
+                "",
+                "- `TurnKey` leads to `Unlocked`",
+                "- `Break` leads to `Broken`",
+            ]
+        );
+
+        assert_eq!(
+            transitions.doc(&parse_quote! { Broken }),
+            vec![
+                "Terminal: no event transitions out of this state.",
+                "",
+                "Reachable from:",
+                "",
+                "- `Locked` via `Break`",
+                "- `Unlocked` via `Break`",
+            ]
+        );
+    }
+
     #[test]
     fn test_transitions_to_tokens() {
         let transitions = Transitions(vec![
@@ -211,6 +575,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Locked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -222,6 +587,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Locked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -233,6 +599,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Unlocked },
                 },
+                weight: None,
             },
             Transition {
                 event: Event {
@@ -244,6 +611,7 @@ mod tests {
                 to: State {
                     name: parse_quote! { Unlocked },
                 },
+                weight: None,
             },
         ]);
 
@@ -251,6 +619,7 @@ mod tests {
             impl<E: Event> Transition<Push> for Machine<Locked, E> {
                 type Machine = Machine<Locked, Push>;
 
+                #[inline]
                 fn transition(self, event: Push) -> Self::Machine {
                     Machine(Locked, Some(event))
                 }
@@ -259,6 +628,7 @@ mod tests {
             impl<E: Event> Transition<Push> for Machine<Unlocked, E> {
                 type Machine = Machine<Locked, Push>;
 
+                #[inline]
                 fn transition(self, event: Push) -> Self::Machine {
                     Machine(Locked, Some(event))
                 }
@@ -267,6 +637,7 @@ mod tests {
             impl<E: Event> Transition<Coin> for Machine<Locked, E> {
                 type Machine = Machine<Unlocked, Coin>;
 
+                #[inline]
                 fn transition(self, event: Coin) -> Self::Machine {
                     Machine(Unlocked, Some(event))
                 }
@@ -275,6 +646,7 @@ mod tests {
             impl<E: Event> Transition<Coin> for Machine<Unlocked, E> {
                 type Machine = Machine<Unlocked, Coin>;
 
+                #[inline]
                 fn transition(self, event: Coin) -> Self::Machine {
                     Machine(Unlocked, Some(event))
                 }
@@ -0,0 +1,181 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::Ident;
+
+use crate::sm::machine::Machine;
+
+/// Ufmt generates [ufmt] `uDebug` and `uDisplay` implementations for the
+/// states, events, `StateId`, `EventId` and `Variant` types of a machine, so
+/// they can be written without pulling in `core::fmt`.
+///
+/// [ufmt]: https://docs.rs/ufmt
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Ufmt<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Ufmt<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let states: Vec<Ident> = self.machine.states().0.into_iter().map(|s| s.name).collect();
+        let events: Vec<Ident> = self.machine.events().0.into_iter().map(|e| e.name).collect();
+
+        for (id, names) in &[("StateId", &states), ("EventId", &events)] {
+            let id = Ident::new(id, Span::call_site());
+
+            for name in names.iter() {
+                let string: String = name.to_string();
+
+                tokens.extend(impls(name, quote! { f.write_str(#string) }));
+            }
+
+            tokens.extend(impls(&id, quote! { f.write_str(self.name()) }));
+        }
+
+        tokens.extend(quote! {
+            impl ::sm::export::ufmt::uDebug for Variant {
+                fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ::sm::export::ufmt::uWrite + ?Sized,
+                {
+                    ::sm::export::ufmt::uDebug::fmt(&self.state_id(), f)?;
+                    f.write_str("(")?;
+                    ::sm::export::ufmt::uDebug::fmt(&self.trigger_id(), f)?;
+                    f.write_str(")")
+                }
+            }
+
+            impl ::sm::export::ufmt::uDisplay for Variant {
+                fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ::sm::export::ufmt::uWrite + ?Sized,
+                {
+                    ::sm::export::ufmt::uDisplay::fmt(&self.state_id(), f)
+                }
+            }
+        });
+    }
+}
+
+/// impls implements `uDebug` for the named type with the provided body, and
+/// `uDisplay` on top of it, as states, events and identifiers are displayed
+/// by their name.
+fn impls(name: &Ident, body: TokenStream) -> TokenStream {
+    quote! {
+        impl ::sm::export::ufmt::uDebug for #name {
+            fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ::sm::export::ufmt::uWrite + ?Sized,
+            {
+                #body
+            }
+        }
+
+        impl ::sm::export::ufmt::uDisplay for #name {
+            fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+            where
+                W: ::sm::export::ufmt::uWrite + ?Sized,
+            {
+                ::sm::export::ufmt::uDebug::fmt(self, f)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_ufmt_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let mut left = TokenStream::new();
+        for (name, body) in &[
+            ("Locked", quote! { f.write_str("Locked") }),
+            ("Unlocked", quote! { f.write_str("Unlocked") }),
+            ("StateId", quote! { f.write_str(self.name()) }),
+            ("TurnKey", quote! { f.write_str("TurnKey") }),
+            ("EventId", quote! { f.write_str(self.name()) }),
+        ] {
+            let name = Ident::new(name, Span::call_site());
+
+            left.extend(quote! {
+                impl ::sm::export::ufmt::uDebug for #name {
+                    fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::sm::export::ufmt::uWrite + ?Sized,
+                    {
+                        #body
+                    }
+                }
+
+                impl ::sm::export::ufmt::uDisplay for #name {
+                    fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                    where
+                        W: ::sm::export::ufmt::uWrite + ?Sized,
+                    {
+                        ::sm::export::ufmt::uDebug::fmt(self, f)
+                    }
+                }
+            });
+        }
+
+        left.extend(quote! {
+            impl ::sm::export::ufmt::uDebug for Variant {
+                fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ::sm::export::ufmt::uWrite + ?Sized,
+                {
+                    ::sm::export::ufmt::uDebug::fmt(&self.state_id(), f)?;
+                    f.write_str("(")?;
+                    ::sm::export::ufmt::uDebug::fmt(&self.trigger_id(), f)?;
+                    f.write_str(")")
+                }
+            }
+
+            impl ::sm::export::ufmt::uDisplay for Variant {
+                fn fmt<W>(&self, f: &mut ::sm::export::ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+                where
+                    W: ::sm::export::ufmt::uWrite + ?Sized,
+                {
+                    ::sm::export::ufmt::uDisplay::fmt(&self.state_id(), f)
+                }
+            }
+        });
+
+        let mut right = TokenStream::new();
+        Ufmt { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -0,0 +1,183 @@
+use alloc::format;
+use alloc::string::String;
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+
+use crate::sm::machine::Machine;
+
+/// Wasm generates a [wasm-bindgen] class wrapping the `Variant` of a machine,
+/// exported to JavaScript under the name of the machine. States and events are
+/// identified by their names.
+///
+/// [wasm-bindgen]: https://docs.rs/wasm-bindgen
+#[derive(Debug)]
+#[allow(single_use_lifetimes)]
+pub(crate) struct Wasm<'a> {
+    pub machine: &'a Machine,
+}
+
+#[allow(single_use_lifetimes)]
+impl<'a> ToTokens for Wasm<'a> {
+    fn to_tokens(&self, tokens: &mut TokenStream) {
+        let name = &self.machine.name;
+        let not_initial: String = format!("not an initial state of {}", name);
+        let not_event: String = format!("not an event of {}", name);
+
+        // Parts of the code generated by wasm-bindgen refer to the crate by
+        // its bare name.
+        tokens.extend(quote! {
+            use ::sm::export::wasm_bindgen;
+
+            #[::sm::export::wasm_bindgen::prelude::wasm_bindgen(js_name = #name, wasm_bindgen = ::sm::export::wasm_bindgen)]
+            #[derive(Debug)]
+            pub struct JsMachine {
+                machine: Variant,
+            }
+
+            #[::sm::export::wasm_bindgen::prelude::wasm_bindgen(js_class = #name, wasm_bindgen = ::sm::export::wasm_bindgen)]
+            impl JsMachine {
+                #[wasm_bindgen(constructor)]
+                pub fn new(initial: &str) -> Result<JsMachine, ::sm::export::wasm_bindgen::JsValue> {
+                    StateId::from_name(initial)
+                        .and_then(|state| Variant::from_ids(state, Option::None))
+                        .map(|machine| JsMachine { machine })
+                        .ok_or_else(|| ::sm::export::wasm_bindgen::JsValue::from_str(#not_initial))
+                }
+
+                pub fn apply(&mut self, event: &str) -> Result<(), ::sm::export::wasm_bindgen::JsValue> {
+                    let event = EventId::from_name(event)
+                        .ok_or_else(|| ::sm::export::wasm_bindgen::JsValue::from_str(#not_event))?;
+
+                    match self.machine.clone().try_transition(event) {
+                        Result::Ok(machine) => {
+                            self.machine = machine;
+                            Result::Ok(())
+                        }
+                        Result::Err(_) => Result::Err(::sm::export::wasm_bindgen::JsValue::from_str(
+                            "event not permitted in the current state",
+                        )),
+                    }
+                }
+
+                pub fn state(&self) -> String {
+                    self.machine.state_id().name().into()
+                }
+
+                #[wasm_bindgen(js_name = permittedEvents)]
+                pub fn permitted_events(&self) -> Vec<String> {
+                    let state = self.machine.state_id();
+
+                    EventId::ALL
+                        .iter()
+                        .filter(|event| Variant::next(state, **event).is_some())
+                        .map(|event| event.name().into())
+                        .collect()
+                }
+            }
+
+            impl JsMachine {
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{format, vec};
+    use crate::sm::event::Event;
+    use crate::sm::initial_state::{InitialState, InitialStates};
+    use crate::sm::state::State;
+    use crate::sm::transition::{Transition, Transitions};
+    use proc_macro2::TokenStream;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_wasm_to_tokens() {
+        let machine = Machine {
+            name: parse_quote! { Lock },
+            attributes: Default::default(),
+            externals: Default::default(),
+            initial_states: InitialStates(vec![InitialState {
+                name: parse_quote! { Locked },
+            }]),
+            transitions: Transitions(vec![Transition {
+                event: Event {
+                    name: parse_quote! { TurnKey },
+                },
+                from: State {
+                    name: parse_quote! { Locked },
+                },
+                to: State {
+                    name: parse_quote! { Unlocked },
+                },
+                weight: None,
+            }]),
+        };
+
+        let left = quote! {
+            use ::sm::export::wasm_bindgen;
+
+            #[::sm::export::wasm_bindgen::prelude::wasm_bindgen(js_name = Lock, wasm_bindgen = ::sm::export::wasm_bindgen)]
+            #[derive(Debug)]
+            pub struct JsMachine {
+                machine: Variant,
+            }
+
+            #[::sm::export::wasm_bindgen::prelude::wasm_bindgen(js_class = Lock, wasm_bindgen = ::sm::export::wasm_bindgen)]
+            impl JsMachine {
+                #[wasm_bindgen(constructor)]
+                pub fn new(initial: &str) -> Result<JsMachine, ::sm::export::wasm_bindgen::JsValue> {
+                    StateId::from_name(initial)
+                        .and_then(|state| Variant::from_ids(state, Option::None))
+                        .map(|machine| JsMachine { machine })
+                        .ok_or_else(|| ::sm::export::wasm_bindgen::JsValue::from_str("not an initial state of Lock"))
+                }
+
+                pub fn apply(&mut self, event: &str) -> Result<(), ::sm::export::wasm_bindgen::JsValue> {
+                    let event = EventId::from_name(event)
+                        .ok_or_else(|| ::sm::export::wasm_bindgen::JsValue::from_str("not an event of Lock"))?;
+
+                    match self.machine.clone().try_transition(event) {
+                        Result::Ok(machine) => {
+                            self.machine = machine;
+                            Result::Ok(())
+                        }
+                        Result::Err(_) => Result::Err(::sm::export::wasm_bindgen::JsValue::from_str(
+                            "event not permitted in the current state",
+                        )),
+                    }
+                }
+
+                pub fn state(&self) -> String {
+                    self.machine.state_id().name().into()
+                }
+
+                #[wasm_bindgen(js_name = permittedEvents)]
+                pub fn permitted_events(&self) -> Vec<String> {
+                    let state = self.machine.state_id();
+
+                    EventId::ALL
+                        .iter()
+                        .filter(|event| Variant::next(state, **event).is_some())
+                        .map(|event| event.name().into())
+                        .collect()
+                }
+            }
+
+            impl JsMachine {
+                pub fn machine(&self) -> &Variant {
+                    &self.machine
+                }
+            }
+        };
+
+        let mut right = TokenStream::new();
+        Wasm { machine: &machine }.to_tokens(&mut right);
+
+        assert_eq!(format!("{}", left), format!("{}", right))
+    }
+}
@@ -1,3 +0,0 @@
-#![feature(/*==========================================]
-#![===  SM test suite requires a nightly compiler.  ===]
-#![=================================================*/)]
@@ -6,10 +6,11 @@ sm!{
         InitialStates { Locked, Unlocked }
 
         TurnKey {
-        //~^ ERROR unexpected end of input, expected identifier
             Locked => Unlocked,
             // TODO: support both with and without comma separation?
             Unlocked => Locked,
         }
     }
 }
+
+fn main() {}
@@ -3,5 +3,6 @@ use sm::sm;
 
 sm!{
     TurnStile {}
-    //~^ ERROR unexpected end of input, expected identifier
 }
+
+fn main() {}
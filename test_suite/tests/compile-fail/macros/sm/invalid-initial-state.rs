@@ -13,5 +13,4 @@ fn main() {
     use Lock::*;
 
     let _sm = Machine::new(Unlocked);
-    //~^ ERROR the trait bound `Lock::Unlocked: sm::InitialState` is not satisfied
 }
@@ -4,6 +4,7 @@ use sm::sm;
 sm!{
     Lock {
         InvalidName { Locked, Unlocked }
-        //~^ ERROR expected `InitialStates { ... }` block
     }
 }
+
+fn main() {}
@@ -14,5 +14,4 @@ fn main() {
 
     let sm = Machine::new(Locked);
     sm = sm.transition(TurnKey)
-    //~^ ERROR mismatched types
 }
@@ -13,5 +13,4 @@ fn main() {
     use Lock::*;
 
     let _sm = Machine(Locked);
-    //~^ ERROR expected function, found struct `Machine`
 }
@@ -1,25 +1,13 @@
-#![cfg(feature = "unstable")]
+extern crate trybuild;
 
-extern crate compiletest_rs as compiletest;
-
-use std::env;
-
-fn run_mode(mode: &'static str) {
-    let mut config = compiletest::Config::default();
-
-    config.mode = mode.parse().expect("invalid mode");
-    config.target_rustcflags = Some("-L deps/target/debug/deps".to_owned());
-    if let Ok(name) = env::var("TESTNAME") {
-        config.filter = Some(name);
-    }
-    config.src_base = format!("tests/{}", mode).into();
-    config.clean_rmeta();
-
-    compiletest::run_tests(&config);
+#[test]
+fn compile_fail() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/compile-fail/derives/*.rs");
+    t.compile_fail("tests/compile-fail/macros/sm/*.rs");
 }
 
 #[test]
-fn compile_fail() {
-    run_mode("compile-fail");
-    run_mode("run-pass");
+fn run_pass() {
+    trybuild::TestCases::new().pass("tests/run-pass/macros/sm/*.rs");
 }